@@ -0,0 +1,45 @@
+-- Schema base (PostgreSQL). Timestamps em TEXT RFC3339, como no SQLite,
+-- para que as mesmas queries funcionem nos dois bancos.
+
+-- Usuários (Clientes e Fornecedores)
+CREATE TABLE IF NOT EXISTS users (
+    id TEXT PRIMARY KEY,
+    username TEXT UNIQUE NOT NULL,
+    stellar_public_key TEXT UNIQUE NOT NULL,
+    stellar_secret_key TEXT NOT NULL,
+    user_type TEXT NOT NULL CHECK (user_type IN ('client', 'supplier')),
+    created_at TEXT NOT NULL
+);
+
+-- Processos com informações confidenciais
+CREATE TABLE IF NOT EXISTS processes (
+    id TEXT PRIMARY KEY,
+    client_id TEXT NOT NULL REFERENCES users (id),
+    title TEXT NOT NULL,
+    encrypted_content TEXT NOT NULL,
+    encryption_key TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'active',
+    created_at TEXT NOT NULL
+);
+
+-- Compartilhamentos de processos via Stellar
+CREATE TABLE IF NOT EXISTS process_shares (
+    id TEXT PRIMARY KEY,
+    process_id TEXT NOT NULL REFERENCES processes (id),
+    supplier_public_key TEXT NOT NULL,
+    stellar_transaction_hash TEXT NOT NULL,
+    shared_at TEXT NOT NULL
+);
+
+-- Acessos aos processos (auditoria)
+CREATE TABLE IF NOT EXISTS process_accesses (
+    id TEXT PRIMARY KEY,
+    process_id TEXT NOT NULL REFERENCES processes (id),
+    supplier_id TEXT NOT NULL REFERENCES users (id),
+    accessed_at TEXT NOT NULL
+);
+
+-- Índices para performance
+CREATE INDEX IF NOT EXISTS idx_processes_client_id ON processes (client_id);
+CREATE INDEX IF NOT EXISTS idx_process_shares_process_id ON process_shares (process_id);
+CREATE INDEX IF NOT EXISTS idx_process_accesses_process_id ON process_accesses (process_id);
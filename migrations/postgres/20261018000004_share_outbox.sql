@@ -0,0 +1,24 @@
+-- Compartilhamentos passam a ser criados como 'pending' e confirmados
+-- depois que a transação é registrada no ledger.
+ALTER TABLE process_shares ALTER COLUMN stellar_transaction_hash DROP NOT NULL;
+ALTER TABLE process_shares ADD COLUMN status TEXT NOT NULL DEFAULT 'confirmed'
+    CHECK (status IN ('pending', 'confirmed', 'failed'));
+ALTER TABLE process_shares ADD COLUMN confirmed_at TEXT;
+
+-- Compartilhamentos existentes já foram registrados no ledger
+UPDATE process_shares SET confirmed_at = shared_at;
+ALTER TABLE process_shares ALTER COLUMN status SET DEFAULT 'pending';
+
+-- Outbox: pedidos de registro no ledger, gravados na mesma transação do compartilhamento
+CREATE TABLE IF NOT EXISTS ledger_outbox (
+    id TEXT PRIMARY KEY,
+    share_id TEXT NOT NULL UNIQUE REFERENCES process_shares (id),
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'done', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TEXT NOT NULL,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    processed_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_outbox_due ON ledger_outbox (status, next_attempt_at);
@@ -0,0 +1,13 @@
+-- Bloqueio de usuários pela administração (nda-admin users lock)
+ALTER TABLE users ADD COLUMN locked_at TEXT;
+
+-- Compartilhamentos podem ser revogados
+ALTER TABLE process_shares DROP CONSTRAINT process_shares_status_check;
+ALTER TABLE process_shares ADD CONSTRAINT process_shares_status_check
+    CHECK (status IN ('pending', 'confirmed', 'failed', 'revoked'));
+ALTER TABLE process_shares ADD COLUMN revoked_at TEXT;
+
+-- Revogados, como os que falharam, não impedem um novo compartilhamento
+DROP INDEX IF EXISTS idx_process_shares_active;
+CREATE UNIQUE INDEX idx_process_shares_active
+    ON process_shares (process_id, supplier_public_key) WHERE status IN ('pending', 'confirmed');
@@ -0,0 +1,3 @@
+-- Busca textual nos títulos dos processos. Índice por expressão (e não coluna tsvector)
+-- para manter SELECT * legível pelo driver Any; as consultas usam a mesma expressão.
+CREATE INDEX IF NOT EXISTS idx_processes_search ON processes USING GIN (to_tsvector('simple', title));
@@ -0,0 +1,33 @@
+-- Metadados não confidenciais dos processos (não criptografados): organização por projeto e busca
+CREATE TABLE IF NOT EXISTS process_metadata (
+    process_id TEXT PRIMARY KEY REFERENCES processes (id),
+    category TEXT,
+    counterparty_reference TEXT,
+    project_code TEXT,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_process_metadata_category ON process_metadata (category);
+CREATE INDEX IF NOT EXISTS idx_process_metadata_project_code ON process_metadata (project_code);
+
+CREATE TABLE IF NOT EXISTS process_tags (
+    process_id TEXT NOT NULL REFERENCES processes (id),
+    tag TEXT NOT NULL,
+    PRIMARY KEY (process_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_process_tags_tag ON process_tags (tag);
+
+CREATE TABLE IF NOT EXISTS process_custom_fields (
+    process_id TEXT NOT NULL REFERENCES processes (id),
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (process_id, name)
+);
+
+-- Texto dos metadados para a busca, regravado junto com as tabelas acima
+ALTER TABLE processes ADD COLUMN IF NOT EXISTS search_metadata TEXT NOT NULL DEFAULT '';
+
+-- O índice de busca passa a cobrir os metadados (mesma expressão das consultas)
+DROP INDEX IF EXISTS idx_processes_search;
+CREATE INDEX idx_processes_search ON processes USING GIN (to_tsvector('simple', title || ' ' || search_metadata));
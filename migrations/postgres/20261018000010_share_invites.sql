@@ -0,0 +1,21 @@
+-- Convites por e-mail para fornecedores ainda não cadastrados; viram
+-- compartilhamentos quando o fornecedor se cadastra com o token
+CREATE TABLE IF NOT EXISTS share_invites (
+    id TEXT PRIMARY KEY,
+    process_id TEXT NOT NULL REFERENCES processes (id),
+    client_id TEXT NOT NULL REFERENCES users (id),
+    email TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'accepted', 'expired')),
+    share_expires_at TEXT,
+    expires_at TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    accepted_at TEXT,
+    share_id TEXT REFERENCES process_shares (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_share_invites_email ON share_invites (email, status);
+
+-- Um convite pendente por processo e e-mail
+CREATE UNIQUE INDEX IF NOT EXISTS idx_share_invites_pending
+    ON share_invites (process_id, email) WHERE status = 'pending';
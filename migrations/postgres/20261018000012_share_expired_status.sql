@@ -0,0 +1,5 @@
+-- Compartilhamentos vencidos passam a 'expired' quando o processo é compartilhado
+-- de novo com o fornecedor; como revogados e falhos, ficam fora de idx_process_shares_active
+ALTER TABLE process_shares DROP CONSTRAINT process_shares_status_check;
+ALTER TABLE process_shares ADD CONSTRAINT process_shares_status_check
+    CHECK (status IN ('pending', 'confirmed', 'failed', 'revoked', 'expired'));
@@ -0,0 +1,4 @@
+-- Chave de submissão (memo da transação), gravada antes do envio ao ledger.
+-- Numa nova tentativa o worker procura a chave no ledger e só reenvia se
+-- nada foi registrado com ela. Entradas de um lote compartilham a chave.
+ALTER TABLE ledger_outbox ADD COLUMN submission_key TEXT;
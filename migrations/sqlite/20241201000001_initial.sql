@@ -1,46 +1,49 @@
+-- Schema base. IF NOT EXISTS permite adotar bancos legados criados antes
+-- das migrações versionadas (tabelas já existentes são mantidas como estão).
+
 -- Usuários (Clientes e Fornecedores)
-CREATE TABLE users (
+CREATE TABLE IF NOT EXISTS users (
     id TEXT PRIMARY KEY,
     username TEXT UNIQUE NOT NULL,
     stellar_public_key TEXT UNIQUE NOT NULL,
     stellar_secret_key TEXT NOT NULL,
     user_type TEXT NOT NULL CHECK (user_type IN ('client', 'supplier')),
-    created_at DATETIME NOT NULL
+    created_at TEXT NOT NULL
 );
 
 -- Processos com informações confidenciais
-CREATE TABLE processes (
+CREATE TABLE IF NOT EXISTS processes (
     id TEXT PRIMARY KEY,
     client_id TEXT NOT NULL,
     title TEXT NOT NULL,
     encrypted_content TEXT NOT NULL,
     encryption_key TEXT NOT NULL,
     status TEXT NOT NULL DEFAULT 'active',
-    created_at DATETIME NOT NULL,
+    created_at TEXT NOT NULL,
     FOREIGN KEY (client_id) REFERENCES users (id)
 );
 
 -- Compartilhamentos de processos via Stellar
-CREATE TABLE process_shares (
+CREATE TABLE IF NOT EXISTS process_shares (
     id TEXT PRIMARY KEY,
     process_id TEXT NOT NULL,
     supplier_public_key TEXT NOT NULL,
     stellar_transaction_hash TEXT NOT NULL,
-    shared_at DATETIME NOT NULL,
+    shared_at TEXT NOT NULL,
     FOREIGN KEY (process_id) REFERENCES processes (id)
 );
 
--- Acessos aos processos (para notificações)
-CREATE TABLE process_accesses (
+-- Acessos aos processos (auditoria)
+CREATE TABLE IF NOT EXISTS process_accesses (
     id TEXT PRIMARY KEY,
     process_id TEXT NOT NULL,
     supplier_id TEXT NOT NULL,
-    accessed_at DATETIME NOT NULL,
+    accessed_at TEXT NOT NULL,
     FOREIGN KEY (process_id) REFERENCES processes (id),
     FOREIGN KEY (supplier_id) REFERENCES users (id)
 );
 
 -- Índices para performance
-CREATE INDEX idx_processes_client_id ON processes (client_id);
-CREATE INDEX idx_process_shares_process_id ON process_shares (process_id);
-CREATE INDEX idx_process_accesses_process_id ON process_accesses (process_id);
\ No newline at end of file
+CREATE INDEX IF NOT EXISTS idx_processes_client_id ON processes (client_id);
+CREATE INDEX IF NOT EXISTS idx_process_shares_process_id ON process_shares (process_id);
+CREATE INDEX IF NOT EXISTS idx_process_accesses_process_id ON process_accesses (process_id);
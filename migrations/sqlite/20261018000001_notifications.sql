@@ -0,0 +1,26 @@
+-- Notificações tipadas com estado de leitura por destinatário
+CREATE TABLE IF NOT EXISTS notifications (
+    id TEXT PRIMARY KEY,
+    recipient_id TEXT NOT NULL,
+    event_type TEXT NOT NULL CHECK (event_type IN ('accessed', 'accepted', 'share_expired', 'revoked', 'denied', 'granted')),
+    process_id TEXT NOT NULL,
+    actor_id TEXT,
+    message TEXT NOT NULL,
+    is_read INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    read_at TEXT,
+    FOREIGN KEY (recipient_id) REFERENCES users (id),
+    FOREIGN KEY (process_id) REFERENCES processes (id),
+    FOREIGN KEY (actor_id) REFERENCES users (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_recipient ON notifications (recipient_id, is_read, created_at);
+
+-- Acessos anteriores viram notificações do cliente dono do processo
+INSERT OR IGNORE INTO notifications (id, recipient_id, event_type, process_id, actor_id, message, is_read, created_at)
+SELECT pa.id, p.client_id, 'accessed', pa.process_id, pa.supplier_id,
+       'Fornecedor ' || u.username || ' acessou o processo ''' || p.title || '''',
+       0, pa.accessed_at
+FROM process_accesses pa
+JOIN processes p ON pa.process_id = p.id
+JOIN users u ON pa.supplier_id = u.id;
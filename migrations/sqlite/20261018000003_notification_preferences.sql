@@ -0,0 +1,10 @@
+-- Preferências de notificação por e-mail
+CREATE TABLE IF NOT EXISTS notification_preferences (
+    user_id TEXT PRIMARY KEY,
+    email TEXT,
+    email_enabled INTEGER NOT NULL DEFAULT 1,
+    language TEXT NOT NULL DEFAULT 'pt' CHECK (language IN ('pt', 'en')),
+    email_events TEXT NOT NULL DEFAULT 'accessed,granted',
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users (id)
+);
@@ -0,0 +1,38 @@
+-- Compartilhamentos passam a ser criados como 'pending' e confirmados
+-- depois que a transação é registrada no ledger. SQLite não altera
+-- restrições de coluna, então a tabela é recriada.
+CREATE TABLE process_shares_new (
+    id TEXT PRIMARY KEY,
+    process_id TEXT NOT NULL,
+    supplier_public_key TEXT NOT NULL,
+    stellar_transaction_hash TEXT,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'confirmed', 'failed')),
+    shared_at TEXT NOT NULL,
+    confirmed_at TEXT,
+    FOREIGN KEY (process_id) REFERENCES processes (id)
+);
+
+-- Compartilhamentos existentes já foram registrados no ledger
+INSERT INTO process_shares_new (id, process_id, supplier_public_key, stellar_transaction_hash, status, shared_at, confirmed_at)
+SELECT id, process_id, supplier_public_key, stellar_transaction_hash, 'confirmed', shared_at, shared_at
+FROM process_shares;
+
+DROP TABLE process_shares;
+ALTER TABLE process_shares_new RENAME TO process_shares;
+
+CREATE INDEX IF NOT EXISTS idx_process_shares_process_id ON process_shares (process_id);
+
+-- Outbox: pedidos de registro no ledger, gravados na mesma transação do compartilhamento
+CREATE TABLE IF NOT EXISTS ledger_outbox (
+    id TEXT PRIMARY KEY,
+    share_id TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'done', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TEXT NOT NULL,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    processed_at TEXT,
+    FOREIGN KEY (share_id) REFERENCES process_shares (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_outbox_due ON ledger_outbox (status, next_attempt_at);
@@ -0,0 +1,28 @@
+-- Respostas armazenadas por Idempotency-Key, reproduzidas em repetições dentro do TTL.
+-- status_code NULL indica requisição original ainda em andamento.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    scope TEXT NOT NULL,
+    idempotency_key TEXT NOT NULL,
+    request_hash TEXT NOT NULL,
+    status_code INTEGER,
+    response_body TEXT,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (scope, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created_at ON idempotency_keys (created_at);
+
+-- No máximo um compartilhamento ativo (pendente ou confirmado) por processo e fornecedor.
+-- Duplicatas já existentes são mantidas como histórico, marcadas como 'failed'.
+UPDATE process_shares SET status = 'failed'
+WHERE status <> 'failed' AND EXISTS (
+    SELECT 1 FROM process_shares older
+    WHERE older.process_id = process_shares.process_id
+      AND older.supplier_public_key = process_shares.supplier_public_key
+      AND older.status <> 'failed'
+      AND (older.shared_at < process_shares.shared_at
+           OR (older.shared_at = process_shares.shared_at AND older.id < process_shares.id))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_process_shares_active
+    ON process_shares (process_id, supplier_public_key) WHERE status <> 'failed';
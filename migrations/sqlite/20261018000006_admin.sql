@@ -0,0 +1,53 @@
+-- Bloqueio de usuários pela administração (nda-admin users lock)
+ALTER TABLE users ADD COLUMN locked_at TEXT;
+
+-- Compartilhamentos podem ser revogados. SQLite não altera restrições de coluna,
+-- então a tabela é recriada; ledger_outbox, que a referencia, é recriada junto
+-- (as chaves estrangeiras continuam ativas durante a migração).
+CREATE TABLE process_shares_backup AS SELECT * FROM process_shares;
+CREATE TABLE ledger_outbox_backup AS SELECT * FROM ledger_outbox;
+
+DROP TABLE ledger_outbox;
+DROP TABLE process_shares;
+
+CREATE TABLE process_shares (
+    id TEXT PRIMARY KEY,
+    process_id TEXT NOT NULL,
+    supplier_public_key TEXT NOT NULL,
+    stellar_transaction_hash TEXT,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'confirmed', 'failed', 'revoked')),
+    shared_at TEXT NOT NULL,
+    confirmed_at TEXT,
+    revoked_at TEXT,
+    FOREIGN KEY (process_id) REFERENCES processes (id)
+);
+
+INSERT INTO process_shares (id, process_id, supplier_public_key, stellar_transaction_hash, status, shared_at, confirmed_at)
+SELECT id, process_id, supplier_public_key, stellar_transaction_hash, status, shared_at, confirmed_at
+FROM process_shares_backup;
+
+CREATE TABLE ledger_outbox (
+    id TEXT PRIMARY KEY,
+    share_id TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'done', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TEXT NOT NULL,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    processed_at TEXT,
+    FOREIGN KEY (share_id) REFERENCES process_shares (id)
+);
+
+INSERT INTO ledger_outbox (id, share_id, status, attempts, next_attempt_at, last_error, created_at, processed_at)
+SELECT id, share_id, status, attempts, next_attempt_at, last_error, created_at, processed_at
+FROM ledger_outbox_backup;
+
+DROP TABLE ledger_outbox_backup;
+DROP TABLE process_shares_backup;
+
+CREATE INDEX IF NOT EXISTS idx_process_shares_process_id ON process_shares (process_id);
+CREATE INDEX IF NOT EXISTS idx_ledger_outbox_due ON ledger_outbox (status, next_attempt_at);
+
+-- Revogados, como os que falharam, não impedem um novo compartilhamento
+CREATE UNIQUE INDEX IF NOT EXISTS idx_process_shares_active
+    ON process_shares (process_id, supplier_public_key) WHERE status IN ('pending', 'confirmed');
@@ -0,0 +1,17 @@
+-- Índice de busca textual dos processos (FTS5). Tabela própria, mantida por triggers:
+-- processes não tem rowid estável (chave TEXT), então o vínculo é pelo id.
+CREATE VIRTUAL TABLE IF NOT EXISTS processes_fts USING fts5(process_id UNINDEXED, title);
+
+CREATE TRIGGER IF NOT EXISTS processes_fts_insert AFTER INSERT ON processes BEGIN
+    INSERT INTO processes_fts (process_id, title) VALUES (new.id, new.title);
+END;
+
+CREATE TRIGGER IF NOT EXISTS processes_fts_update AFTER UPDATE OF title ON processes BEGIN
+    UPDATE processes_fts SET title = new.title WHERE process_id = new.id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS processes_fts_delete AFTER DELETE ON processes BEGIN
+    DELETE FROM processes_fts WHERE process_id = old.id;
+END;
+
+INSERT INTO processes_fts (process_id, title) SELECT id, title FROM processes;
@@ -0,0 +1,51 @@
+-- Metadados não confidenciais dos processos (não criptografados): organização por projeto e busca
+CREATE TABLE IF NOT EXISTS process_metadata (
+    process_id TEXT PRIMARY KEY REFERENCES processes (id),
+    category TEXT,
+    counterparty_reference TEXT,
+    project_code TEXT,
+    updated_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_process_metadata_category ON process_metadata (category);
+CREATE INDEX IF NOT EXISTS idx_process_metadata_project_code ON process_metadata (project_code);
+
+CREATE TABLE IF NOT EXISTS process_tags (
+    process_id TEXT NOT NULL REFERENCES processes (id),
+    tag TEXT NOT NULL,
+    PRIMARY KEY (process_id, tag)
+);
+
+CREATE INDEX IF NOT EXISTS idx_process_tags_tag ON process_tags (tag);
+
+CREATE TABLE IF NOT EXISTS process_custom_fields (
+    process_id TEXT NOT NULL REFERENCES processes (id),
+    name TEXT NOT NULL,
+    value TEXT NOT NULL,
+    PRIMARY KEY (process_id, name)
+);
+
+-- Texto dos metadados para a busca, regravado junto com as tabelas acima
+ALTER TABLE processes ADD COLUMN search_metadata TEXT NOT NULL DEFAULT '';
+
+-- O índice de busca passa a cobrir os metadados
+DROP TRIGGER IF EXISTS processes_fts_insert;
+DROP TRIGGER IF EXISTS processes_fts_update;
+DROP TRIGGER IF EXISTS processes_fts_delete;
+DROP TABLE IF EXISTS processes_fts;
+
+CREATE VIRTUAL TABLE processes_fts USING fts5(process_id UNINDEXED, title, metadata);
+
+CREATE TRIGGER processes_fts_insert AFTER INSERT ON processes BEGIN
+    INSERT INTO processes_fts (process_id, title, metadata) VALUES (new.id, new.title, new.search_metadata);
+END;
+
+CREATE TRIGGER processes_fts_update AFTER UPDATE OF title, search_metadata ON processes BEGIN
+    UPDATE processes_fts SET title = new.title, metadata = new.search_metadata WHERE process_id = new.id;
+END;
+
+CREATE TRIGGER processes_fts_delete AFTER DELETE ON processes BEGIN
+    DELETE FROM processes_fts WHERE process_id = old.id;
+END;
+
+INSERT INTO processes_fts (process_id, title, metadata) SELECT id, title, search_metadata FROM processes;
@@ -0,0 +1,6 @@
+-- Aceite do NDA pelo fornecedor e validade opcional do compartilhamento
+ALTER TABLE process_shares ADD COLUMN accepted_at TEXT;
+ALTER TABLE process_shares ADD COLUMN expires_at TEXT;
+
+-- Listagem do lado do fornecedor (GET /api/supplier/processes)
+CREATE INDEX IF NOT EXISTS idx_process_shares_supplier ON process_shares (supplier_public_key, shared_at);
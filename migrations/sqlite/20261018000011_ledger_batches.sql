@@ -0,0 +1,5 @@
+-- Compartilhamentos em lote: entradas com o mesmo batch_id são registradas
+-- no ledger numa única transação Stellar (até 100 operações)
+ALTER TABLE ledger_outbox ADD COLUMN batch_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_ledger_outbox_batch ON ledger_outbox (batch_id);
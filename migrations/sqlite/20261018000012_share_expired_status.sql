@@ -0,0 +1,87 @@
+-- Compartilhamentos vencidos passam a 'expired' quando o processo é compartilhado
+-- de novo com o fornecedor; como revogados e falhos, não ocupam o índice de ativos.
+-- SQLite não altera restrições de coluna: process_shares é recriada junto com
+-- ledger_outbox e share_invites, que a referenciam.
+CREATE TABLE process_shares_backup AS SELECT * FROM process_shares;
+CREATE TABLE ledger_outbox_backup AS SELECT * FROM ledger_outbox;
+CREATE TABLE share_invites_backup AS SELECT * FROM share_invites;
+
+DROP TABLE share_invites;
+DROP TABLE ledger_outbox;
+DROP TABLE process_shares;
+
+CREATE TABLE process_shares (
+    id TEXT PRIMARY KEY,
+    process_id TEXT NOT NULL,
+    supplier_public_key TEXT NOT NULL,
+    stellar_transaction_hash TEXT,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'confirmed', 'failed', 'revoked', 'expired')),
+    shared_at TEXT NOT NULL,
+    confirmed_at TEXT,
+    revoked_at TEXT,
+    accepted_at TEXT,
+    expires_at TEXT,
+    FOREIGN KEY (process_id) REFERENCES processes (id)
+);
+
+INSERT INTO process_shares (
+    id, process_id, supplier_public_key, stellar_transaction_hash, status, shared_at,
+    confirmed_at, revoked_at, accepted_at, expires_at
+)
+SELECT id, process_id, supplier_public_key, stellar_transaction_hash, status, shared_at,
+       confirmed_at, revoked_at, accepted_at, expires_at
+FROM process_shares_backup;
+
+CREATE TABLE ledger_outbox (
+    id TEXT PRIMARY KEY,
+    share_id TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'done', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TEXT NOT NULL,
+    last_error TEXT,
+    created_at TEXT NOT NULL,
+    processed_at TEXT,
+    batch_id TEXT,
+    FOREIGN KEY (share_id) REFERENCES process_shares (id)
+);
+
+INSERT INTO ledger_outbox (id, share_id, status, attempts, next_attempt_at, last_error, created_at, processed_at, batch_id)
+SELECT id, share_id, status, attempts, next_attempt_at, last_error, created_at, processed_at, batch_id
+FROM ledger_outbox_backup;
+
+CREATE TABLE share_invites (
+    id TEXT PRIMARY KEY,
+    process_id TEXT NOT NULL,
+    client_id TEXT NOT NULL,
+    email TEXT NOT NULL,
+    token_hash TEXT NOT NULL UNIQUE,
+    status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'accepted', 'expired')),
+    share_expires_at TEXT,
+    expires_at TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    accepted_at TEXT,
+    share_id TEXT,
+    FOREIGN KEY (process_id) REFERENCES processes (id),
+    FOREIGN KEY (client_id) REFERENCES users (id),
+    FOREIGN KEY (share_id) REFERENCES process_shares (id)
+);
+
+INSERT INTO share_invites (
+    id, process_id, client_id, email, token_hash, status, share_expires_at, expires_at, created_at, accepted_at, share_id
+)
+SELECT id, process_id, client_id, email, token_hash, status, share_expires_at, expires_at, created_at, accepted_at, share_id
+FROM share_invites_backup;
+
+DROP TABLE share_invites_backup;
+DROP TABLE ledger_outbox_backup;
+DROP TABLE process_shares_backup;
+
+CREATE INDEX IF NOT EXISTS idx_process_shares_process_id ON process_shares (process_id);
+CREATE INDEX IF NOT EXISTS idx_process_shares_supplier ON process_shares (supplier_public_key, shared_at);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_process_shares_active
+    ON process_shares (process_id, supplier_public_key) WHERE status IN ('pending', 'confirmed');
+CREATE INDEX IF NOT EXISTS idx_ledger_outbox_due ON ledger_outbox (status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS idx_ledger_outbox_batch ON ledger_outbox (batch_id);
+CREATE INDEX IF NOT EXISTS idx_share_invites_email ON share_invites (email, status);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_share_invites_pending
+    ON share_invites (process_id, email) WHERE status = 'pending';
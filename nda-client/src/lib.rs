@@ -0,0 +1,741 @@
+// nda-client/src/lib.rs
+// Cliente tipado para a API do NDA Backend, com os modelos de src/models.rs
+use chrono::{DateTime, Utc};
+use reqwest::{header, Method, RequestBuilder, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::fmt;
+
+pub use nda_models as models;
+pub use nda_models::{ProcessSort, ReadStatus, SortOrder};
+use models::*;
+
+/// Erro retornado pela API (corpo padrão { "error": { code, message, request_id } })
+#[derive(Debug, Clone)]
+pub struct ApiFailure {
+    pub status: StatusCode,
+    pub code: String,
+    pub message: String,
+    pub request_id: Option<String>,
+    pub retry_after: Option<u64>, // segundos, em respostas 429
+}
+
+#[derive(Debug)]
+pub enum ClientError {
+    Api(ApiFailure),
+    Http(reqwest::Error), // conexão, timeout ou resposta em formato inesperado
+    Decode(serde_json::Error), // evento do stream ou corpo de /health/ready fora do formato esperado
+    InvalidUrl(String),
+}
+
+impl ClientError {
+    /// Código estável do erro da API (ex.: "user_not_found"), se houver
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            ClientError::Api(failure) => Some(&failure.code),
+            _ => None,
+        }
+    }
+
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            ClientError::Api(failure) => Some(failure.status),
+            ClientError::Http(e) => e.status(),
+            ClientError::Decode(_) | ClientError::InvalidUrl(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClientError::Api(failure) => write!(f, "{} ({}): {}", failure.status, failure.code, failure.message),
+            ClientError::Http(e) => write!(f, "Erro HTTP: {}", e),
+            ClientError::Decode(e) => write!(f, "Resposta em formato inesperado: {}", e),
+            ClientError::InvalidUrl(url) => write!(f, "URL inválida: {}", url),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(error: reqwest::Error) -> Self {
+        ClientError::Http(error)
+    }
+}
+
+impl From<serde_json::Error> for ClientError {
+    fn from(error: serde_json::Error) -> Self {
+        ClientError::Decode(error)
+    }
+}
+
+#[derive(Deserialize)]
+struct ErrorEnvelope {
+    error: ErrorPayload,
+}
+
+#[derive(Deserialize)]
+struct ErrorPayload {
+    code: String,
+    message: String,
+    request_id: Option<String>,
+}
+
+/// Filtros, ordenação e paginação de GET /api/processes
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProcessFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_to: Option<DateTime<Utc>>,
+    /// Busca no título
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+    /// Username ou chave pública do fornecedor
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project_code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<ProcessSort>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<SortOrder>,
+    /// next_cursor da página anterior
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+}
+
+/// Busca de GET /api/processes/search
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct SearchQuery<'a> {
+    username: &'a str,
+    q: &'a str,
+}
+
+/// Filtros de GET /api/supplier/processes
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SupplierFilter {
+    /// pending, confirmed, revoked ou expired
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    /// next_cursor da página anterior
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct SupplierQuery<'a> {
+    supplier_public_key: &'a str,
+}
+
+/// Filtros de GET /api/notifications
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct NotificationFilter {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unread_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ReadStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<NotificationEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_from: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_to: Option<DateTime<Utc>>,
+    /// Busca no título do processo
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub q: Option<String>,
+    /// Username do fornecedor que originou a notificação
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supplier: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub order: Option<SortOrder>,
+    /// next_cursor da página anterior; tem precedência sobre page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct UsernameQuery<'a> {
+    username: &'a str,
+}
+
+#[derive(Serialize)]
+struct ClientQuery<'a> {
+    client_username: &'a str,
+}
+
+// Repetições com a mesma chave recebem a resposta original, sem refazer a operação
+const IDEMPOTENCY_KEY: &str = "Idempotency-Key";
+
+#[derive(Debug, Clone)]
+pub struct NdaClient {
+    base_url: reqwest::Url,
+    http: reqwest::Client,
+    token: Option<String>,
+    language: Option<String>,
+}
+
+impl NdaClient {
+    /// base_url: endereço do servidor, ex.: http://localhost:3000
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        Self::with_http_client(base_url, reqwest::Client::new())
+    }
+
+    /// Usa um reqwest::Client já configurado (timeouts, proxy, certificados)
+    pub fn with_http_client(base_url: &str, http: reqwest::Client) -> Result<Self, ClientError> {
+        let parsed = reqwest::Url::parse(base_url).map_err(|_| ClientError::InvalidUrl(base_url.to_string()))?;
+        if !matches!(parsed.scheme(), "http" | "https") {
+            return Err(ClientError::InvalidUrl(base_url.to_string()));
+        }
+
+        Ok(Self {
+            base_url: parsed,
+            http,
+            token: None,
+            language: None,
+        })
+    }
+
+    /// Token enviado como `Authorization: Bearer` (ex.: exigido por um gateway à frente da API)
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = Some(token.into());
+        self
+    }
+
+    pub fn set_token(&mut self, token: Option<String>) {
+        self.token = token;
+    }
+
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    /// Idioma das mensagens de erro ("pt" ou "en")
+    pub fn with_language(mut self, language: impl Into<String>) -> Self {
+        self.language = Some(language.into());
+        self
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        let segments: Vec<&str> = path.split('/').filter(|segment| !segment.is_empty()).collect();
+        self.request_at(method, &segments)
+    }
+
+    // Cada segmento é codificado (percent-encoding): ids com "/", "?" ou "#" não alteram a rota
+    fn request_at(&self, method: Method, segments: &[&str]) -> RequestBuilder {
+        let mut url = self.base_url.clone();
+        url.path_segments_mut()
+            .expect("base_url http(s) validada em with_http_client")
+            .pop_if_empty()
+            .extend(segments);
+
+        let mut request = self.http.request(method, url);
+        if let Some(token) = &self.token {
+            request = request.bearer_auth(token);
+        }
+        if let Some(language) = &self.language {
+            request = request.header(header::ACCEPT_LANGUAGE, language);
+        }
+        request
+    }
+
+    async fn execute(&self, request: RequestBuilder) -> Result<reqwest::Response, ClientError> {
+        let response = request.send().await?;
+        self.execute_response(response).await
+    }
+
+    async fn failure(response: reqwest::Response) -> ClientError {
+        let status = response.status();
+        let retry_after = response
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        let body = response.text().await.unwrap_or_default();
+
+        // Respostas fora do formato padrão (ex.: proxy) mantêm o corpo como mensagem
+        let failure = match serde_json::from_str::<ErrorEnvelope>(&body) {
+            Ok(envelope) => ApiFailure {
+                status,
+                code: envelope.error.code,
+                message: envelope.error.message,
+                request_id: envelope.error.request_id,
+                retry_after,
+            },
+            Err(_) => ApiFailure {
+                status,
+                code: "unknown".to_string(),
+                message: body,
+                request_id: None,
+                retry_after,
+            },
+        };
+        ClientError::Api(failure)
+    }
+
+    async fn execute_response(&self, response: reqwest::Response) -> Result<reqwest::Response, ClientError> {
+        if response.status().is_success() {
+            return Ok(response);
+        }
+        Err(Self::failure(response).await)
+    }
+
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, ClientError> {
+        Ok(self.execute(request).await?.json().await?)
+    }
+
+    async fn send_empty(&self, request: RequestBuilder) -> Result<(), ClientError> {
+        self.execute(request).await?;
+        Ok(())
+    }
+
+    /// GET /health
+    pub async fn health(&self) -> Result<String, ClientError> {
+        Ok(self.execute(self.request(Method::GET, "/health")).await?.text().await?)
+    }
+
+    /// GET /health/live: o processo está de pé
+    pub async fn health_live(&self) -> Result<(), ClientError> {
+        self.send_empty(self.request(Method::GET, "/health/live")).await
+    }
+
+    /// GET /health/ready; com alguma dependência indisponível (503) o corpo ainda é retornado,
+    /// com status "not_ready" ou "shutting_down"
+    pub async fn health_ready(&self) -> Result<ReadinessResponse, ClientError> {
+        let response = self.request(Method::GET, "/health/ready").send().await?;
+        if response.status() != StatusCode::SERVICE_UNAVAILABLE {
+            return Ok(self.execute_response(response).await?.json().await?);
+        }
+
+        // 503 sem o corpo de readiness (ex.: vindo de um proxy) segue como erro da API
+        let status = response.status();
+        let body = response.text().await?;
+        serde_json::from_str(&body).map_err(|_| {
+            ClientError::Api(ApiFailure {
+                status,
+                code: "unknown".to_string(),
+                message: body,
+                request_id: None,
+                retry_after: None,
+            })
+        })
+    }
+
+    /// POST /api/users/register
+    pub async fn register(&self, request: &RegisterRequest) -> Result<UserResponse, ClientError> {
+        self.send(self.request(Method::POST, "/api/users/register").json(request)).await
+    }
+
+    /// POST /api/users/register com Idempotency-Key
+    pub async fn register_idempotent(&self, request: &RegisterRequest, idempotency_key: &str) -> Result<UserResponse, ClientError> {
+        let request = self.request(Method::POST, "/api/users/register").header(IDEMPOTENCY_KEY, idempotency_key).json(request);
+        self.send(request).await
+    }
+
+    /// POST /api/users/login
+    pub async fn login(&self, request: &LoginRequest) -> Result<UserResponse, ClientError> {
+        self.send(self.request(Method::POST, "/api/users/login").json(request)).await
+    }
+
+    /// GET /api/users/preferences
+    pub async fn preferences(&self, username: &str) -> Result<NotificationPreferences, ClientError> {
+        self.send(self.request(Method::GET, "/api/users/preferences").query(&UsernameQuery { username })).await
+    }
+
+    /// PUT /api/users/preferences
+    pub async fn update_preferences(&self, request: &UpdatePreferencesRequest) -> Result<NotificationPreferences, ClientError> {
+        self.send(self.request(Method::PUT, "/api/users/preferences").json(request)).await
+    }
+
+    /// POST /api/processes
+    pub async fn create_process(&self, request: &CreateProcessRequest) -> Result<ProcessResponse, ClientError> {
+        self.send(self.request(Method::POST, "/api/processes").json(request)).await
+    }
+
+    /// POST /api/processes com Idempotency-Key
+    pub async fn create_process_idempotent(
+        &self,
+        request: &CreateProcessRequest,
+        idempotency_key: &str,
+    ) -> Result<ProcessResponse, ClientError> {
+        let request = self.request(Method::POST, "/api/processes").header(IDEMPOTENCY_KEY, idempotency_key).json(request);
+        self.send(request).await
+    }
+
+    /// GET /api/processes; a próxima página vem com filter.cursor = next_cursor
+    pub async fn list_processes(&self, client_username: &str, filter: &ProcessFilter) -> Result<ProcessPage, ClientError> {
+        let request = self
+            .request(Method::GET, "/api/processes")
+            .query(&ClientQuery { client_username })
+            .query(filter);
+        self.send(request).await
+    }
+
+    /// GET /api/processes/search: processos do usuário e compartilhados com ele, por relevância
+    pub async fn search_processes(&self, username: &str, q: &str, filter: &SearchFilter) -> Result<ProcessSearchPage, ClientError> {
+        let request = self
+            .request(Method::GET, "/api/processes/search")
+            .query(&SearchQuery { username, q })
+            .query(filter);
+        self.send(request).await
+    }
+
+    /// PUT /api/processes/:id/metadata; substitui tags, categoria, referências e campos livres
+    pub async fn update_process_metadata(
+        &self,
+        process_id: &str,
+        request: &UpdateProcessMetadataRequest,
+    ) -> Result<ProcessResponse, ClientError> {
+        let segments = ["api", "processes", process_id, "metadata"];
+        self.send(self.request_at(Method::PUT, &segments).json(request)).await
+    }
+
+    /// POST /api/processes/share; o compartilhamento fica pendente até a confirmação no ledger
+    pub async fn share_process(&self, request: &ShareProcessRequest) -> Result<ProcessShare, ClientError> {
+        self.send(self.request(Method::POST, "/api/processes/share").json(request)).await
+    }
+
+    /// POST /api/processes/share com Idempotency-Key
+    pub async fn share_process_idempotent(
+        &self,
+        request: &ShareProcessRequest,
+        idempotency_key: &str,
+    ) -> Result<ProcessShare, ClientError> {
+        let request = self.request(Method::POST, "/api/processes/share").header(IDEMPOTENCY_KEY, idempotency_key).json(request);
+        self.send(request).await
+    }
+
+    /// POST /api/processes/share/bulk: resultado por fornecedor, na ordem do pedido
+    pub async fn bulk_share_process(&self, request: &BulkShareRequest) -> Result<BulkShareResponse, ClientError> {
+        self.send(self.request(Method::POST, "/api/processes/share/bulk").json(request)).await
+    }
+
+    /// POST /api/processes/share/bulk com Idempotency-Key
+    pub async fn bulk_share_process_idempotent(
+        &self,
+        request: &BulkShareRequest,
+        idempotency_key: &str,
+    ) -> Result<BulkShareResponse, ClientError> {
+        let request = self.request(Method::POST, "/api/processes/share/bulk").header(IDEMPOTENCY_KEY, idempotency_key).json(request);
+        self.send(request).await
+    }
+
+    /// POST /api/processes/invite: convite por e-mail para fornecedor ainda não cadastrado;
+    /// o token só vem nesta resposta
+    pub async fn invite_supplier(&self, request: &InviteSupplierRequest) -> Result<CreateShareInviteResponse, ClientError> {
+        self.send(self.request(Method::POST, "/api/processes/invite").json(request)).await
+    }
+
+    /// POST /api/processes/access
+    pub async fn access_process(&self, request: &AccessProcessRequest) -> Result<ProcessAccessResponse, ClientError> {
+        self.send(self.request(Method::POST, "/api/processes/access").json(request)).await
+    }
+
+    /// GET /api/supplier/processes: compartilhamentos recebidos pelo fornecedor
+    pub async fn list_supplier_processes(
+        &self,
+        supplier_public_key: &str,
+        filter: &SupplierFilter,
+    ) -> Result<SupplierProcessPage, ClientError> {
+        let request = self
+            .request(Method::GET, "/api/supplier/processes")
+            .query(&SupplierQuery { supplier_public_key })
+            .query(filter);
+        self.send(request).await
+    }
+
+    /// POST /api/supplier/processes/:id/accept
+    pub async fn accept_share(&self, share_id: &str, supplier_username: &str) -> Result<ProcessShare, ClientError> {
+        let segments = ["api", "supplier", "processes", share_id, "accept"];
+        let request = AcceptShareRequest { supplier_username: supplier_username.to_string() };
+        self.send(self.request_at(Method::POST, &segments).json(&request)).await
+    }
+
+    /// GET /api/notifications
+    pub async fn notifications(&self, username: &str, filter: &NotificationFilter) -> Result<NotificationPage, ClientError> {
+        let request = self
+            .request(Method::GET, "/api/notifications")
+            .query(&UsernameQuery { username })
+            .query(filter);
+        self.send(request).await
+    }
+
+    /// GET /api/notifications/stream (SSE): notificações em tempo real do usuário.
+    /// Eventos perdidos enquanto desconectado devem ser buscados com notifications()
+    pub async fn notification_stream(&self, username: &str) -> Result<NotificationStream, ClientError> {
+        let request = self
+            .request(Method::GET, "/api/notifications/stream")
+            .header(header::ACCEPT, "text/event-stream")
+            .query(&UsernameQuery { username });
+        let response = self.execute(request).await?;
+        Ok(NotificationStream { response, buffer: Vec::new(), data: String::new() })
+    }
+
+    /// POST /api/notifications/:id/read
+    pub async fn mark_notification_read(&self, notification_id: &str, username: &str) -> Result<(), ClientError> {
+        let segments = ["api", "notifications", notification_id, "read"];
+        let body = MarkNotificationsReadRequest { username: username.to_string() };
+        self.send_empty(self.request_at(Method::POST, &segments).json(&body)).await
+    }
+
+    /// POST /api/notifications/read-all
+    pub async fn mark_all_notifications_read(&self, username: &str) -> Result<(), ClientError> {
+        let body = MarkNotificationsReadRequest { username: username.to_string() };
+        self.send_empty(self.request(Method::POST, "/api/notifications/read-all").json(&body)).await
+    }
+
+    /// POST /api/webhooks; o segredo só é retornado nesta chamada
+    pub async fn create_webhook(&self, request: &CreateWebhookRequest) -> Result<CreateWebhookResponse, ClientError> {
+        self.send(self.request(Method::POST, "/api/webhooks").json(request)).await
+    }
+
+    /// GET /api/webhooks
+    pub async fn list_webhooks(&self, client_username: &str) -> Result<Vec<WebhookEndpoint>, ClientError> {
+        self.send(self.request(Method::GET, "/api/webhooks").query(&ClientQuery { client_username })).await
+    }
+
+    /// DELETE /api/webhooks/:id
+    pub async fn delete_webhook(&self, webhook_id: &str, client_username: &str) -> Result<(), ClientError> {
+        let segments = ["api", "webhooks", webhook_id];
+        self.send_empty(self.request_at(Method::DELETE, &segments).query(&ClientQuery { client_username })).await
+    }
+
+    /// GET /api/webhooks/:id/deliveries
+    pub async fn webhook_deliveries(&self, webhook_id: &str, client_username: &str) -> Result<Vec<WebhookDelivery>, ClientError> {
+        let segments = ["api", "webhooks", webhook_id, "deliveries"];
+        self.send(self.request_at(Method::GET, &segments).query(&ClientQuery { client_username })).await
+    }
+}
+
+/// Stream SSE de GET /api/notifications/stream
+#[derive(Debug)]
+pub struct NotificationStream {
+    response: reqwest::Response,
+    buffer: Vec<u8>, // bytes recebidos ainda sem fim de linha
+    data: String,    // linhas data: do evento em montagem
+}
+
+impl NotificationStream {
+    /// Próxima notificação; None quando o servidor encerra a conexão
+    pub async fn next(&mut self) -> Option<Result<Notification, ClientError>> {
+        loop {
+            while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+                let line: Vec<u8> = self.buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim_end_matches(['\n', '\r']);
+
+                // Linha em branco encerra o evento; comentários (":") são keep-alive
+                if line.is_empty() {
+                    if self.data.is_empty() {
+                        continue;
+                    }
+                    let data = std::mem::take(&mut self.data);
+                    return Some(serde_json::from_str(&data).map_err(ClientError::from));
+                }
+                if let Some(value) = line.strip_prefix("data:") {
+                    if !self.data.is_empty() {
+                        self.data.push('\n');
+                    }
+                    self.data.push_str(value.strip_prefix(' ').unwrap_or(value));
+                }
+            }
+
+            match self.response.chunk().await {
+                Ok(Some(chunk)) => self.buffer.extend_from_slice(&chunk),
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        extract::Path,
+        http::{HeaderMap, StatusCode as AxumStatus, Uri},
+        response::IntoResponse,
+        routing::{delete, get, post},
+        Json, Router,
+    };
+    use serde_json::json;
+
+    async fn spawn_server(app: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", address)
+    }
+
+    #[tokio::test]
+    async fn test_sends_token_and_decodes_shared_models() {
+        let app = Router::new().route(
+            "/api/users/login",
+            post(|headers: HeaderMap, Json(body): Json<serde_json::Value>| async move {
+                assert_eq!(headers["authorization"], "Bearer segredo");
+                assert_eq!(body["username"], "ana");
+                Json(json!({
+                    "id": "u1",
+                    "username": "ana",
+                    "stellar_public_key": "GABC",
+                    "user_type": "client",
+                    "created_at": "2024-01-01T00:00:00Z"
+                }))
+            }),
+        );
+        let client = NdaClient::new(&spawn_server(app).await).unwrap().with_token("segredo");
+
+        let user = client
+            .login(&LoginRequest { username: "ana".to_string(), password: "senha".to_string() })
+            .await
+            .unwrap();
+        assert_eq!(user.id, "u1");
+        assert_eq!(user.user_type, "client");
+    }
+
+    #[tokio::test]
+    async fn test_maps_api_error_body() {
+        let app = Router::new().route(
+            "/api/processes",
+            get(|| async {
+                let body = json!({ "error": { "code": "rate_limited", "message": "Muitas requisições", "request_id": "r1" } });
+                (AxumStatus::TOO_MANY_REQUESTS, [("retry-after", "7")], Json(body)).into_response()
+            }),
+        );
+        let client = NdaClient::new(&spawn_server(app).await).unwrap();
+
+        let error = client.list_processes("ana", &ProcessFilter::default()).await.unwrap_err();
+        assert_eq!(error.code(), Some("rate_limited"));
+        match error {
+            ClientError::Api(failure) => {
+                assert_eq!(failure.status, StatusCode::TOO_MANY_REQUESTS);
+                assert_eq!(failure.request_id.as_deref(), Some("r1"));
+                assert_eq!(failure.retry_after, Some(7));
+            }
+            other => panic!("erro inesperado: {:?}", other),
+        }
+
+        assert!(matches!(NdaClient::new("ftp://exemplo"), Err(ClientError::InvalidUrl(_))));
+    }
+
+    #[tokio::test]
+    async fn test_sends_idempotency_key_and_reads_readiness_body() {
+        let app = Router::new()
+            .route(
+                "/api/processes/share",
+                post(|headers: HeaderMap| async move {
+                    assert_eq!(headers["idempotency-key"], "chave-1");
+                    Json(json!({
+                        "id": "s1",
+                        "process_id": "p1",
+                        "supplier_public_key": "GABC",
+                        "stellar_transaction_hash": null,
+                        "status": "pending",
+                        "shared_at": "2024-01-01T00:00:00Z",
+                        "confirmed_at": null,
+                        "revoked_at": null,
+                        "accepted_at": null,
+                        "expires_at": null
+                    }))
+                }),
+            )
+            .route(
+                "/health/ready",
+                get(|| async {
+                    let body = json!({
+                        "status": "not_ready",
+                        "database": { "ok": true },
+                        "migrations": { "ok": true },
+                        "horizon": { "ok": false, "detail": "Tempo esgotado" }
+                    });
+                    (AxumStatus::SERVICE_UNAVAILABLE, Json(body)).into_response()
+                }),
+            );
+        let client = NdaClient::new(&spawn_server(app).await).unwrap();
+
+        let request = ShareProcessRequest {
+            process_id: "p1".to_string(),
+            supplier_username: None,
+            supplier_public_key: Some("GABC".to_string()),
+            client_username: "ana".to_string(),
+            expires_at: None,
+        };
+        let share = client.share_process_idempotent(&request, "chave-1").await.unwrap();
+        assert_eq!(share.status, "pending");
+
+        let readiness = client.health_ready().await.unwrap();
+        assert_eq!(readiness.status, "not_ready");
+        assert!(!readiness.horizon.ok);
+        assert_eq!(readiness.horizon.detail.as_deref(), Some("Tempo esgotado"));
+    }
+
+    #[tokio::test]
+    async fn test_reads_notification_stream() {
+        let app = Router::new().route(
+            "/api/notifications/stream",
+            get(|| async {
+                let notification = json!({
+                    "id": "n1",
+                    "recipient_id": "u1",
+                    "event_type": "accessed",
+                    "process_id": "p1",
+                    "process_title": "Projeto",
+                    "actor_id": null,
+                    "actor_username": null,
+                    "message": "Processo acessado",
+                    "is_read": false,
+                    "created_at": "2024-01-01T00:00:00Z",
+                    "read_at": null
+                });
+                let body = format!(":\n\nevent: accessed\nid: n1\ndata: {}\n\n", notification);
+                ([(axum::http::header::CONTENT_TYPE, "text/event-stream")], body)
+            }),
+        );
+        let client = NdaClient::new(&spawn_server(app).await).unwrap();
+
+        let mut stream = client.notification_stream("ana").await.unwrap();
+        let notification = stream.next().await.unwrap().unwrap();
+        assert_eq!(notification.id, "n1");
+        assert_eq!(notification.process_title, "Projeto");
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_encodes_path_ids() {
+        let app = Router::new().route(
+            "/prefixo/api/webhooks/:id",
+            delete(|Path(id): Path<String>, uri: Uri| async move {
+                assert_eq!(id, "a/b?c#d");
+                assert_eq!(uri.path(), "/prefixo/api/webhooks/a%2Fb%3Fc%23d");
+                AxumStatus::NO_CONTENT
+            }),
+        );
+        let client = NdaClient::new(&format!("{}/prefixo/", spawn_server(app).await)).unwrap();
+
+        client.delete_webhook("a/b?c#d", "ana").await.unwrap();
+    }
+}
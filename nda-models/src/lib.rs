@@ -0,0 +1,462 @@
+// nda-models/src/lib.rs
+// Tipos de requisição e resposta da API, compartilhados entre o servidor e o nda-client
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use utoipa::{IntoParams, ToSchema};
+
+mod pagination;
+pub use pagination::{ProcessSort, ReadStatus, SortOrder};
+
+// Debug que oculta campos sensíveis (chaves, senhas, segredos e conteúdo do NDA),
+// para que structs registradas em log com {:?} não os exponham
+#[macro_export]
+#[doc(hidden)]
+macro_rules! debug_redacted {
+    ($name:ident { $($field:ident),* ; $($secret:ident),* }) => {
+        impl std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.debug_struct(stringify!($name))
+                    $(.field(stringify!($field), &self.$field))*
+                    $(.field(stringify!($secret), &"***"))*
+                    .finish()
+            }
+        }
+    };
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ProcessShare {
+    pub id: String,
+    pub process_id: String,
+    pub supplier_public_key: String,
+    pub stellar_transaction_hash: Option<String>, // preenchido quando o ledger confirma
+    pub status: String, // "pending", "confirmed", "failed", "revoked" ou "expired"
+    pub shared_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub accepted_at: Option<DateTime<Utc>>, // aceite do NDA pelo fornecedor
+    pub expires_at: Option<DateTime<Utc>>,  // sem valor: não expira
+}
+
+impl ProcessShare {
+    /// Pendente ou confirmado dentro da validade; falhos, revogados e vencidos
+    /// permitem um novo compartilhamento
+    pub fn is_active(&self) -> bool {
+        match self.status.as_str() {
+            "pending" => true,
+            "confirmed" => !self.is_expired(Utc::now()),
+            _ => false,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct RegisterRequest {
+    pub username: String,
+    pub password: String,
+    pub user_type: String,
+    // Token de convite recebido por e-mail; converte os convites pendentes em compartilhamentos
+    #[serde(default)]
+    pub invite_token: Option<String>,
+}
+
+debug_redacted!(RegisterRequest { username, user_type; password, invite_token });
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct LoginRequest {
+    pub username: String,
+    pub password: String,
+}
+
+debug_redacted!(LoginRequest { username; password });
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateProcessRequest {
+    pub title: String,
+    pub confidential_content: String,
+    pub client_username: String,  // ← Adicionar este campo
+    #[serde(default)]
+    pub metadata: ProcessMetadata,
+}
+
+debug_redacted!(CreateProcessRequest { title, client_username, metadata; confidential_content });
+
+// Metadados não confidenciais (gravados sem criptografia), para organizar e buscar processos
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct ProcessMetadata {
+    pub tags: Vec<String>,
+    pub category: Option<String>,
+    pub counterparty_reference: Option<String>,
+    pub project_code: Option<String>,
+    pub custom_fields: BTreeMap<String, String>,
+}
+
+impl ProcessMetadata {
+    // Texto indexado pela busca textual, junto com o título
+    pub fn search_text(&self) -> String {
+        let mut parts: Vec<&str> = self.tags.iter().map(String::as_str).collect();
+        parts.extend(self.category.as_deref());
+        parts.extend(self.counterparty_reference.as_deref());
+        parts.extend(self.project_code.as_deref());
+        parts.extend(self.custom_fields.values().map(String::as_str));
+        parts.join(" ")
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdateProcessMetadataRequest {
+    pub client_username: String,
+    pub metadata: ProcessMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ShareProcessRequest {
+    pub process_id: String,
+    // Fornecedor cadastrado, identificado pelo username ou pela chave pública (um dos dois)
+    #[serde(default)]
+    pub supplier_username: Option<String>,
+    #[serde(default)]
+    pub supplier_public_key: Option<String>,
+    pub client_username: String,
+    // Validade do acesso; sem valor, o compartilhamento não expira
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+// Fornecedor de um compartilhamento em lote: username ou chave pública (um dos dois)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ShareTarget {
+    #[serde(default)]
+    pub supplier_username: Option<String>,
+    #[serde(default)]
+    pub supplier_public_key: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkShareRequest {
+    pub process_id: String,
+    pub client_username: String,
+    pub suppliers: Vec<ShareTarget>,
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+// Resultado por fornecedor, na ordem do pedido
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkShareResult {
+    pub supplier: ShareTarget,
+    pub share: Option<ProcessShare>, // criado como "pending"
+    pub error: Option<String>,       // código do erro (ex.: "not_a_supplier")
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct BulkShareResponse {
+    pub results: Vec<BulkShareResult>,
+    pub created: usize,
+    pub failed: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct InviteSupplierRequest {
+    pub process_id: String,
+    pub client_username: String,
+    pub email: String,
+    // Validade do compartilhamento criado quando o convite for aceito
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+// Convite por e-mail para um fornecedor ainda não cadastrado
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ShareInvite {
+    pub id: String,
+    pub process_id: String,
+    pub client_id: String,
+    pub email: String,
+    pub status: String, // "pending", "accepted" ou "expired"
+    pub share_expires_at: Option<DateTime<Utc>>,
+    pub expires_at: DateTime<Utc>, // validade do próprio convite
+    pub created_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub share_id: Option<String>, // compartilhamento criado no cadastro
+}
+
+// Retornado apenas na criação: único momento em que o token é exibido
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateShareInviteResponse {
+    #[serde(flatten)]
+    pub invite: ShareInvite,
+    pub token: String,
+}
+
+debug_redacted!(CreateShareInviteResponse { invite; token });
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AcceptShareRequest {
+    pub supplier_username: String,
+}
+
+// Compartilhamento visto pelo fornecedor (GET /api/supplier/processes)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SupplierProcess {
+    pub share_id: String,
+    pub process_id: String,
+    pub title: String,
+    pub client_username: String,
+    pub status: String, // status do compartilhamento
+    pub shared_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub expired: bool,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub access_count: i64,
+    pub last_accessed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct SupplierProcessPage {
+    pub processes: Vec<SupplierProcess>,
+    pub total: i64,
+    // Cursor da próxima página; ausente na última
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct AccessProcessRequest {
+    pub process_id: String,
+    pub supplier_public_key: String,
+    pub supplier_username: String,
+}
+
+// Response models para API
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct HealthCheck {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl HealthCheck {
+    pub fn ok() -> Self {
+        HealthCheck { ok: true, detail: None }
+    }
+
+    pub fn failed(detail: impl std::fmt::Display) -> Self {
+        HealthCheck { ok: false, detail: Some(detail.to_string()) }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ReadinessResponse {
+    pub status: String, // ready, not_ready ou shutting_down
+    pub database: HealthCheck,
+    pub migrations: HealthCheck,
+    pub horizon: HealthCheck,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UserResponse {
+    pub id: String,
+    pub username: String,
+    pub stellar_public_key: String,
+    pub user_type: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProcessResponse {
+    pub id: String,
+    pub title: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    #[serde(default)]
+    pub metadata: ProcessMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProcessPage {
+    pub processes: Vec<ProcessResponse>,
+    // Total com os filtros aplicados, em todas as páginas
+    pub total: i64,
+    // Cursor da próxima página; ausente na última
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProcessSearchHit {
+    pub id: String,
+    pub title: String,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    // Título escapado para HTML, com os termos encontrados entre <mark> e </mark>
+    pub highlight: String,
+    // Mesmo destaque sobre tags e metadados; vazio quando não há metadados
+    pub metadata_highlight: String,
+    // Relevância; maior é melhor
+    pub score: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct ProcessSearchPage {
+    pub results: Vec<ProcessSearchHit>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: i64,
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct ProcessAccessResponse {
+    pub process_id: String,
+    pub title: String,
+    pub content: String,
+    pub accessed_at: DateTime<Utc>,
+}
+
+debug_redacted!(ProcessAccessResponse { process_id, title, accessed_at; content });
+
+// Tipos de eventos de notificação
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationEvent {
+    Accessed,      // fornecedor acessou o conteúdo
+    Accepted,      // fornecedor aceitou o NDA
+    ShareExpired,  // compartilhamento expirou
+    Revoked,       // compartilhamento revogado
+    Denied,        // tentativa de acesso negada
+    Granted,       // fornecedor recebeu acesso a um processo
+}
+
+impl NotificationEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationEvent::Accessed => "accessed",
+            NotificationEvent::Accepted => "accepted",
+            NotificationEvent::ShareExpired => "share_expired",
+            NotificationEvent::Revoked => "revoked",
+            NotificationEvent::Denied => "denied",
+            NotificationEvent::Granted => "granted",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "accessed" => Some(NotificationEvent::Accessed),
+            "accepted" => Some(NotificationEvent::Accepted),
+            "share_expired" => Some(NotificationEvent::ShareExpired),
+            "revoked" => Some(NotificationEvent::Revoked),
+            "denied" => Some(NotificationEvent::Denied),
+            "granted" => Some(NotificationEvent::Granted),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Notification {
+    pub id: String,
+    pub recipient_id: String,
+    pub event_type: NotificationEvent,
+    pub process_id: String,
+    pub process_title: String,
+    pub actor_id: Option<String>,
+    pub actor_username: Option<String>,
+    pub message: String,
+    pub is_read: bool,
+    pub created_at: DateTime<Utc>,
+    pub read_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationPage {
+    pub notifications: Vec<Notification>,
+    pub page: u32,
+    pub per_page: u32,
+    pub total: i64,
+    pub unread_count: i64,
+    // Cursor da próxima página; ausente na última
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct MarkNotificationsReadRequest {
+    pub username: String,
+}
+
+// Webhooks
+#[derive(Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhookEndpoint {
+    pub id: String,
+    pub client_id: String,
+    pub url: String,
+    #[serde(default, skip_serializing)]
+    pub secret: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+debug_redacted!(WebhookEndpoint { id, client_id, url, events, active, created_at; secret });
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub endpoint_id: String,
+    pub event_type: String,
+    pub payload: String,
+    pub status: String, // "pending", "delivered" ou "failed"
+    pub attempts: i64,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_status_code: Option<i64>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub client_username: String,
+    pub url: String,
+    pub events: Option<Vec<String>>, // vazio ou ausente = todos os eventos
+}
+
+// Retornado apenas na criação: único momento em que o segredo é exibido
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct CreateWebhookResponse {
+    #[serde(flatten)]
+    pub endpoint: WebhookEndpoint,
+    pub secret: String,
+}
+
+debug_redacted!(CreateWebhookResponse { endpoint; secret });
+
+// Preferências de notificação por usuário
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct NotificationPreferences {
+    pub user_id: String,
+    pub email: Option<String>,
+    pub email_enabled: bool,
+    pub language: String, // "pt" ou "en"
+    pub email_events: Vec<NotificationEvent>,
+    pub updated_at: Option<DateTime<Utc>>, // ausente = preferências padrão
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PreferencesQuery {
+    pub username: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct UpdatePreferencesRequest {
+    pub username: String,
+    pub email: Option<String>,
+    pub email_enabled: Option<bool>,
+    pub language: Option<String>,
+    pub email_events: Option<Vec<NotificationEvent>>,
+}
+
@@ -0,0 +1,45 @@
+// nda-models/src/pagination.rs
+// Ordenação e filtros aceitos nas listagens
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "asc",
+            SortOrder::Desc => "desc",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessSort {
+    #[default]
+    CreatedAt,
+    Title,
+}
+
+impl ProcessSort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProcessSort::CreatedAt => "created_at",
+            ProcessSort::Title => "title",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReadStatus {
+    Read,
+    Unread,
+}
@@ -0,0 +1,368 @@
+// src/admin.rs
+// Operações administrativas do nda-admin, executadas diretamente no banco configurado
+use chrono::{DateTime, Utc};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+
+use crate::crypto::{decrypt_content, encrypt_content, generate_key};
+use crate::database::{queries, DbPool};
+use crate::handlers::{emit_webhook, notify, AppState};
+use crate::models::*;
+use crate::stellar_real::StellarClient;
+use crate::webhooks::WebhookEvent;
+
+pub type AdminResult<T> = Result<T, Box<dyn Error>>;
+
+pub async fn find_user(pool: &DbPool, username: &str) -> AdminResult<User> {
+    queries::find_user_by_username(pool, username)
+        .await?
+        .ok_or_else(|| format!("Usuário não encontrado: {}", username).into())
+}
+
+/// Bloqueia (ou desbloqueia) o usuário: login e acesso a processos passam a ser recusados
+pub async fn set_user_locked(pool: &DbPool, username: &str, locked: bool) -> AdminResult<User> {
+    let user = find_user(pool, username).await?;
+    queries::set_user_locked(pool, &user.id, locked).await?;
+    tracing::info!(user_id = %user.id, locked, "bloqueio de usuário alterado");
+    find_user(pool, username).await
+}
+
+pub struct ShareReport {
+    pub share: ProcessShare,
+    pub supplier_username: Option<String>,
+    pub outbox: Option<LedgerOutboxEntry>,
+}
+
+pub struct ProcessReport {
+    pub process: Process,
+    pub client: Option<User>,
+    pub shares: Vec<ShareReport>,
+    pub accesses: Vec<ProcessAccess>,
+}
+
+/// Processo com dono, compartilhamentos (e registro no ledger) e acessos
+pub async fn inspect_process(pool: &DbPool, process_id: &str) -> AdminResult<ProcessReport> {
+    let process = queries::find_process_by_id(pool, process_id)
+        .await?
+        .ok_or_else(|| format!("Processo não encontrado: {}", process_id))?;
+    let client = queries::find_user_by_id(pool, &process.client_id).await?;
+
+    let mut shares = Vec::new();
+    for share in queries::list_process_shares_by_process(pool, process_id).await? {
+        let supplier_username = queries::find_user_by_public_key(pool, &share.supplier_public_key)
+            .await?
+            .map(|user| user.username);
+        let outbox = queries::find_ledger_outbox_by_share(pool, &share.id).await?;
+        shares.push(ShareReport { share, supplier_username, outbox });
+    }
+    let accesses = queries::list_process_accesses_by_process(pool, process_id).await?;
+
+    Ok(ProcessReport { process, client, shares, accesses })
+}
+
+/// Revoga o compartilhamento, avisa o fornecedor e emite share.revoked para o cliente
+pub async fn revoke_share(state: &AppState, share_id: &str) -> AdminResult<ProcessShare> {
+    let share = queries::find_process_share_by_id(&state.pool, share_id)
+        .await?
+        .ok_or_else(|| format!("Compartilhamento não encontrado: {}", share_id))?;
+    if !queries::revoke_process_share(&state.pool, share_id).await? {
+        return Err(format!("Compartilhamento não está ativo (status {})", share.status).into());
+    }
+    let share = queries::find_process_share_by_id(&state.pool, share_id)
+        .await?
+        .ok_or("Compartilhamento removido durante a revogação")?;
+    tracing::info!(share_id, process_id = %share.process_id, "compartilhamento revogado");
+
+    if let Some(process) = queries::find_process_by_id(&state.pool, &share.process_id).await? {
+        emit_webhook(
+            state,
+            &process.client_id,
+            WebhookEvent::ShareRevoked,
+            json!({
+                "share_id": share.id,
+                "process_id": share.process_id,
+                "supplier_public_key": share.supplier_public_key,
+                "revoked_at": share.revoked_at,
+            }),
+        )
+        .await;
+
+        if let Some(supplier) = queries::find_user_by_public_key(&state.pool, &share.supplier_public_key).await? {
+            notify(
+                state,
+                &supplier.id,
+                NotificationEvent::Revoked,
+                &process.id,
+                None,
+                &format!("Seu acesso ao processo '{}' foi revogado", process.title),
+            )
+            .await;
+        }
+    }
+
+    Ok(share)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditIssue {
+    pub subject: String, // ex.: "acesso <id>"
+    pub problem: String,
+}
+
+fn issue(issues: &mut Vec<AuditIssue>, subject: String, problem: impl Into<String>) {
+    issues.push(AuditIssue { subject, problem: problem.into() });
+}
+
+// Compartilhamento válido no instante do acesso: confirmado antes e não revogado até lá
+fn share_covers(share: &ProcessShare, accessed_at: DateTime<Utc>) -> bool {
+    matches!(share.status.as_str(), "confirmed" | "revoked")
+        && share.confirmed_at.is_some_and(|confirmed_at| confirmed_at <= accessed_at)
+        && share.revoked_at.is_none_or(|revoked_at| revoked_at > accessed_at)
+}
+
+/// Verifica a cadeia de auditoria: processo → compartilhamento registrado no ledger → acessos.
+/// Retorna as inconsistências encontradas (vazio = cadeia íntegra).
+pub async fn verify_audit(pool: &DbPool) -> AdminResult<Vec<AuditIssue>> {
+    let users: HashMap<String, User> = queries::list_users(pool).await?.into_iter().map(|u| (u.id.clone(), u)).collect();
+    let processes: HashSet<String> = queries::list_processes(pool).await?.into_iter().map(|p| p.id).collect();
+    let shares = queries::list_process_shares(pool).await?;
+    let outbox: HashMap<String, LedgerOutboxEntry> = queries::list_ledger_outbox(pool)
+        .await?
+        .into_iter()
+        .map(|entry| (entry.share_id.clone(), entry))
+        .collect();
+    let mut issues = Vec::new();
+
+    for share in &shares {
+        let subject = format!("compartilhamento {}", share.id);
+        if !processes.contains(&share.process_id) {
+            issue(&mut issues, subject.clone(), format!("processo inexistente {}", share.process_id));
+        }
+        if share.confirmed_at.is_some() && share.stellar_transaction_hash.is_none() {
+            issue(&mut issues, subject.clone(), "confirmado sem hash de transação no ledger");
+        }
+        if share.status == "confirmed" && share.confirmed_at.is_none() {
+            issue(&mut issues, subject.clone(), "confirmado sem data de confirmação");
+        }
+        if share.status == "revoked" && share.revoked_at.is_none() {
+            issue(&mut issues, subject.clone(), "revogado sem data de revogação");
+        }
+
+        let expected = match share.status.as_str() {
+            "pending" => &["pending"][..],
+            "confirmed" => &["done"][..],
+            "failed" => &["failed"][..],
+            _ => &["done", "failed"][..], // revogado antes ou depois do registro no ledger
+        };
+        match outbox.get(&share.id) {
+            Some(entry) if !expected.contains(&entry.status.as_str()) => issue(
+                &mut issues,
+                subject,
+                format!("status '{}' divergente do registro no ledger '{}'", share.status, entry.status),
+            ),
+            Some(_) => {}
+            // Compartilhamentos anteriores ao outbox foram migrados já confirmados
+            None if share.confirmed_at.is_some() => {}
+            None => issue(&mut issues, subject, "sem pedido de registro no ledger"),
+        }
+    }
+
+    for access in queries::list_process_accesses(pool).await? {
+        let subject = format!("acesso {}", access.id);
+        if !processes.contains(&access.process_id) {
+            issue(&mut issues, subject.clone(), format!("processo inexistente {}", access.process_id));
+        }
+        let Some(supplier) = users.get(&access.supplier_id) else {
+            issue(&mut issues, subject, format!("fornecedor inexistente {}", access.supplier_id));
+            continue;
+        };
+        let covered = shares.iter().any(|share| {
+            share.process_id == access.process_id
+                && share.supplier_public_key == supplier.stellar_public_key
+                && share_covers(share, access.accessed_at)
+        });
+        if !covered {
+            issue(&mut issues, subject, "sem compartilhamento confirmado no momento do acesso");
+        }
+    }
+
+    Ok(issues)
+}
+
+#[derive(Default)]
+pub struct ReconcileReport {
+    pub checked: usize,
+    pub missing_on_ledger: Vec<ProcessShare>, // confirmados sem transação encontrada no Horizon
+    pub failed: Vec<ProcessShare>,            // registro no ledger falhou definitivamente
+    pub requeued: usize,
+}
+
+/// Compara os compartilhamentos com o ledger. Sem `stellar`, apenas o banco é analisado;
+/// com `retry_failed`, registros que falharam voltam para a fila do worker.
+pub async fn reconcile_ledger(
+    pool: &DbPool,
+    stellar: Option<&StellarClient>,
+    retry_failed: bool,
+) -> AdminResult<ReconcileReport> {
+    let mut report = ReconcileReport::default();
+
+    for share in queries::list_process_shares(pool).await? {
+        match share.status.as_str() {
+            "confirmed" => {
+                report.checked += 1;
+                if let Some(stellar) = stellar {
+                    if !stellar.verify_process_access(&share.process_id, &share.supplier_public_key).await? {
+                        report.missing_on_ledger.push(share);
+                    }
+                }
+            }
+            "failed" => {
+                if retry_failed && queries::requeue_ledger_outbox(pool, &share.id).await? {
+                    tracing::info!(share_id = %share.id, "registro no ledger reenfileirado");
+                    report.requeued += 1;
+                } else {
+                    report.failed.push(share);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(report)
+}
+
+/// Gera novas chaves de criptografia e recriptografa o conteúdo (de um processo ou de todos).
+/// Retorna quantos processos tiveram a chave trocada.
+pub async fn rotate_keys(pool: &DbPool, process_id: Option<&str>) -> AdminResult<usize> {
+    let processes = match process_id {
+        Some(id) => vec![queries::find_process_by_id(pool, id)
+            .await?
+            .ok_or_else(|| format!("Processo não encontrado: {}", id))?],
+        None => queries::list_processes(pool).await?,
+    };
+
+    let mut rotated = 0;
+    for process in processes {
+        let content = decrypt_content(&process.encrypted_content, &process.encryption_key)
+            .map_err(|e| format!("Processo {}: {}", process.id, e))?;
+        let key = generate_key();
+        let encrypted = encrypt_content(&content, &key)?;
+
+        if queries::update_process_encryption(pool, &process.id, &process.encryption_key, &encrypted, &key).await? {
+            rotated += 1;
+        } else {
+            tracing::warn!(process_id = %process.id, "chave alterada durante a rotação, processo ignorado");
+        }
+    }
+
+    tracing::info!(rotated, "chaves de criptografia rotacionadas");
+    Ok(rotated)
+}
+
+/// Exporta os dados em JSON, sem chaves secretas, chaves de criptografia ou conteúdo dos NDAs
+pub async fn export(pool: &DbPool) -> AdminResult<serde_json::Value> {
+    let users: Vec<_> = queries::list_users(pool)
+        .await?
+        .into_iter()
+        .map(|user| {
+            json!({
+                "id": user.id,
+                "username": user.username,
+                "stellar_public_key": user.stellar_public_key,
+                "user_type": user.user_type,
+                "created_at": user.created_at,
+                "locked_at": user.locked_at,
+            })
+        })
+        .collect();
+    let processes = queries::list_processes(pool).await?;
+    let ids: Vec<String> = processes.iter().map(|p| p.id.clone()).collect();
+    let mut metadata = queries::find_process_metadata(pool, &ids).await?;
+    let processes: Vec<_> = processes
+        .into_iter()
+        .map(|process| {
+            json!({
+                "metadata": metadata.remove(&process.id).unwrap_or_default(),
+                "id": process.id,
+                "client_id": process.client_id,
+                "title": process.title,
+                "status": process.status,
+                "created_at": process.created_at,
+            })
+        })
+        .collect();
+
+    Ok(json!({
+        "exported_at": Utc::now(),
+        "users": users,
+        "processes": processes,
+        "shares": queries::list_process_shares(pool).await?,
+        "accesses": queries::list_process_accesses(pool).await?,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_pool;
+
+    #[tokio::test]
+    async fn test_revoke_share_breaks_audit_chain_for_later_accesses() {
+        let state = AppState::new(test_pool().await);
+        let pool = &state.pool;
+        let client = queries::create_user(pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let supplier = queries::create_user(pool, "fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+        let key = generate_key();
+        let process = queries::create_process(pool, &client.id, "NDA Alpha", &encrypt_content("segredo", &key).unwrap(), &key)
+            .await
+            .unwrap();
+        let share = queries::create_process_share(pool, &process.id, "GSUPPLIER", None).await.unwrap();
+
+        // Pendente no ledger: cadeia ainda íntegra
+        assert!(verify_audit(pool).await.unwrap().is_empty());
+
+        let entry = queries::find_ledger_outbox_by_share(pool, &share.id).await.unwrap().unwrap();
+        assert!(queries::confirm_ledger_outbox(pool, &entry, "hash-1").await.unwrap());
+        queries::create_process_access(pool, &process.id, &supplier.id).await.unwrap();
+        assert!(verify_audit(pool).await.unwrap().is_empty());
+
+        let revoked = revoke_share(&state, &share.id).await.unwrap();
+        assert_eq!(revoked.status, "revoked");
+        assert!(revoke_share(&state, &share.id).await.is_err());
+        let notifications = queries::list_notifications_for_user(pool, &supplier.id, &Default::default()).await.unwrap().items;
+        assert_eq!(notifications[0].event_type, NotificationEvent::Revoked);
+
+        // Acesso depois da revogação não tem compartilhamento que o cubra
+        let late = queries::create_process_access(pool, &process.id, &supplier.id).await.unwrap();
+        let issues = verify_audit(pool).await.unwrap();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].subject, format!("acesso {}", late.id));
+
+        // Revogado pode ser compartilhado de novo
+        queries::create_process_share(pool, &process.id, "GSUPPLIER", None).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rotate_keys_and_lock_user() {
+        let pool = test_pool().await;
+        let client = queries::create_user(&pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let key = generate_key();
+        let process = queries::create_process(&pool, &client.id, "NDA Alpha", &encrypt_content("segredo", &key).unwrap(), &key)
+            .await
+            .unwrap();
+
+        assert_eq!(rotate_keys(&pool, None).await.unwrap(), 1);
+        let rotated = queries::find_process_by_id(&pool, &process.id).await.unwrap().unwrap();
+        assert_ne!(rotated.encryption_key, key);
+        assert_eq!(decrypt_content(&rotated.encrypted_content, &rotated.encryption_key).unwrap(), "segredo");
+
+        assert!(set_user_locked(&pool, "cliente", true).await.unwrap().locked_at.is_some());
+        assert!(set_user_locked(&pool, "cliente", false).await.unwrap().locked_at.is_none());
+        assert!(set_user_locked(&pool, "ninguem", true).await.is_err());
+
+        let exported = export(&pool).await.unwrap().to_string();
+        assert!(!exported.contains("SCLIENT"));
+        assert!(!exported.contains(&rotated.encryption_key));
+    }
+}
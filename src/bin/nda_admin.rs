@@ -0,0 +1,258 @@
+// src/bin/nda_admin.rs
+// CLI de operação: age diretamente no banco configurado (config.toml, .env e ambiente)
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use nda_backend::config::Config;
+use nda_backend::handlers::AppState;
+use nda_backend::stellar_real::StellarClient;
+use nda_backend::{admin, database, notifier};
+
+#[derive(Parser)]
+#[command(name = "nda-admin", about = "Operações administrativas do NDA Backend")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Lista, bloqueia e desbloqueia usuários
+    #[command(subcommand)]
+    Users(UsersCommand),
+    /// Inspeciona processos
+    #[command(subcommand)]
+    Process(ProcessCommand),
+    /// Revoga compartilhamentos
+    #[command(subcommand)]
+    Shares(SharesCommand),
+    /// Auditoria dos acessos
+    #[command(subcommand)]
+    Audit(AuditCommand),
+    /// Conciliação com o ledger Stellar
+    #[command(subcommand)]
+    Ledger(LedgerCommand),
+    /// Executa as migrações pendentes
+    Migrate {
+        /// Apenas exibe a situação do schema
+        #[arg(long)]
+        status: bool,
+    },
+    /// Chaves de criptografia dos processos
+    #[command(subcommand)]
+    Keys(KeysCommand),
+    /// Exporta usuários, processos, compartilhamentos e acessos em JSON (sem segredos)
+    Export {
+        /// Arquivo de saída (padrão: saída padrão)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum UsersCommand {
+    List {
+        /// Filtra por tipo: client ou supplier
+        #[arg(long = "type")]
+        user_type: Option<String>,
+    },
+    Lock {
+        username: String,
+    },
+    Unlock {
+        username: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProcessCommand {
+    /// Exibe o processo com compartilhamentos, registro no ledger e acessos
+    Show {
+        process_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SharesCommand {
+    Revoke {
+        share_id: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum AuditCommand {
+    /// Verifica processo → compartilhamento confirmado → acessos
+    Verify,
+}
+
+#[derive(Subcommand)]
+enum LedgerCommand {
+    Reconcile {
+        /// Reenfileira os registros no ledger que falharam definitivamente
+        #[arg(long)]
+        retry_failed: bool,
+        /// Não consulta o Horizon; analisa apenas o banco
+        #[arg(long)]
+        offline: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeysCommand {
+    /// Gera novas chaves e recriptografa o conteúdo
+    Rotate {
+        /// Apenas este processo (padrão: todos)
+        #[arg(long)]
+        process: Option<String>,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    // Logs apenas de avisos, em stderr: stdout fica com a saída dos comandos
+    tracing_subscriber::fmt()
+        .with_writer(std::io::stderr)
+        .with_env_filter(tracing_subscriber::EnvFilter::new("warn"))
+        .init();
+
+    let cli = Cli::parse();
+    match run(cli.command).await {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("❌ {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Ok(false): comando executado, mas encontrou problemas (auditoria ou conciliação)
+async fn run(command: Command) -> admin::AdminResult<bool> {
+    let config = Config::load()?;
+    let pool = database::open(&config.database_url).await?;
+
+    if let Command::Migrate { status } = command {
+        if !status {
+            database::run_migrations(&pool).await?;
+        }
+        let schema = database::schema_status(&pool).await?;
+        println!("versão: {:?}", schema.current_version);
+        println!("pendentes: {:?}", schema.pending);
+        return Ok(schema.is_up_to_date());
+    }
+
+    // Os demais comandos exigem o schema atual, sem migrar implicitamente
+    let schema = database::schema_status(&pool).await?;
+    if !schema.is_up_to_date() {
+        return Err(format!("Schema desatualizado (pendentes: {:?}); execute nda-admin migrate", schema.pending).into());
+    }
+
+    match command {
+        Command::Users(UsersCommand::List { user_type }) => {
+            for user in database::queries::list_users(&pool).await? {
+                if user_type.as_ref().is_some_and(|t| *t != user.user_type) {
+                    continue;
+                }
+                let locked = if user.locked_at.is_some() { "bloqueado" } else { "ativo" };
+                println!("{}\t{}\t{}\t{}\t{}", user.id, user.username, user.user_type, user.stellar_public_key, locked);
+            }
+        }
+        Command::Users(UsersCommand::Lock { username }) => {
+            admin::set_user_locked(&pool, &username, true).await?;
+            println!("✅ {} bloqueado", username);
+        }
+        Command::Users(UsersCommand::Unlock { username }) => {
+            admin::set_user_locked(&pool, &username, false).await?;
+            println!("✅ {} desbloqueado", username);
+        }
+        Command::Process(ProcessCommand::Show { process_id }) => {
+            let report = admin::inspect_process(&pool, &process_id).await?;
+            let process = &report.process;
+            println!("processo:  {} ({})", process.id, process.title);
+            println!("status:    {}", process.status);
+            println!("criado em: {}", process.created_at);
+            match &report.client {
+                Some(client) => println!("cliente:   {} ({})", client.username, client.id),
+                None => println!("cliente:   {} (inexistente)", process.client_id),
+            }
+            println!("\ncompartilhamentos:");
+            for item in &report.shares {
+                let share = &item.share;
+                println!(
+                    "  {}\t{}\t{}\t{}",
+                    share.id,
+                    item.supplier_username.as_deref().unwrap_or(&share.supplier_public_key),
+                    share.status,
+                    share.stellar_transaction_hash.as_deref().unwrap_or("-"),
+                );
+                if let Some(entry) = &item.outbox {
+                    println!(
+                        "    ledger: {} ({} tentativas){}",
+                        entry.status,
+                        entry.attempts,
+                        entry.last_error.as_ref().map(|e| format!(" — {}", e)).unwrap_or_default(),
+                    );
+                }
+            }
+            println!("\nacessos:");
+            for access in &report.accesses {
+                println!("  {}\t{}\t{}", access.id, access.supplier_id, access.accessed_at);
+            }
+        }
+        Command::Shares(SharesCommand::Revoke { share_id }) => {
+            let mut state = AppState::new(pool).with_config(config.clone());
+            if let Some(smtp) = &config.smtp {
+                let smtp = notifier::SmtpNotifier::from_config(smtp).map_err(|e| e.to_string())?;
+                state = state.with_notifier(Arc::new(smtp));
+            }
+            let share = admin::revoke_share(&state, &share_id).await?;
+            println!("✅ compartilhamento {} revogado em {}", share.id, share.revoked_at.unwrap_or_default());
+        }
+        Command::Audit(AuditCommand::Verify) => {
+            let issues = admin::verify_audit(&pool).await?;
+            for issue in &issues {
+                println!("⚠️  {}: {}", issue.subject, issue.problem);
+            }
+            if issues.is_empty() {
+                println!("✅ cadeia de auditoria íntegra");
+            }
+            return Ok(issues.is_empty());
+        }
+        Command::Ledger(LedgerCommand::Reconcile { retry_failed, offline }) => {
+            let stellar = (!offline).then(|| StellarClient::from_config(&config.stellar));
+            let report = admin::reconcile_ledger(&pool, stellar.as_ref(), retry_failed).await?;
+            for share in &report.missing_on_ledger {
+                println!("⚠️  {}: confirmado, mas sem transação no ledger", share.id);
+            }
+            for share in &report.failed {
+                println!("⚠️  {}: registro no ledger falhou", share.id);
+            }
+            println!(
+                "confirmados verificados: {}, reenfileirados: {}{}",
+                report.checked,
+                report.requeued,
+                if offline { " (sem consulta ao Horizon)" } else { "" },
+            );
+            return Ok(report.missing_on_ledger.is_empty() && report.failed.is_empty());
+        }
+        Command::Keys(KeysCommand::Rotate { process }) => {
+            let rotated = admin::rotate_keys(&pool, process.as_deref()).await?;
+            println!("✅ {} processo(s) com chave rotacionada", rotated);
+        }
+        Command::Export { output } => {
+            let data = serde_json::to_string_pretty(&admin::export(&pool).await?)?;
+            match output {
+                Some(path) => {
+                    std::fs::write(&path, data)?;
+                    println!("✅ dados exportados para {}", path.display());
+                }
+                None => println!("{}", data),
+            }
+        }
+        Command::Migrate { .. } => unreachable!("tratado acima"),
+    }
+
+    Ok(true)
+}
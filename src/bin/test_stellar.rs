@@ -3,6 +3,11 @@ use nda_backend::stellar_real::StellarClient;  // ← Mudança aqui
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // Detalhes das chamadas ao Horizon
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::new("warn,nda_backend=debug"))
+        .init();
+
     println!("🌟 Testando Integração Stellar Testnet");
     println!("=====================================");
     
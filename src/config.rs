@@ -0,0 +1,644 @@
+// src/config.rs
+// Configuração tipada: arquivo TOML opcional + variáveis de ambiente (que têm precedência)
+use serde::Deserialize;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use crate::database::Backend;
+
+pub const CONFIG_PATH_ENV: &str = "NDA_CONFIG";
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+const TESTNET_HORIZON_URL: &str = "https://horizon-testnet.stellar.org";
+const MAINNET_HORIZON_URL: &str = "https://horizon.stellar.org";
+const TESTNET_FRIENDBOT_URL: &str = "https://friendbot.stellar.org";
+const DEFAULT_CORS_METHODS: [&str; 4] = ["GET", "POST", "PUT", "DELETE"];
+const DEFAULT_SHUTDOWN_TIMEOUT_SECS: u64 = 30;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StellarNetwork {
+    Testnet,
+    Mainnet,
+}
+
+impl StellarNetwork {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StellarNetwork::Testnet => "testnet",
+            StellarNetwork::Mainnet => "mainnet",
+        }
+    }
+
+    pub fn passphrase(&self) -> &'static str {
+        match self {
+            StellarNetwork::Testnet => "Test SDF Network ; September 2015",
+            StellarNetwork::Mainnet => "Public Global Stellar Network ; September 2015",
+        }
+    }
+}
+
+// Onde ficam as chaves secretas das carteiras Stellar dos usuários
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStore {
+    Database, // coluna users.stellar_secret_key
+}
+
+impl KeyStore {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyStore::Database => "database",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    Pretty,
+    Json,
+}
+
+impl LogFormat {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogFormat::Pretty => "pretty",
+            LogFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StellarConfig {
+    pub network: StellarNetwork,
+    pub horizon_url: String,
+    pub friendbot_url: Option<String>, // apenas testnet
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: Option<u16>,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub tls: String, // "starttls", "tls" ou "none"
+}
+
+/// Limite de uma rota: baldes de tokens por IP e, opcionalmente, por usuário
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RouteLimit {
+    pub ip_per_minute: u32,
+    pub ip_burst: u32,
+    pub user_per_minute: Option<u32>,
+    pub user_burst: Option<u32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    pub login: RouteLimit,
+    pub register: RouteLimit,
+    pub access: RouteLimit,
+    pub share: RouteLimit,
+    // Logins malsucedidos seguidos antes do bloqueio do usuário
+    pub lockout_threshold: u32,
+    pub lockout_minutes: u64,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            login: RouteLimit { ip_per_minute: 20, ip_burst: 10, user_per_minute: Some(10), user_burst: Some(5) },
+            // Cada registro chama o friendbot
+            register: RouteLimit { ip_per_minute: 5, ip_burst: 3, user_per_minute: None, user_burst: None },
+            access: RouteLimit { ip_per_minute: 60, ip_burst: 20, user_per_minute: Some(30), user_burst: Some(10) },
+            share: RouteLimit { ip_per_minute: 60, ip_burst: 20, user_per_minute: None, user_burst: None },
+            lockout_threshold: 5,
+            lockout_minutes: 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub bind_address: SocketAddr,
+    pub database_url: String,
+    pub stellar: StellarConfig,
+    pub cors_origins: Vec<String>, // vazio = nenhuma origem externa
+    pub cors_methods: Vec<String>,
+    pub tls: Option<TlsConfig>,
+    pub hsts: bool, // padrão: ativo quando há TLS; ative manualmente atrás de proxy HTTPS
+    pub key_store: KeyStore,
+    pub log_format: LogFormat,
+    // Coletor OpenTelemetry (OTLP/HTTP); usado apenas com a feature "otlp"
+    pub otlp_endpoint: Option<String>,
+    pub smtp: Option<SmtpConfig>,
+    pub rate_limit: RateLimitConfig,
+    // Tempo máximo para concluir requisições e lotes em andamento no encerramento
+    pub shutdown_timeout_secs: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            bind_address: SocketAddr::from(([0, 0, 0, 0], 3000)),
+            database_url: "sqlite:./stellar_mvp.db".to_string(),
+            stellar: StellarConfig {
+                network: StellarNetwork::Testnet,
+                horizon_url: TESTNET_HORIZON_URL.to_string(),
+                friendbot_url: Some(TESTNET_FRIENDBOT_URL.to_string()),
+            },
+            cors_origins: Vec::new(),
+            cors_methods: DEFAULT_CORS_METHODS.iter().map(|m| m.to_string()).collect(),
+            tls: None,
+            hsts: false,
+            key_store: KeyStore::Database,
+            log_format: LogFormat::Pretty,
+            otlp_endpoint: None,
+            smtp: None,
+            rate_limit: RateLimitConfig::default(),
+            shutdown_timeout_secs: DEFAULT_SHUTDOWN_TIMEOUT_SECS,
+        }
+    }
+}
+
+/// Todos os problemas encontrados na configuração, não só o primeiro
+pub struct ConfigError(pub Vec<String>);
+
+// main retorna o erro via Debug; mantém a lista legível na saída
+impl fmt::Debug for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Configuração inválida:")?;
+        for problem in &self.0 {
+            write!(f, "\n  - {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+// Formato do arquivo: todos os campos opcionais
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    bind_address: Option<String>,
+    database_url: Option<String>,
+    cors_origins: Option<Vec<String>>,
+    cors_methods: Option<Vec<String>>,
+    hsts: Option<bool>,
+    tls: Option<FileTlsConfig>,
+    key_store: Option<String>,
+    log_format: Option<String>,
+    otlp_endpoint: Option<String>,
+    shutdown_timeout_secs: Option<u64>,
+    #[serde(default)]
+    stellar: FileStellarConfig,
+    smtp: Option<FileSmtpConfig>,
+    rate_limit: Option<RateLimitConfig>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileStellarConfig {
+    network: Option<String>,
+    horizon_url: Option<String>,
+    friendbot_url: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileTlsConfig {
+    cert_path: Option<PathBuf>,
+    key_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileSmtpConfig {
+    host: Option<String>,
+    port: Option<u16>,
+    username: Option<String>,
+    password: Option<String>,
+    from: Option<String>,
+    tls: Option<String>,
+}
+
+impl Config {
+    /// Carrega .env, o arquivo de NDA_CONFIG (ou ./config.toml, se existir) e o ambiente
+    pub fn load() -> Result<Self, ConfigError> {
+        dotenv::dotenv().ok();
+
+        let path = std::env::var(CONFIG_PATH_ENV).ok().map(PathBuf::from);
+        let contents = match &path {
+            Some(path) => Some(std::fs::read_to_string(path).map_err(|e| {
+                ConfigError(vec![format!("Não foi possível ler {}: {}", path.display(), e)])
+            })?),
+            None => std::fs::read_to_string(DEFAULT_CONFIG_PATH).ok(),
+        };
+
+        Self::from_sources(contents.as_deref(), |name| std::env::var(name).ok())
+    }
+
+    /// Monta a configuração a partir do conteúdo TOML e de uma fonte de variáveis
+    pub fn from_sources(
+        file: Option<&str>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let file: FileConfig = match file {
+            Some(contents) => toml::from_str(contents)
+                .map_err(|e| ConfigError(vec![format!("Arquivo de configuração inválido: {}", e)]))?,
+            None => FileConfig::default(),
+        };
+        let env = |name: &str| env(name).filter(|value| !value.trim().is_empty());
+        let defaults = Config::default();
+        let mut problems = Vec::new();
+
+        let bind_address = match env("BIND_ADDRESS").or(file.bind_address) {
+            Some(value) => value.parse().unwrap_or_else(|_| {
+                problems.push(format!("bind_address inválido: {}", value));
+                defaults.bind_address
+            }),
+            None => defaults.bind_address,
+        };
+
+        let database_url = env("DATABASE_URL").or(file.database_url).unwrap_or(defaults.database_url);
+        if Backend::from_url(&database_url).is_err() {
+            problems.push(format!("database_url não suportada: {} (use sqlite: ou postgres://)", redact_url(&database_url)));
+        }
+
+        let network = match env("STELLAR_NETWORK").or(file.stellar.network).as_deref() {
+            None | Some("testnet") => StellarNetwork::Testnet,
+            Some("mainnet") => StellarNetwork::Mainnet,
+            Some(other) => {
+                problems.push(format!("stellar.network inválida: {} (use testnet ou mainnet)", other));
+                StellarNetwork::Testnet
+            }
+        };
+
+        let horizon_url = env("HORIZON_URL").or(file.stellar.horizon_url).unwrap_or_else(|| {
+            match network {
+                StellarNetwork::Testnet => TESTNET_HORIZON_URL,
+                StellarNetwork::Mainnet => MAINNET_HORIZON_URL,
+            }
+            .to_string()
+        });
+        if !is_http_url(&horizon_url) {
+            problems.push(format!("stellar.horizon_url inválida: {}", horizon_url));
+        }
+
+        let friendbot_url = env("FRIENDBOT_URL").or(file.stellar.friendbot_url);
+        let friendbot_url = match network {
+            StellarNetwork::Testnet => Some(friendbot_url.unwrap_or_else(|| TESTNET_FRIENDBOT_URL.to_string())),
+            StellarNetwork::Mainnet => {
+                if friendbot_url.is_some() {
+                    problems.push("stellar.friendbot_url só é suportada na testnet".to_string());
+                }
+                None
+            }
+        };
+        if let Some(url) = friendbot_url.as_deref().filter(|url| !is_http_url(url)) {
+            problems.push(format!("stellar.friendbot_url inválida: {}", url));
+        }
+
+        let cors_origins = match env("CORS_ORIGINS") {
+            Some(value) => value.split(',').map(|origin| origin.trim().to_string()).filter(|o| !o.is_empty()).collect(),
+            None => file.cors_origins.unwrap_or_default(),
+        };
+        for origin in &cors_origins {
+            if !is_origin(origin) {
+                problems.push(format!("cors_origins contém origem inválida: {}", origin));
+            }
+        }
+
+        let cors_methods: Vec<String> = match env("CORS_METHODS") {
+            Some(value) => value.split(',').map(|method| method.trim().to_uppercase()).filter(|m| !m.is_empty()).collect(),
+            None => file.cors_methods.unwrap_or(defaults.cors_methods),
+        };
+        for method in &cors_methods {
+            if method.parse::<axum::http::Method>().is_err() {
+                problems.push(format!("cors_methods contém método inválido: {}", method));
+            }
+        }
+
+        let file_tls = file.tls.unwrap_or_default();
+        let tls = match (
+            env("TLS_CERT_PATH").map(PathBuf::from).or(file_tls.cert_path),
+            env("TLS_KEY_PATH").map(PathBuf::from).or(file_tls.key_path),
+        ) {
+            (Some(cert_path), Some(key_path)) => {
+                for path in [&cert_path, &key_path] {
+                    if !path.is_file() {
+                        problems.push(format!("tls: arquivo não encontrado: {}", path.display()));
+                    }
+                }
+                Some(TlsConfig { cert_path, key_path })
+            }
+            (None, None) => None,
+            _ => {
+                problems.push("tls: informe cert_path e key_path juntos".to_string());
+                None
+            }
+        };
+
+        let hsts = match env("HSTS") {
+            Some(value) => match value.as_str() {
+                "true" | "1" => true,
+                "false" | "0" => false,
+                other => {
+                    problems.push(format!("hsts inválido: {} (use true ou false)", other));
+                    false
+                }
+            },
+            None => file.hsts.unwrap_or(tls.is_some()),
+        };
+
+        let key_store = match env("KEY_STORE").or(file.key_store).as_deref() {
+            None | Some("database") => KeyStore::Database,
+            Some(other) => {
+                problems.push(format!("key_store não suportado: {} (valores aceitos: database)", other));
+                KeyStore::Database
+            }
+        };
+
+        let log_format = match env("LOG_FORMAT").or(file.log_format).as_deref() {
+            None | Some("pretty") => LogFormat::Pretty,
+            Some("json") => LogFormat::Json,
+            Some(other) => {
+                problems.push(format!("log_format inválido: {} (use pretty ou json)", other));
+                LogFormat::Pretty
+            }
+        };
+
+        let otlp_endpoint = env("OTEL_EXPORTER_OTLP_ENDPOINT").or(file.otlp_endpoint);
+        if let Some(endpoint) = &otlp_endpoint {
+            if !is_http_url(endpoint) {
+                problems.push(format!("otlp_endpoint inválido: {}", endpoint));
+            }
+        }
+
+        let shutdown_timeout_secs = match env("SHUTDOWN_TIMEOUT_SECS") {
+            Some(value) => value.parse().unwrap_or_else(|_| {
+                problems.push(format!("shutdown_timeout_secs inválido: {}", value));
+                DEFAULT_SHUTDOWN_TIMEOUT_SECS
+            }),
+            None => file.shutdown_timeout_secs.unwrap_or(DEFAULT_SHUTDOWN_TIMEOUT_SECS),
+        };
+
+        let file_smtp = file.smtp.unwrap_or_default();
+        let smtp = env("SMTP_HOST").or(file_smtp.host).map(|host| {
+            let port = match env("SMTP_PORT") {
+                Some(value) => value.parse().map(Some).unwrap_or_else(|_| {
+                    problems.push(format!("smtp.port inválida: {}", value));
+                    None
+                }),
+                None => file_smtp.port,
+            };
+            let tls = env("SMTP_TLS").or(file_smtp.tls).unwrap_or_else(|| "starttls".to_string());
+            if !["starttls", "tls", "none"].contains(&tls.as_str()) {
+                problems.push(format!("smtp.tls inválido: {} (use starttls, tls ou none)", tls));
+            }
+            let from = env("SMTP_FROM")
+                .or(file_smtp.from)
+                .unwrap_or_else(|| "NDA Blockchain <no-reply@localhost>".to_string());
+            if from.parse::<lettre::message::Mailbox>().is_err() {
+                problems.push(format!("smtp.from inválido: {}", from));
+            }
+
+            SmtpConfig {
+                host,
+                port,
+                username: env("SMTP_USERNAME").or(file_smtp.username),
+                password: env("SMTP_PASSWORD").or(file_smtp.password),
+                from,
+                tls,
+            }
+        });
+
+        let mut rate_limit = file.rate_limit.unwrap_or_default();
+        if let Some(value) = env("RATE_LIMIT_ENABLED") {
+            match value.as_str() {
+                "true" | "1" => rate_limit.enabled = true,
+                "false" | "0" => rate_limit.enabled = false,
+                other => problems.push(format!("rate_limit.enabled inválido: {} (use true ou false)", other)),
+            }
+        }
+        for (route, limit) in [
+            ("login", &rate_limit.login),
+            ("register", &rate_limit.register),
+            ("access", &rate_limit.access),
+            ("share", &rate_limit.share),
+        ] {
+            let user_valid = match (limit.user_per_minute, limit.user_burst) {
+                (None, None) => true,
+                (Some(per_minute), Some(burst)) => per_minute > 0 && burst > 0,
+                _ => false,
+            };
+            if limit.ip_per_minute == 0 || limit.ip_burst == 0 || !user_valid {
+                problems.push(format!(
+                    "rate_limit.{}: limites devem ser maiores que zero (user_per_minute e user_burst juntos)",
+                    route
+                ));
+            }
+        }
+        if rate_limit.lockout_threshold == 0 {
+            problems.push("rate_limit.lockout_threshold deve ser maior que zero".to_string());
+        }
+
+        if !problems.is_empty() {
+            return Err(ConfigError(problems));
+        }
+
+        Ok(Config {
+            bind_address,
+            database_url,
+            stellar: StellarConfig {
+                network,
+                horizon_url,
+                friendbot_url,
+            },
+            cors_origins,
+            cors_methods,
+            tls,
+            hsts,
+            key_store,
+            log_format,
+            otlp_endpoint,
+            smtp,
+            rate_limit,
+            shutdown_timeout_secs,
+        })
+    }
+}
+
+fn is_http_url(value: &str) -> bool {
+    url::Url::parse(value).is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.host().is_some())
+}
+
+// Origem CORS: esquema + host (+ porta), sem caminho
+fn is_origin(value: &str) -> bool {
+    value == "*"
+        || url::Url::parse(value).is_ok_and(|url| {
+            matches!(url.scheme(), "http" | "https")
+                && url.host().is_some()
+                && url.path() == "/"
+                && !value.ends_with('/')
+                && url.query().is_none()
+        })
+}
+
+// Senhas de URLs nunca aparecem no log
+fn redact_url(value: &str) -> String {
+    match url::Url::parse(value) {
+        Ok(mut url) if url.password().is_some() => {
+            let _ = url.set_password(Some("***"));
+            url.to_string()
+        }
+        _ => value.to_string(),
+    }
+}
+
+// Versão para log, com segredos ocultos
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "   bind_address: {}", self.bind_address)?;
+        writeln!(f, "   database_url: {}", redact_url(&self.database_url))?;
+        writeln!(f, "   stellar.network: {}", self.stellar.network.as_str())?;
+        writeln!(f, "   stellar.horizon_url: {}", self.stellar.horizon_url)?;
+        writeln!(f, "   stellar.friendbot_url: {}", self.stellar.friendbot_url.as_deref().unwrap_or("-"))?;
+        writeln!(f, "   cors_origins: {}", if self.cors_origins.is_empty() { "-".to_string() } else { self.cors_origins.join(", ") })?;
+        writeln!(f, "   cors_methods: {}", self.cors_methods.join(", "))?;
+        match &self.tls {
+            Some(tls) => writeln!(f, "   tls: {} / {}", tls.cert_path.display(), tls.key_path.display())?,
+            None => writeln!(f, "   tls: -")?,
+        }
+        writeln!(f, "   hsts: {}", self.hsts)?;
+        if self.rate_limit.enabled {
+            writeln!(
+                f,
+                "   rate_limit: login {}/min por IP, bloqueio após {} falhas por {} min",
+                self.rate_limit.login.ip_per_minute, self.rate_limit.lockout_threshold, self.rate_limit.lockout_minutes
+            )?;
+        } else {
+            writeln!(f, "   rate_limit: desativado")?;
+        }
+        writeln!(f, "   shutdown_timeout_secs: {}", self.shutdown_timeout_secs)?;
+        writeln!(f, "   key_store: {}", self.key_store.as_str())?;
+        writeln!(f, "   otlp_endpoint: {}", self.otlp_endpoint.as_deref().unwrap_or("-"))?;
+        write!(f, "   log_format: {}", self.log_format.as_str())?;
+        if let Some(smtp) = &self.smtp {
+            write!(
+                f,
+                "\n   smtp: {}:{} ({}), usuário {}, senha {}, remetente {}",
+                smtp.host,
+                smtp.port.map(|p| p.to_string()).unwrap_or_else(|| "padrão".to_string()),
+                smtp.tls,
+                smtp.username.as_deref().unwrap_or("-"),
+                if smtp.password.is_some() { "***" } else { "-" },
+                smtp.from,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn load(file: Option<&str>, env: &[(&str, &str)]) -> Result<Config, ConfigError> {
+        let env: HashMap<String, String> = env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        Config::from_sources(file, |name| env.get(name).cloned())
+    }
+
+    #[test]
+    fn test_env_overrides_file() {
+        let file = r#"
+            bind_address = "127.0.0.1:8080"
+            cors_origins = ["https://app.empresa.com"]
+            [stellar]
+            network = "mainnet"
+            [rate_limit]
+            lockout_threshold = 3
+            [rate_limit.login]
+            ip_per_minute = 5
+            ip_burst = 5
+        "#;
+        let config = load(Some(file), &[("BIND_ADDRESS", "0.0.0.0:9000"), ("LOG_FORMAT", "json")]).unwrap();
+
+        assert_eq!(config.bind_address.port(), 9000);
+        assert_eq!(config.log_format, LogFormat::Json);
+        assert_eq!(config.cors_origins, vec!["https://app.empresa.com"]);
+        assert_eq!(config.stellar.network, StellarNetwork::Mainnet);
+        assert_eq!(config.stellar.horizon_url, MAINNET_HORIZON_URL);
+        assert!(config.stellar.friendbot_url.is_none());
+        assert_eq!(config.rate_limit.lockout_threshold, 3);
+        assert_eq!(config.rate_limit.login.ip_per_minute, 5);
+        assert_eq!(config.rate_limit.login.user_per_minute, None);
+        assert_eq!(config.rate_limit.access, RateLimitConfig::default().access);
+    }
+
+    #[test]
+    fn test_reports_every_invalid_value() {
+        let error = load(
+            None,
+            &[
+                ("BIND_ADDRESS", "localhost"),
+                ("DATABASE_URL", "mysql://db"),
+                ("CORS_ORIGINS", "https://ok.com, https://bad.com/path"),
+                ("KEY_STORE", "vault"),
+            ],
+        )
+        .unwrap_err();
+
+        assert_eq!(error.0.len(), 4, "{}", error);
+    }
+
+    #[test]
+    fn test_tls_requires_both_files_and_enables_hsts() {
+        let error = load(None, &[("TLS_CERT_PATH", "/nao/existe.pem")]).unwrap_err();
+        assert_eq!(error.0, vec!["tls: informe cert_path e key_path juntos"]);
+
+        let cert = std::env::temp_dir().join(format!("nda-cert-{}.pem", uuid::Uuid::new_v4()));
+        std::fs::write(&cert, "x").unwrap();
+        let path = cert.to_str().unwrap();
+        let config = load(None, &[("TLS_CERT_PATH", path), ("TLS_KEY_PATH", path)]).unwrap();
+        assert!(config.tls.is_some());
+        assert!(config.hsts);
+        std::fs::remove_file(cert).unwrap();
+    }
+
+    #[test]
+    fn test_display_redacts_secrets() {
+        let config = load(
+            None,
+            &[
+                ("DATABASE_URL", "postgres://nda:segredo@db/nda"),
+                ("SMTP_HOST", "smtp.empresa.com"),
+                ("SMTP_PASSWORD", "senha-smtp"),
+            ],
+        )
+        .unwrap();
+        let printed = config.to_string();
+
+        assert!(printed.contains("postgres://nda:***@db/nda"));
+        assert!(!printed.contains("segredo"));
+        assert!(!printed.contains("senha-smtp"));
+    }
+}
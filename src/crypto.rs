@@ -6,6 +6,8 @@ use aes_gcm::{
 use base64::{engine::general_purpose, Engine as _};
 use rand::Rng;
 
+use crate::metrics::metrics;
+
 #[derive(Debug)]
 pub struct CryptoError(String);
 
@@ -18,11 +20,13 @@ impl std::fmt::Display for CryptoError {
 impl std::error::Error for CryptoError {}
 
 pub fn generate_key() -> String {
+    let _timer = metrics().crypto_timer("generate_key");
     let key = Aes256Gcm::generate_key(OsRng);
     general_purpose::STANDARD.encode(key)
 }
 
-pub fn encrypt_content(content: &str, key: &str) -> Result<String, Box<dyn std::error::Error>> {
+pub fn encrypt_content(content: &str, key: &str) -> Result<String, CryptoError> {
+    let _timer = metrics().crypto_timer("encrypt");
     let key_bytes = general_purpose::STANDARD.decode(key)
         .map_err(|e| CryptoError(format!("Erro ao decodificar chave: {}", e)))?;
     
@@ -42,7 +46,8 @@ pub fn encrypt_content(content: &str, key: &str) -> Result<String, Box<dyn std::
     Ok(general_purpose::STANDARD.encode(encrypted_data))
 }
 
-pub fn decrypt_content(encrypted_content: &str, key: &str) -> Result<String, Box<dyn std::error::Error>> {
+pub fn decrypt_content(encrypted_content: &str, key: &str) -> Result<String, CryptoError> {
+    let _timer = metrics().crypto_timer("decrypt");
     let key_bytes = general_purpose::STANDARD.decode(key)
         .map_err(|e| CryptoError(format!("Erro ao decodificar chave: {}", e)))?;
     
@@ -53,7 +58,7 @@ pub fn decrypt_content(encrypted_content: &str, key: &str) -> Result<String, Box
         .map_err(|e| CryptoError(format!("Erro ao decodificar dados: {}", e)))?;
     
     if encrypted_data.len() < 12 {
-        return Err(Box::new(CryptoError("Dados criptografados inválidos".to_string())));
+        return Err(CryptoError("Dados criptografados inválidos".to_string()));
     }
 
     let (nonce_bytes, ciphertext) = encrypted_data.split_at(12);
@@ -63,5 +68,5 @@ pub fn decrypt_content(encrypted_content: &str, key: &str) -> Result<String, Box
         .map_err(|e| CryptoError(format!("Erro na descriptografia: {:?}", e)))?;
     
     String::from_utf8(plaintext)
-        .map_err(|e| Box::new(CryptoError(format!("Erro UTF-8: {}", e))) as Box<dyn std::error::Error>)
+        .map_err(|e| CryptoError(format!("Erro UTF-8: {}", e)))
 }
\ No newline at end of file
@@ -1,100 +1,185 @@
 // src/database.rs
-use sqlx::{SqlitePool, migrate::MigrateDatabase, Sqlite};
+use sqlx::{
+    any::{AnyPoolOptions, AnyRow},
+    migrate::{MigrateDatabase, Migrator},
+    Any, AnyPool,
+};
 use std::error::Error;
 use chrono::{DateTime, Utc};
 
-pub async fn init_database() -> Result<SqlitePool, Box<dyn Error>> {
-    let database_url = std::env::var("DATABASE_URL")
-        .unwrap_or_else(|_| "sqlite:./stellar_mvp.db".to_string());
-    
-    // Criar banco se não existir
-    if !Sqlite::database_exists(&database_url).await.unwrap_or(false) {
-        println!("🔨 Criando banco de dados...");
-        match Sqlite::create_database(&database_url).await {
-            Ok(_) => println!("✅ Banco criado com sucesso"),
-            Err(error) => panic!("❌ Erro ao criar banco: {}", error),
+use crate::config::Config;
+
+// Pool genérico: SQLite (desenvolvimento) ou PostgreSQL (produção), conforme DATABASE_URL.
+// As queries usam placeholders $N e apenas TEXT/INTEGER, suportados pelos dois bancos.
+pub type DbPool = AnyPool;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    Sqlite,
+    Postgres,
+}
+
+impl Backend {
+    pub fn from_url(database_url: &str) -> Result<Self, Box<dyn Error>> {
+        match database_url.split(':').next() {
+            Some("sqlite") => Ok(Backend::Sqlite),
+            Some("postgres") | Some("postgresql") => Ok(Backend::Postgres),
+            _ => Err(format!("Banco de dados não suportado: {}", database_url).into()),
+        }
+    }
+
+    pub fn of(pool: &DbPool) -> Self {
+        match pool.connect_options().database_url.scheme() {
+            "postgres" | "postgresql" => Backend::Postgres,
+            _ => Backend::Sqlite,
+        }
+    }
+
+    pub fn migrator(&self) -> &'static Migrator {
+        match self {
+            Backend::Sqlite => &SQLITE_MIGRATOR,
+            Backend::Postgres => &POSTGRES_MIGRATOR,
         }
     }
-    
-    let pool = SqlitePool::connect(&database_url).await?;
-    
+}
+
+pub async fn init_database(config: &Config) -> Result<DbPool, Box<dyn Error>> {
+    connect(&config.database_url).await
+}
+
+pub async fn connect(database_url: &str) -> Result<DbPool, Box<dyn Error>> {
+    let pool = open(database_url).await?;
+
     // Executar migrações
     run_migrations(&pool).await?;
-    
+
     Ok(pool)
 }
 
-async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
-    println!("🔄 Executando migrações...");
-    
-    // Criar tabela de usuários
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS users (
-            id TEXT PRIMARY KEY,
-            username TEXT UNIQUE NOT NULL,
-            stellar_public_key TEXT UNIQUE NOT NULL,
-            stellar_secret_key TEXT NOT NULL,
-            user_type TEXT NOT NULL CHECK (user_type IN ('client', 'supplier')),
-            created_at TEXT NOT NULL
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Criar tabela de processos
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS processes (
-            id TEXT PRIMARY KEY,
-            client_id TEXT NOT NULL,
-            title TEXT NOT NULL,
-            encrypted_content TEXT NOT NULL,
-            encryption_key TEXT NOT NULL,
-            status TEXT NOT NULL DEFAULT 'active',
-            created_at TEXT NOT NULL
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Criar tabela de compartilhamentos
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS process_shares (
-            id TEXT PRIMARY KEY,
-            process_id TEXT NOT NULL,
-            supplier_public_key TEXT NOT NULL,
-            stellar_transaction_hash TEXT NOT NULL,
-            shared_at TEXT NOT NULL
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    // Criar tabela de acessos
-    sqlx::query(
-        r#"
-        CREATE TABLE IF NOT EXISTS process_accesses (
-            id TEXT PRIMARY KEY,
-            process_id TEXT NOT NULL,
-            supplier_id TEXT NOT NULL,
-            accessed_at TEXT NOT NULL
-        )
-        "#,
-    )
-    .execute(pool)
-    .await?;
-
-    println!("✅ Migrações executadas com sucesso!");
+/// Conecta (criando o banco se não existir) sem executar migrações
+pub async fn open(database_url: &str) -> Result<DbPool, Box<dyn Error>> {
+    sqlx::any::install_default_drivers();
+    let backend = Backend::from_url(database_url)?;
+    tracing::info!(backend = ?backend, "conectando ao banco de dados");
+
+    // Criar banco se não existir
+    if !Any::database_exists(database_url).await.unwrap_or(false) {
+        tracing::info!("criando banco de dados");
+        match Any::create_database(database_url).await {
+            Ok(_) => tracing::info!("banco criado"),
+            Err(error) => panic!("❌ Erro ao criar banco: {}", error),
+        }
+    }
+
+    Ok(AnyPoolOptions::new().connect(database_url).await?)
+}
+
+// Migrações versionadas por banco, embutidas no binário
+pub static SQLITE_MIGRATOR: Migrator = sqlx::migrate!("./migrations/sqlite");
+pub static POSTGRES_MIGRATOR: Migrator = sqlx::migrate!("./migrations/postgres");
+
+// Situação do schema do banco em relação às migrações conhecidas pelo binário
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaStatus {
+    pub current_version: Option<i64>,
+    pub pending: Vec<i64>,
+    pub unknown: Vec<i64>, // aplicadas no banco mas desconhecidas (binário mais antigo)
+    pub dirty: Vec<i64>,   // migrações que falharam no meio da execução
+    pub legacy: bool,      // tabelas criadas antes das migrações versionadas
+}
+
+impl SchemaStatus {
+    pub fn is_compatible(&self) -> bool {
+        self.unknown.is_empty() && self.dirty.is_empty()
+    }
+
+    pub fn is_up_to_date(&self) -> bool {
+        self.is_compatible() && self.pending.is_empty()
+    }
+}
+
+async fn table_exists(pool: &DbPool, table: &str) -> Result<bool, sqlx::Error> {
+    let sql = match Backend::of(pool) {
+        Backend::Sqlite => "SELECT COUNT(*) as total FROM sqlite_master WHERE type = 'table' AND name = $1",
+        Backend::Postgres => {
+            "SELECT COUNT(*) as total FROM information_schema.tables WHERE table_schema = current_schema() AND table_name = $1"
+        }
+    };
+    let row = sqlx::query(sql).bind(table).fetch_one(pool).await?;
+
+    Ok(sqlx::Row::get::<i64, _>(&row, "total") > 0)
+}
+
+pub async fn schema_status(pool: &DbPool) -> Result<SchemaStatus, sqlx::Error> {
+    let known: Vec<i64> = Backend::of(pool).migrator().iter().map(|m| m.version).collect();
+
+    if !table_exists(pool, "_sqlx_migrations").await? {
+        return Ok(SchemaStatus {
+            current_version: None,
+            pending: known,
+            unknown: vec![],
+            dirty: vec![],
+            legacy: table_exists(pool, "users").await?,
+        });
+    }
+
+    // success é BOOLEAN, tipo que o driver Any não decodifica a partir do SQLite
+    let rows = sqlx::query("SELECT version, CAST(success AS INTEGER) as success FROM _sqlx_migrations ORDER BY version")
+        .fetch_all(pool)
+        .await?;
+
+    let mut applied = Vec::new();
+    let mut dirty = Vec::new();
+    for row in rows {
+        let version: i64 = sqlx::Row::get(&row, "version");
+        let success: i64 = sqlx::Row::get(&row, "success");
+        if success != 0 {
+            applied.push(version);
+        } else {
+            dirty.push(version);
+        }
+    }
+
+    Ok(SchemaStatus {
+        current_version: applied.last().copied(),
+        pending: known.iter().filter(|v| !applied.contains(v)).copied().collect(),
+        unknown: applied.iter().filter(|v| !known.contains(v)).copied().collect(),
+        dirty,
+        legacy: false,
+    })
+}
+
+pub async fn run_migrations(pool: &DbPool) -> Result<(), Box<dyn Error>> {
+    tracing::info!("executando migrações");
+
+    // Recusar bancos com schema mais novo (ou quebrado) que o deste binário
+    let status = schema_status(pool).await?;
+    if !status.unknown.is_empty() {
+        return Err(format!(
+            "Schema do banco em versão desconhecida {:?}; atualize o binário antes de iniciar",
+            status.unknown
+        )
+        .into());
+    }
+    if !status.dirty.is_empty() {
+        return Err(format!("Migrações incompletas no banco: {:?}", status.dirty).into());
+    }
+
+    // Bancos criados pelo antigo CREATE TABLE IF NOT EXISTS manual são adotados:
+    // as migrações usam IF NOT EXISTS e apenas completam o que falta
+    if status.legacy {
+        tracing::info!("banco legado sem controle de versão detectado, adotando migrações versionadas");
+    }
+
+    Backend::of(pool).migrator().run(pool).await?;
+
+    let status = schema_status(pool).await?;
+    tracing::info!(schema_version = ?status.current_version, "migrações executadas");
     Ok(())
 }
 
 // Função auxiliar para converter DateTime para string
-fn datetime_to_string(dt: &DateTime<Utc>) -> String {
+pub(crate) fn datetime_to_string(dt: &DateTime<Utc>) -> String {
     dt.to_rfc3339()
 }
 
@@ -103,15 +188,84 @@ fn string_to_datetime(s: &str) -> Result<DateTime<Utc>, chrono::ParseError> {
     DateTime::parse_from_rfc3339(s).map(|dt| dt.with_timezone(&Utc))
 }
 
+// Converte coluna TEXT com timestamp RFC3339 para DateTime
+fn column_datetime(row: &AnyRow, column: &str) -> Result<DateTime<Utc>, sqlx::Error> {
+    use sqlx::Row;
+
+    let value: String = row.try_get(column)?;
+    string_to_datetime(&value).map_err(|_| sqlx::Error::ColumnDecode {
+        index: column.to_string(),
+        source: Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid datetime")),
+    })
+}
+
+// Mesma conversão para colunas opcionais
+fn column_optional_datetime(row: &AnyRow, column: &str) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+    use sqlx::Row;
+
+    let value: Option<String> = row.try_get(column)?;
+    match value {
+        Some(value) => string_to_datetime(&value)
+            .map(Some)
+            .map_err(|_| sqlx::Error::ColumnDecode {
+                index: column.to_string(),
+                source: Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid datetime")),
+            }),
+        None => Ok(None),
+    }
+}
+
 // Módulo de queries
 pub mod queries {
     use super::*;
+    use std::collections::HashMap;
     use crate::models::*;
+    use crate::search;
+    use crate::pagination::{
+        like_pattern, Cursor, NotificationListOptions, Page, ProcessListOptions, ProcessSortExt, ReadStatus, SortOrder,
+        SortOrderExt, SupplierShareListOptions,
+    };
     use uuid::Uuid;
     use sqlx::Row;
 
     pub async fn create_user(
-        pool: &SqlitePool,
+        pool: &DbPool,
+        username: &str,
+        stellar_public_key: &str,
+        stellar_secret_key: &str,
+        user_type: &str,
+    ) -> Result<User, sqlx::Error> {
+        let mut conn = pool.acquire().await?;
+        insert_user(&mut conn, username, stellar_public_key, stellar_secret_key, user_type).await
+    }
+
+    // Cadastro por convite: o usuário, o e-mail comprovado pelo token e os compartilhamentos
+    // convidados são gravados juntos; se algo falhar, nada fica pela metade
+    pub async fn create_invited_supplier(
+        pool: &DbPool,
+        username: &str,
+        stellar_public_key: &str,
+        stellar_secret_key: &str,
+        email: &str,
+    ) -> Result<(User, Vec<(ShareInvite, ProcessShare)>), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        let user = insert_user(&mut tx, username, stellar_public_key, stellar_secret_key, "supplier").await?;
+        sqlx::query("INSERT INTO notification_preferences (user_id, email, updated_at) VALUES ($1, $2, $3)")
+            .bind(&user.id)
+            .bind(email)
+            .bind(datetime_to_string(&user.created_at))
+            .execute(&mut *tx)
+            .await?;
+        let redeemed = redeem_share_invites(&mut tx, email, stellar_public_key).await?;
+
+        tx.commit().await?;
+
+        Ok((user, redeemed))
+    }
+
+    async fn insert_user(
+        conn: &mut sqlx::AnyConnection,
         username: &str,
         stellar_public_key: &str,
         stellar_secret_key: &str,
@@ -124,7 +278,7 @@ pub mod queries {
         sqlx::query(
             r#"
             INSERT INTO users (id, username, stellar_public_key, stellar_secret_key, user_type, created_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            VALUES ($1, $2, $3, $4, $5, $6)
             "#,
         )
         .bind(&id)
@@ -133,7 +287,7 @@ pub mod queries {
         .bind(stellar_secret_key)
         .bind(user_type)
         .bind(&created_at_str)
-        .execute(pool)
+        .execute(&mut *conn)
         .await?;
 
         Ok(User {
@@ -143,14 +297,43 @@ pub mod queries {
             stellar_secret_key: stellar_secret_key.to_string(),
             user_type: user_type.to_string(),
             created_at,
+            locked_at: None,
         })
     }
 
+    // Busca pelo e-mail cadastrado nas preferências de notificação
+    pub async fn find_user_by_email(pool: &DbPool, email: &str) -> Result<Option<User>, sqlx::Error> {
+        let row = sqlx::query(
+            r#"
+            SELECT u.* FROM users u
+            JOIN notification_preferences p ON p.user_id = u.id
+            WHERE LOWER(p.email) = $1
+            LIMIT 1
+            "#,
+        )
+        .bind(email.to_lowercase())
+        .fetch_optional(pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(User {
+                id: row.get("id"),
+                username: row.get("username"),
+                stellar_public_key: row.get("stellar_public_key"),
+                stellar_secret_key: row.get("stellar_secret_key"),
+                user_type: row.get("user_type"),
+                created_at: column_datetime(&row, "created_at")?,
+                locked_at: column_optional_datetime(&row, "locked_at")?,
+            })),
+            None => Ok(None),
+        }
+    }
+
     pub async fn find_user_by_username(
-        pool: &SqlitePool,
+        pool: &DbPool,
         username: &str,
     ) -> Result<Option<User>, sqlx::Error> {
-        let row = sqlx::query("SELECT * FROM users WHERE username = ?1")
+        let row = sqlx::query("SELECT * FROM users WHERE username = $1")
             .bind(username)
             .fetch_optional(pool)
             .await?;
@@ -171,14 +354,61 @@ pub mod queries {
                     stellar_secret_key: row.get("stellar_secret_key"),
                     user_type: row.get("user_type"),
                     created_at,
+                    locked_at: column_optional_datetime(&row, "locked_at")?,
                 }))
             },
             None => Ok(None),
         }
     }
 
+    pub async fn find_user_by_id(
+        pool: &DbPool,
+        user_id: &str,
+    ) -> Result<Option<User>, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM users WHERE id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(User {
+                id: row.get("id"),
+                username: row.get("username"),
+                stellar_public_key: row.get("stellar_public_key"),
+                stellar_secret_key: row.get("stellar_secret_key"),
+                user_type: row.get("user_type"),
+                created_at: column_datetime(&row, "created_at")?,
+                locked_at: column_optional_datetime(&row, "locked_at")?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn find_user_by_public_key(
+        pool: &DbPool,
+        stellar_public_key: &str,
+    ) -> Result<Option<User>, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM users WHERE stellar_public_key = $1")
+            .bind(stellar_public_key)
+            .fetch_optional(pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(User {
+                id: row.get("id"),
+                username: row.get("username"),
+                stellar_public_key: row.get("stellar_public_key"),
+                stellar_secret_key: row.get("stellar_secret_key"),
+                user_type: row.get("user_type"),
+                created_at: column_datetime(&row, "created_at")?,
+                locked_at: column_optional_datetime(&row, "locked_at")?,
+            })),
+            None => Ok(None),
+        }
+    }
+
     pub async fn create_process(
-        pool: &SqlitePool,
+        pool: &DbPool,
         client_id: &str,
         title: &str,
         encrypted_content: &str,
@@ -192,7 +422,7 @@ pub mod queries {
         sqlx::query(
             r#"
             INSERT INTO processes (id, client_id, title, encrypted_content, encryption_key, status, created_at)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             "#,
         )
         .bind(&id)
@@ -217,10 +447,10 @@ pub mod queries {
     }
 
     pub async fn find_process_by_id(
-        pool: &SqlitePool,
+        pool: &DbPool,
         process_id: &str,
     ) -> Result<Option<Process>, sqlx::Error> {
-        let row = sqlx::query("SELECT * FROM processes WHERE id = ?1")
+        let row = sqlx::query("SELECT * FROM processes WHERE id = $1")
             .bind(process_id)
             .fetch_optional(pool)
             .await?;
@@ -248,105 +478,710 @@ pub mod queries {
         }
     }
 
+    // Condições WHERE montadas conforme os filtros, com placeholders $N na ordem dos argumentos
+    struct Filters {
+        conditions: Vec<String>,
+        args: Vec<String>,
+    }
+
+    impl Filters {
+        fn new() -> Self {
+            Self { conditions: Vec::new(), args: Vec::new() }
+        }
+
+        fn arg(&mut self, value: impl Into<String>) -> String {
+            self.args.push(value.into());
+            format!("${}", self.args.len())
+        }
+
+        fn push(&mut self, condition: String) {
+            self.conditions.push(condition);
+        }
+
+        // Keyset: (coluna, id) depois do cursor, na direção da ordenação
+        fn push_after(&mut self, column: &str, id_column: &str, order: SortOrder, cursor: &Cursor) {
+            let op = order.after_operator();
+            let value = self.arg(cursor.value.as_str());
+            let same_value = self.arg(cursor.value.as_str());
+            let id = self.arg(cursor.id.as_str());
+            self.push(format!(
+                "({column} {op} {value} OR ({column} = {same_value} AND {id_column} {op} {id}))"
+            ));
+        }
+
+        fn where_clause(&self) -> String {
+            self.conditions.join(" AND ")
+        }
+
+        fn query<'q>(&'q self, sql: &'q str) -> sqlx::query::Query<'q, Any, sqlx::any::AnyArguments<'q>> {
+            self.args.iter().fold(sqlx::query(sql), |query, arg| query.bind(arg.as_str()))
+        }
+    }
+
     pub async fn list_processes_by_client(
-        pool: &SqlitePool,
+        pool: &DbPool,
         client_id: &str,
-    ) -> Result<Vec<Process>, sqlx::Error> {
-        let rows = sqlx::query("SELECT * FROM processes WHERE client_id = ?1 ORDER BY created_at DESC")
-            .bind(client_id)
+        options: &ProcessListOptions,
+    ) -> Result<Page<Process>, sqlx::Error> {
+        let mut filters = Filters::new();
+        let client = filters.arg(client_id);
+        filters.push(format!("p.client_id = {}", client));
+
+        if let Some(status) = &options.status {
+            let status = filters.arg(status.as_str());
+            filters.push(format!("p.status = {}", status));
+        }
+        if let Some(from) = &options.created_from {
+            let from = filters.arg(datetime_to_string(from));
+            filters.push(format!("p.created_at >= {}", from));
+        }
+        if let Some(to) = &options.created_to {
+            let to = filters.arg(datetime_to_string(to));
+            filters.push(format!("p.created_at <= {}", to));
+        }
+        if let Some(search) = &options.search {
+            let pattern = filters.arg(like_pattern(search));
+            filters.push(format!("LOWER(p.title) LIKE {} ESCAPE '\\'", pattern));
+        }
+        if let Some(tag) = &options.tag {
+            let tag = filters.arg(tag.as_str());
+            filters.push(format!("EXISTS (SELECT 1 FROM process_tags t WHERE t.process_id = p.id AND t.tag = {})", tag));
+        }
+        if let Some(category) = &options.category {
+            let category = filters.arg(category.as_str());
+            filters.push(format!("EXISTS (SELECT 1 FROM process_metadata m WHERE m.process_id = p.id AND m.category = {})", category));
+        }
+        if let Some(project_code) = &options.project_code {
+            let project_code = filters.arg(project_code.as_str());
+            filters.push(format!(
+                "EXISTS (SELECT 1 FROM process_metadata m WHERE m.process_id = p.id AND m.project_code = {})",
+                project_code
+            ));
+        }
+        if let Some(supplier) = &options.supplier {
+            let public_key = filters.arg(supplier.as_str());
+            let username = filters.arg(supplier.as_str());
+            filters.push(format!(
+                r#"EXISTS (
+                    SELECT 1 FROM process_shares s
+                    LEFT JOIN users u ON u.stellar_public_key = s.supplier_public_key
+                    WHERE s.process_id = p.id
+                      AND s.status IN ('pending', 'confirmed')
+                      AND (s.supplier_public_key = {} OR u.username = {})
+                )"#,
+                public_key, username
+            ));
+        }
+
+        // Total com os filtros, independente do cursor
+        let count_sql = format!("SELECT COUNT(*) as total FROM processes p WHERE {}", filters.where_clause());
+        let total: i64 = filters.query(&count_sql).fetch_one(pool).await?.get("total");
+
+        if let Some(cursor) = &options.after {
+            filters.push_after(options.sort.column(), "p.id", options.order, cursor);
+        }
+
+        let direction = options.order.as_sql();
+        let sql = format!(
+            "SELECT p.* FROM processes p WHERE {} ORDER BY {} {}, p.id {} LIMIT {}",
+            filters.where_clause(),
+            options.sort.column(),
+            direction,
+            direction,
+            options.limit as i64 + 1
+        );
+        let rows = filters.query(&sql).fetch_all(pool).await?;
+        let processes = rows.iter().map(process_from_row).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Page::from_items(processes, options.limit, total, |p| options.cursor_after(p)))
+    }
+
+    // Metadados de vários processos; processos sem metadados ficam fora do mapa
+    pub async fn find_process_metadata(
+        pool: &DbPool,
+        process_ids: &[String],
+    ) -> Result<HashMap<String, ProcessMetadata>, sqlx::Error> {
+        let mut metadata: HashMap<String, ProcessMetadata> = HashMap::new();
+        if process_ids.is_empty() {
+            return Ok(metadata);
+        }
+
+        let placeholders = (1..=process_ids.len()).map(|i| format!("${}", i)).collect::<Vec<_>>().join(", ");
+        let bind_ids = |sql: &str| {
+            let sql = sql.replace("{ids}", &placeholders);
+            async move {
+                process_ids
+                    .iter()
+                    .fold(sqlx::query(&sql), |query, id| query.bind(id.as_str()))
+                    .fetch_all(pool)
+                    .await
+            }
+        };
+
+        for row in bind_ids("SELECT * FROM process_metadata WHERE process_id IN ({ids})").await? {
+            let entry = metadata.entry(row.get("process_id")).or_default();
+            entry.category = row.get("category");
+            entry.counterparty_reference = row.get("counterparty_reference");
+            entry.project_code = row.get("project_code");
+        }
+        for row in bind_ids("SELECT process_id, tag FROM process_tags WHERE process_id IN ({ids}) ORDER BY tag").await? {
+            metadata.entry(row.get("process_id")).or_default().tags.push(row.get("tag"));
+        }
+        for row in bind_ids("SELECT process_id, name, value FROM process_custom_fields WHERE process_id IN ({ids})").await? {
+            metadata.entry(row.get("process_id")).or_default().custom_fields.insert(row.get("name"), row.get("value"));
+        }
+
+        Ok(metadata)
+    }
+
+    // Substitui todos os metadados do processo, incluindo o texto indexado pela busca
+    pub async fn update_process_metadata(
+        pool: &DbPool,
+        process_id: &str,
+        metadata: &ProcessMetadata,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO process_metadata (process_id, category, counterparty_reference, project_code, updated_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (process_id) DO UPDATE SET
+                category = excluded.category,
+                counterparty_reference = excluded.counterparty_reference,
+                project_code = excluded.project_code,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(process_id)
+        .bind(&metadata.category)
+        .bind(&metadata.counterparty_reference)
+        .bind(&metadata.project_code)
+        .bind(datetime_to_string(&Utc::now()))
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM process_tags WHERE process_id = $1")
+            .bind(process_id)
+            .execute(&mut *tx)
+            .await?;
+        for tag in &metadata.tags {
+            sqlx::query("INSERT INTO process_tags (process_id, tag) VALUES ($1, $2)")
+                .bind(process_id)
+                .bind(tag)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("DELETE FROM process_custom_fields WHERE process_id = $1")
+            .bind(process_id)
+            .execute(&mut *tx)
+            .await?;
+        for (name, value) in &metadata.custom_fields {
+            sqlx::query("INSERT INTO process_custom_fields (process_id, name, value) VALUES ($1, $2, $3)")
+                .bind(process_id)
+                .bind(name)
+                .bind(value)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        sqlx::query("UPDATE processes SET search_metadata = $1 WHERE id = $2")
+            .bind(metadata.search_text())
+            .bind(process_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    // Processos do usuário (como cliente) e os compartilhados com ele já confirmados (como fornecedor)
+    const SEARCH_SCOPE: &str = r#"
+        (p.client_id = $2 OR EXISTS (
+            SELECT 1 FROM process_shares s
+            WHERE s.process_id = p.id AND s.supplier_public_key = $3 AND s.status = 'confirmed'
+        ))
+    "#;
+
+    pub async fn search_processes(
+        pool: &DbPool,
+        user: &User,
+        terms: &[String],
+        limit: i64,
+        offset: i64,
+    ) -> Result<(Vec<ProcessSearchHit>, i64), sqlx::Error> {
+        let (start, end) = (search::HIGHLIGHT_START, search::HIGHLIGHT_END);
+        let (from, matches, columns, query) = match Backend::of(pool) {
+            Backend::Sqlite => (
+                "processes_fts JOIN processes p ON p.id = processes_fts.process_id",
+                "processes_fts MATCH $1",
+                format!(
+                    r#"highlight(processes_fts, 1, '{start}', '{end}') as highlight,
+                       highlight(processes_fts, 2, '{start}', '{end}') as metadata_highlight,
+                       -bm25(processes_fts) as score"#
+                ),
+                search::fts5_query(terms),
+            ),
+            Backend::Postgres => (
+                "processes p, to_tsquery('simple', $1) AS query",
+                "to_tsvector('simple', p.title || ' ' || p.search_metadata) @@ query",
+                format!(
+                    r#"ts_headline('simple', p.title, query, 'StartSel={start}, StopSel={end}, HighlightAll=true') as highlight,
+                       ts_headline('simple', p.search_metadata, query, 'StartSel={start}, StopSel={end}, HighlightAll=true') as metadata_highlight,
+                       CAST(ts_rank(to_tsvector('simple', p.title || ' ' || p.search_metadata), query) AS DOUBLE PRECISION) as score"#
+                ),
+                search::tsquery(terms),
+            ),
+        };
+
+        let count_sql = format!("SELECT COUNT(*) as total FROM {} WHERE {} AND {}", from, matches, SEARCH_SCOPE);
+        let total: i64 = sqlx::query(&count_sql)
+            .bind(&query)
+            .bind(&user.id)
+            .bind(&user.stellar_public_key)
+            .fetch_one(pool)
+            .await?
+            .get("total");
+
+        let sql = format!(
+            "SELECT p.*, {} FROM {} WHERE {} AND {} ORDER BY score DESC, p.id LIMIT {} OFFSET {}",
+            columns, from, matches, SEARCH_SCOPE, limit, offset
+        );
+        let rows = sqlx::query(&sql)
+            .bind(&query)
+            .bind(&user.id)
+            .bind(&user.stellar_public_key)
             .fetch_all(pool)
             .await?;
 
-        let mut processes = Vec::new();
-        for row in rows {
-            let created_at_str: String = row.get("created_at");
-            let created_at = string_to_datetime(&created_at_str)
-                .map_err(|_| sqlx::Error::ColumnDecode { 
-                    index: "created_at".to_string(), 
-                    source: Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid datetime")) 
-                })?;
+        let hits = rows
+            .iter()
+            .map(|row| {
+                let process = process_from_row(row)?;
+                Ok(ProcessSearchHit {
+                    id: process.id,
+                    title: process.title,
+                    status: process.status,
+                    created_at: process.created_at,
+                    highlight: search::render_highlight(&row.try_get::<String, _>("highlight")?),
+                    metadata_highlight: search::render_highlight(&row.try_get::<String, _>("metadata_highlight")?),
+                    score: row.try_get("score")?,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
 
-            processes.push(Process {
-                id: row.get("id"),
-                client_id: row.get("client_id"),
-                title: row.get("title"),
-                encrypted_content: row.get("encrypted_content"),
-                encryption_key: row.get("encryption_key"),
-                status: row.get("status"),
-                created_at,
-            });
-        }
+        Ok((hits, total))
+    }
 
-        Ok(processes)
+    fn process_share_from_row(row: &AnyRow) -> Result<ProcessShare, sqlx::Error> {
+        Ok(ProcessShare {
+            id: row.get("id"),
+            process_id: row.get("process_id"),
+            supplier_public_key: row.get("supplier_public_key"),
+            stellar_transaction_hash: row.get("stellar_transaction_hash"),
+            status: row.get("status"),
+            shared_at: column_datetime(row, "shared_at")?,
+            confirmed_at: column_optional_datetime(row, "confirmed_at")?,
+            revoked_at: column_optional_datetime(row, "revoked_at")?,
+            accepted_at: column_optional_datetime(row, "accepted_at")?,
+            expires_at: column_optional_datetime(row, "expires_at")?,
+        })
     }
 
+    // Cria o compartilhamento como 'pending' e o pedido de registro no ledger
+    // na mesma transação; o worker do outbox confirma depois
     pub async fn create_process_share(
-        pool: &SqlitePool,
+        pool: &DbPool,
         process_id: &str,
         supplier_public_key: &str,
-        stellar_transaction_hash: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<ProcessShare, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let share = insert_process_share(&mut tx, process_id, supplier_public_key, expires_at, None).await?;
+        tx.commit().await?;
+
+        Ok(share)
+    }
+
+    // Compartilhamentos em lote numa única transação do banco; cada grupo de até
+    // `batch_size` fornecedores recebe um batch_id e vai ao ledger numa só transação
+    pub async fn create_process_shares_batch(
+        pool: &DbPool,
+        process_id: &str,
+        supplier_public_keys: &[String],
+        expires_at: Option<DateTime<Utc>>,
+        batch_size: usize,
+    ) -> Result<Vec<ProcessShare>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let mut shares = Vec::with_capacity(supplier_public_keys.len());
+
+        for chunk in supplier_public_keys.chunks(batch_size.max(1)) {
+            let batch_id = Uuid::new_v4().to_string();
+            for public_key in chunk {
+                shares.push(insert_process_share(&mut tx, process_id, public_key, expires_at, Some(&batch_id)).await?);
+            }
+        }
+
+        tx.commit().await?;
+        Ok(shares)
+    }
+
+    // Compartilhamento e pedido de registro no ledger, dentro da transação de quem chama
+    async fn insert_process_share(
+        conn: &mut sqlx::AnyConnection,
+        process_id: &str,
+        supplier_public_key: &str,
+        expires_at: Option<DateTime<Utc>>,
+        batch_id: Option<&str>,
     ) -> Result<ProcessShare, sqlx::Error> {
         let id = Uuid::new_v4().to_string();
         let shared_at = Utc::now();
         let shared_at_str = datetime_to_string(&shared_at);
 
+        // Compartilhamento vencido com o mesmo fornecedor sai do índice de ativos
+        sqlx::query(
+            r#"
+            UPDATE process_shares SET status = 'expired'
+            WHERE process_id = $1 AND supplier_public_key = $2 AND status = 'confirmed'
+              AND expires_at IS NOT NULL AND expires_at <= $3
+            "#,
+        )
+        .bind(process_id)
+        .bind(supplier_public_key)
+        .bind(&shared_at_str)
+        .execute(&mut *conn)
+        .await?;
+
         sqlx::query(
             r#"
-            INSERT INTO process_shares (id, process_id, supplier_public_key, stellar_transaction_hash, shared_at)
-            VALUES (?1, ?2, ?3, ?4, ?5)
+            INSERT INTO process_shares (id, process_id, supplier_public_key, status, shared_at, expires_at)
+            VALUES ($1, $2, $3, 'pending', $4, $5)
             "#,
         )
         .bind(&id)
         .bind(process_id)
         .bind(supplier_public_key)
-        .bind(stellar_transaction_hash)
         .bind(&shared_at_str)
-        .execute(pool)
+        .bind(expires_at.as_ref().map(datetime_to_string))
+        .execute(&mut *conn)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO ledger_outbox (id, share_id, status, attempts, next_attempt_at, created_at, batch_id)
+            VALUES ($1, $2, 'pending', 0, $3, $3, $4)
+            "#,
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&id)
+        .bind(&shared_at_str)
+        .bind(batch_id)
+        .execute(&mut *conn)
         .await?;
 
         Ok(ProcessShare {
             id,
             process_id: process_id.to_string(),
             supplier_public_key: supplier_public_key.to_string(),
-            stellar_transaction_hash: stellar_transaction_hash.to_string(),
+            stellar_transaction_hash: None,
+            status: "pending".to_string(),
             shared_at,
+            confirmed_at: None,
+            revoked_at: None,
+            accepted_at: None,
+            expires_at,
         })
     }
 
-    pub async fn create_process_access(
-        pool: &SqlitePool,
+    pub async fn find_process_share(
+        pool: &DbPool,
         process_id: &str,
-        supplier_id: &str,
-    ) -> Result<ProcessAccess, sqlx::Error> {
-        let id = Uuid::new_v4().to_string();
-        let accessed_at = Utc::now();
-        let accessed_at_str = datetime_to_string(&accessed_at);
-
-        sqlx::query(
+        supplier_public_key: &str,
+    ) -> Result<Option<ProcessShare>, sqlx::Error> {
+        // O mais recente vale: um novo compartilhamento só é criado depois que o anterior
+        // falhou, foi revogado ou venceu
+        let row = sqlx::query(
             r#"
-            INSERT INTO process_accesses (id, process_id, supplier_id, accessed_at)
-            VALUES (?1, ?2, ?3, ?4)
+            SELECT * FROM process_shares
+            WHERE process_id = $1 AND supplier_public_key = $2
+            ORDER BY shared_at DESC
+            LIMIT 1
             "#,
         )
-        .bind(&id)
         .bind(process_id)
-        .bind(supplier_id)
-        .bind(&accessed_at_str)
+        .bind(supplier_public_key)
+        .fetch_optional(pool)
+        .await?;
+
+        row.as_ref().map(process_share_from_row).transpose()
+    }
+
+    pub async fn find_process_share_by_id(
+        pool: &DbPool,
+        share_id: &str,
+    ) -> Result<Option<ProcessShare>, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM process_shares WHERE id = $1")
+            .bind(share_id)
+            .fetch_optional(pool)
+            .await?;
+
+        row.as_ref().map(process_share_from_row).transpose()
+    }
+
+    pub async fn accept_process_share(pool: &DbPool, share_id: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE process_shares SET accepted_at = $1 WHERE id = $2 AND status = 'confirmed' AND accepted_at IS NULL",
+        )
+        .bind(datetime_to_string(&Utc::now()))
+        .bind(share_id)
         .execute(pool)
         .await?;
 
-        Ok(ProcessAccess {
-            id,
-            process_id: process_id.to_string(),
-            supplier_id: supplier_id.to_string(),
-            accessed_at,
-        })
+        Ok(result.rows_affected() > 0)
     }
 
-    pub async fn list_process_accesses_by_client(
-        pool: &SqlitePool,
-        client_id: &str,
-    ) -> Result<Vec<ProcessAccessWithDetails>, sqlx::Error> {
+    // Compartilhamentos de um fornecedor (exceto os que falharam no ledger), com processo,
+    // cliente e acessos, dos mais recentes para os mais antigos
+    pub async fn list_supplier_shares(
+        pool: &DbPool,
+        supplier: &User,
+        options: &SupplierShareListOptions,
+    ) -> Result<Page<SupplierProcess>, sqlx::Error> {
+        let mut filters = Filters::new();
+        let public_key = filters.arg(supplier.stellar_public_key.as_str());
+        filters.push(format!("s.supplier_public_key = {} AND s.status <> 'failed'", public_key));
+        if let Some(status) = &options.status {
+            let status = filters.arg(status.as_str());
+            filters.push(format!("s.status = {}", status));
+        }
+
+        let from = r#"
+            FROM process_shares s
+            JOIN processes p ON p.id = s.process_id
+            JOIN users c ON c.id = p.client_id
+        "#;
+        let count_sql = format!("SELECT COUNT(*) as total {} WHERE {}", from, filters.where_clause());
+        let total: i64 = filters.query(&count_sql).fetch_one(pool).await?.get("total");
+
+        if let Some(cursor) = &options.after {
+            filters.push_after("s.shared_at", "s.id", SortOrder::Desc, cursor);
+        }
+        let supplier_id = filters.arg(supplier.id.as_str());
+        let sql = format!(
+            r#"
+            SELECT s.*, p.title, c.username as client_username,
+                   (SELECT COUNT(*) FROM process_accesses a
+                    WHERE a.process_id = s.process_id AND a.supplier_id = {id}) as access_count,
+                   (SELECT MAX(a.accessed_at) FROM process_accesses a
+                    WHERE a.process_id = s.process_id AND a.supplier_id = {id}) as last_accessed_at
+            {from}
+            WHERE {where_clause}
+            ORDER BY s.shared_at DESC, s.id DESC
+            LIMIT {limit}
+            "#,
+            id = supplier_id,
+            from = from,
+            where_clause = filters.where_clause(),
+            limit = options.limit as i64 + 1
+        );
+        let rows = filters.query(&sql).fetch_all(pool).await?;
+
+        let now = Utc::now();
+        let processes = rows
+            .iter()
+            .map(|row| {
+                let share = process_share_from_row(row)?;
+                Ok(SupplierProcess {
+                    expired: share.is_expired(now),
+                    share_id: share.id,
+                    process_id: share.process_id,
+                    title: row.get("title"),
+                    client_username: row.get("client_username"),
+                    status: share.status,
+                    shared_at: share.shared_at,
+                    confirmed_at: share.confirmed_at,
+                    expires_at: share.expires_at,
+                    accepted_at: share.accepted_at,
+                    access_count: row.get("access_count"),
+                    last_accessed_at: column_optional_datetime(row, "last_accessed_at")?,
+                })
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+
+        Ok(Page::from_items(processes, options.limit, total, |p| options.cursor_after(p)))
+    }
+
+    fn share_invite_from_row(row: &AnyRow) -> Result<ShareInvite, sqlx::Error> {
+        Ok(ShareInvite {
+            id: row.get("id"),
+            process_id: row.get("process_id"),
+            client_id: row.get("client_id"),
+            email: row.get("email"),
+            status: row.get("status"),
+            share_expires_at: column_optional_datetime(row, "share_expires_at")?,
+            expires_at: column_datetime(row, "expires_at")?,
+            created_at: column_datetime(row, "created_at")?,
+            accepted_at: column_optional_datetime(row, "accepted_at")?,
+            share_id: row.get("share_id"),
+        })
+    }
+
+    // Guarda apenas o hash do token; convites vencidos do mesmo processo e
+    // e-mail são encerrados para liberar o índice de convite pendente
+    pub async fn create_share_invite(
+        pool: &DbPool,
+        process_id: &str,
+        client_id: &str,
+        email: &str,
+        token_hash: &str,
+        share_expires_at: Option<DateTime<Utc>>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<ShareInvite, sqlx::Error> {
+        let id = Uuid::new_v4().to_string();
+        let created_at = Utc::now();
+        let created_at_str = datetime_to_string(&created_at);
+
+        let mut tx = pool.begin().await?;
+
+        sqlx::query(
+            r#"
+            UPDATE share_invites SET status = 'expired'
+            WHERE process_id = $1 AND email = $2 AND status = 'pending' AND expires_at <= $3
+            "#,
+        )
+        .bind(process_id)
+        .bind(email)
+        .bind(&created_at_str)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO share_invites (id, process_id, client_id, email, token_hash, status, share_expires_at, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, 'pending', $6, $7, $8)
+            "#,
+        )
+        .bind(&id)
+        .bind(process_id)
+        .bind(client_id)
+        .bind(email)
+        .bind(token_hash)
+        .bind(share_expires_at.as_ref().map(datetime_to_string))
+        .bind(datetime_to_string(&expires_at))
+        .bind(&created_at_str)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(ShareInvite {
+            id,
+            process_id: process_id.to_string(),
+            client_id: client_id.to_string(),
+            email: email.to_string(),
+            status: "pending".to_string(),
+            share_expires_at,
+            expires_at,
+            created_at,
+            accepted_at: None,
+            share_id: None,
+        })
+    }
+
+    // Convite pendente e dentro da validade
+    pub async fn find_pending_share_invite(pool: &DbPool, token_hash: &str) -> Result<Option<ShareInvite>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT * FROM share_invites WHERE token_hash = $1 AND status = 'pending' AND expires_at > $2",
+        )
+        .bind(token_hash)
+        .bind(datetime_to_string(&Utc::now()))
+        .fetch_optional(pool)
+        .await?;
+
+        row.as_ref().map(share_invite_from_row).transpose()
+    }
+
+    // Converte os convites pendentes do e-mail em compartilhamentos, dentro da transação de quem chama
+    async fn redeem_share_invites(
+        conn: &mut sqlx::AnyConnection,
+        email: &str,
+        supplier_public_key: &str,
+    ) -> Result<Vec<(ShareInvite, ProcessShare)>, sqlx::Error> {
+        let now = Utc::now();
+        let now_str = datetime_to_string(&now);
+
+        let rows = sqlx::query(
+            "SELECT * FROM share_invites WHERE email = $1 AND status = 'pending' AND expires_at > $2 ORDER BY created_at",
+        )
+        .bind(email)
+        .bind(&now_str)
+        .fetch_all(&mut *conn)
+        .await?;
+
+        let mut redeemed = Vec::new();
+        for row in &rows {
+            let mut invite = share_invite_from_row(row)?;
+            if invite.share_expires_at.is_some_and(|expires_at| expires_at <= now) {
+                sqlx::query("UPDATE share_invites SET status = 'expired' WHERE id = $1")
+                    .bind(&invite.id)
+                    .execute(&mut *conn)
+                    .await?;
+                continue;
+            }
+
+            let share =
+                insert_process_share(conn, &invite.process_id, supplier_public_key, invite.share_expires_at, None).await?;
+            sqlx::query("UPDATE share_invites SET status = 'accepted', accepted_at = $1, share_id = $2 WHERE id = $3")
+                .bind(&now_str)
+                .bind(&share.id)
+                .bind(&invite.id)
+                .execute(&mut *conn)
+                .await?;
+
+            invite.status = "accepted".to_string();
+            invite.accepted_at = Some(now);
+            invite.share_id = Some(share.id.clone());
+            redeemed.push((invite, share));
+        }
+
+        Ok(redeemed)
+    }
+
+    pub async fn create_process_access(
+        pool: &DbPool,
+        process_id: &str,
+        supplier_id: &str,
+    ) -> Result<ProcessAccess, sqlx::Error> {
+        let id = Uuid::new_v4().to_string();
+        let accessed_at = Utc::now();
+        let accessed_at_str = datetime_to_string(&accessed_at);
+
+        sqlx::query(
+            r#"
+            INSERT INTO process_accesses (id, process_id, supplier_id, accessed_at)
+            VALUES ($1, $2, $3, $4)
+            "#,
+        )
+        .bind(&id)
+        .bind(process_id)
+        .bind(supplier_id)
+        .bind(&accessed_at_str)
+        .execute(pool)
+        .await?;
+
+        Ok(ProcessAccess {
+            id,
+            process_id: process_id.to_string(),
+            supplier_id: supplier_id.to_string(),
+            accessed_at,
+        })
+    }
+
+    pub async fn list_process_accesses_by_client(
+        pool: &DbPool,
+        client_id: &str,
+    ) -> Result<Vec<ProcessAccessWithDetails>, sqlx::Error> {
         let rows = sqlx::query(
             r#"
             SELECT 
@@ -359,7 +1194,7 @@ pub mod queries {
             FROM process_accesses pa
             JOIN processes p ON pa.process_id = p.id
             JOIN users u ON pa.supplier_id = u.id
-            WHERE p.client_id = ?1
+            WHERE p.client_id = $1
             ORDER BY pa.accessed_at DESC
             "#,
         )
@@ -389,5 +1224,1547 @@ pub mod queries {
         Ok(accesses)
     }
 
-}
+    pub async fn create_notification(
+        pool: &DbPool,
+        recipient_id: &str,
+        event: NotificationEvent,
+        process_id: &str,
+        actor_id: Option<&str>,
+        message: &str,
+    ) -> Result<String, sqlx::Error> {
+        let id = Uuid::new_v4().to_string();
+        let created_at_str = datetime_to_string(&Utc::now());
+
+        sqlx::query(
+            r#"
+            INSERT INTO notifications (id, recipient_id, event_type, process_id, actor_id, message, is_read, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, 0, $7)
+            "#,
+        )
+        .bind(&id)
+        .bind(recipient_id)
+        .bind(event.as_str())
+        .bind(process_id)
+        .bind(actor_id)
+        .bind(message)
+        .bind(&created_at_str)
+        .execute(pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    const NOTIFICATION_SELECT: &str = r#"
+        SELECT 
+            n.id,
+            n.recipient_id,
+            n.event_type,
+            n.process_id,
+            COALESCE(p.title, '') as process_title,
+            n.actor_id,
+            u.username as actor_username,
+            n.message,
+            n.is_read,
+            n.created_at,
+            n.read_at
+        FROM notifications n
+        LEFT JOIN processes p ON n.process_id = p.id
+        LEFT JOIN users u ON n.actor_id = u.id
+    "#;
+
+    fn notification_from_row(row: &AnyRow) -> Result<Notification, sqlx::Error> {
+        let event_type: String = row.get("event_type");
+        let event_type = NotificationEvent::parse(&event_type)
+            .ok_or_else(|| sqlx::Error::ColumnDecode {
+                index: "event_type".to_string(),
+                source: Box::new(std::io::Error::new(std::io::ErrorKind::InvalidData, "Invalid event type")),
+            })?;
+
+        Ok(Notification {
+            id: row.get("id"),
+            recipient_id: row.get("recipient_id"),
+            event_type,
+            process_id: row.get("process_id"),
+            process_title: row.get("process_title"),
+            actor_id: row.get("actor_id"),
+            actor_username: row.get("actor_username"),
+            message: row.get("message"),
+            is_read: row.get::<i64, _>("is_read") != 0,
+            created_at: column_datetime(row, "created_at")?,
+            read_at: column_optional_datetime(row, "read_at")?,
+        })
+    }
+
+    pub async fn find_notification_by_id(
+        pool: &DbPool,
+        notification_id: &str,
+    ) -> Result<Option<Notification>, sqlx::Error> {
+        let row = sqlx::query(&format!("{} WHERE n.id = $1", NOTIFICATION_SELECT))
+            .bind(notification_id)
+            .fetch_optional(pool)
+            .await?;
+
+        row.as_ref().map(notification_from_row).transpose()
+    }
+
+    pub async fn list_notifications_for_user(
+        pool: &DbPool,
+        recipient_id: &str,
+        options: &NotificationListOptions,
+    ) -> Result<Page<Notification>, sqlx::Error> {
+        let mut filters = Filters::new();
+        let recipient = filters.arg(recipient_id);
+        filters.push(format!("n.recipient_id = {}", recipient));
+
+        match options.status {
+            Some(ReadStatus::Read) => filters.push("n.is_read = 1".to_string()),
+            Some(ReadStatus::Unread) => filters.push("n.is_read = 0".to_string()),
+            None => {}
+        }
+        if let Some(event) = options.event {
+            let event = filters.arg(event.as_str());
+            filters.push(format!("n.event_type = {}", event));
+        }
+        if let Some(from) = &options.created_from {
+            let from = filters.arg(datetime_to_string(from));
+            filters.push(format!("n.created_at >= {}", from));
+        }
+        if let Some(to) = &options.created_to {
+            let to = filters.arg(datetime_to_string(to));
+            filters.push(format!("n.created_at <= {}", to));
+        }
+        if let Some(search) = &options.search {
+            let pattern = filters.arg(like_pattern(search));
+            filters.push(format!("LOWER(COALESCE(p.title, '')) LIKE {} ESCAPE '\\'", pattern));
+        }
+        if let Some(supplier) = &options.supplier {
+            let supplier = filters.arg(supplier.as_str());
+            filters.push(format!("u.username = {}", supplier));
+        }
 
+        let count_sql = format!(
+            r#"
+            SELECT COUNT(*) as total
+            FROM notifications n
+            LEFT JOIN processes p ON n.process_id = p.id
+            LEFT JOIN users u ON n.actor_id = u.id
+            WHERE {}
+            "#,
+            filters.where_clause()
+        );
+        let total: i64 = filters.query(&count_sql).fetch_one(pool).await?.get("total");
+
+        // Com cursor a posição vem dele; sem cursor vale o offset da paginação por página
+        let offset = match &options.after {
+            Some(cursor) => {
+                filters.push_after("n.created_at", "n.id", options.order, cursor);
+                0
+            }
+            None => options.offset,
+        };
+
+        let direction = options.order.as_sql();
+        let sql = format!(
+            "{} WHERE {} ORDER BY n.created_at {}, n.id {} LIMIT {} OFFSET {}",
+            NOTIFICATION_SELECT,
+            filters.where_clause(),
+            direction,
+            direction,
+            options.limit as i64 + 1,
+            offset
+        );
+        let rows = filters.query(&sql).fetch_all(pool).await?;
+        let notifications = rows.iter().map(notification_from_row).collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Page::from_items(notifications, options.limit, total, |n| options.cursor_after(n)))
+    }
+
+    pub async fn count_notifications_for_user(
+        pool: &DbPool,
+        recipient_id: &str,
+        unread_only: bool,
+    ) -> Result<i64, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT COUNT(*) as total FROM notifications WHERE recipient_id = $1 AND ($2 = 0 OR is_read = 0)",
+        )
+        .bind(recipient_id)
+        .bind(unread_only as i64)
+        .fetch_one(pool)
+        .await?;
+
+        Ok(row.get("total"))
+    }
+
+    pub async fn mark_notification_read(
+        pool: &DbPool,
+        notification_id: &str,
+        recipient_id: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let read_at_str = datetime_to_string(&Utc::now());
+
+        let result = sqlx::query(
+            r#"
+            UPDATE notifications
+            SET is_read = 1, read_at = COALESCE(read_at, $1)
+            WHERE id = $2 AND recipient_id = $3
+            "#,
+        )
+        .bind(&read_at_str)
+        .bind(notification_id)
+        .bind(recipient_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn mark_all_notifications_read(
+        pool: &DbPool,
+        recipient_id: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let read_at_str = datetime_to_string(&Utc::now());
+
+        let result = sqlx::query(
+            "UPDATE notifications SET is_read = 1, read_at = $1 WHERE recipient_id = $2 AND is_read = 0",
+        )
+        .bind(&read_at_str)
+        .bind(recipient_id)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    fn webhook_endpoint_from_row(row: &AnyRow) -> Result<WebhookEndpoint, sqlx::Error> {
+        let events: String = row.get("events");
+
+        Ok(WebhookEndpoint {
+            id: row.get("id"),
+            client_id: row.get("client_id"),
+            url: row.get("url"),
+            secret: row.get("secret"),
+            events: events
+                .split(',')
+                .filter(|e| !e.is_empty())
+                .map(|e| e.to_string())
+                .collect(),
+            active: row.get::<i64, _>("active") != 0,
+            created_at: column_datetime(row, "created_at")?,
+        })
+    }
+
+    fn webhook_delivery_from_row(row: &AnyRow) -> Result<WebhookDelivery, sqlx::Error> {
+        Ok(WebhookDelivery {
+            id: row.get("id"),
+            endpoint_id: row.get("endpoint_id"),
+            event_type: row.get("event_type"),
+            payload: row.get("payload"),
+            status: row.get("status"),
+            attempts: row.get("attempts"),
+            next_attempt_at: column_optional_datetime(row, "next_attempt_at")?,
+            last_status_code: row.get("last_status_code"),
+            last_error: row.get("last_error"),
+            created_at: column_datetime(row, "created_at")?,
+            delivered_at: column_optional_datetime(row, "delivered_at")?,
+        })
+    }
+
+    pub async fn create_webhook_endpoint(
+        pool: &DbPool,
+        client_id: &str,
+        url: &str,
+        secret: &str,
+        events: &[String],
+    ) -> Result<WebhookEndpoint, sqlx::Error> {
+        let id = Uuid::new_v4().to_string();
+        let created_at = Utc::now();
+        let created_at_str = datetime_to_string(&created_at);
+
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_endpoints (id, client_id, url, secret, events, active, created_at)
+            VALUES ($1, $2, $3, $4, $5, 1, $6)
+            "#,
+        )
+        .bind(&id)
+        .bind(client_id)
+        .bind(url)
+        .bind(secret)
+        .bind(events.join(","))
+        .bind(&created_at_str)
+        .execute(pool)
+        .await?;
+
+        Ok(WebhookEndpoint {
+            id,
+            client_id: client_id.to_string(),
+            url: url.to_string(),
+            secret: secret.to_string(),
+            events: events.to_vec(),
+            active: true,
+            created_at,
+        })
+    }
+
+    pub async fn find_webhook_endpoint(
+        pool: &DbPool,
+        endpoint_id: &str,
+    ) -> Result<Option<WebhookEndpoint>, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM webhook_endpoints WHERE id = $1")
+            .bind(endpoint_id)
+            .fetch_optional(pool)
+            .await?;
+
+        row.as_ref().map(webhook_endpoint_from_row).transpose()
+    }
+
+    pub async fn list_webhook_endpoints_by_client(
+        pool: &DbPool,
+        client_id: &str,
+    ) -> Result<Vec<WebhookEndpoint>, sqlx::Error> {
+        let rows = sqlx::query("SELECT * FROM webhook_endpoints WHERE client_id = $1 ORDER BY created_at DESC")
+            .bind(client_id)
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(webhook_endpoint_from_row).collect()
+    }
+
+    pub async fn deactivate_webhook_endpoint(
+        pool: &DbPool,
+        endpoint_id: &str,
+        client_id: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("UPDATE webhook_endpoints SET active = 0 WHERE id = $1 AND client_id = $2")
+            .bind(endpoint_id)
+            .bind(client_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn create_webhook_delivery(
+        pool: &DbPool,
+        endpoint_id: &str,
+        event_type: &str,
+        payload: &str,
+    ) -> Result<String, sqlx::Error> {
+        let id = Uuid::new_v4().to_string();
+        let now_str = datetime_to_string(&Utc::now());
+
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (id, endpoint_id, event_type, payload, status, attempts, next_attempt_at, created_at)
+            VALUES ($1, $2, $3, $4, 'pending', 0, $5, $5)
+            "#,
+        )
+        .bind(&id)
+        .bind(endpoint_id)
+        .bind(event_type)
+        .bind(payload)
+        .bind(&now_str)
+        .execute(pool)
+        .await?;
+
+        Ok(id)
+    }
+
+    pub async fn list_due_webhook_deliveries(
+        pool: &DbPool,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+        let now_str = datetime_to_string(&Utc::now());
+
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM webhook_deliveries
+            WHERE status = 'pending' AND next_attempt_at <= $1
+            ORDER BY next_attempt_at
+            LIMIT $2
+            "#,
+        )
+        .bind(&now_str)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(webhook_delivery_from_row).collect()
+    }
+
+    pub async fn list_webhook_deliveries_by_endpoint(
+        pool: &DbPool,
+        endpoint_id: &str,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT * FROM webhook_deliveries WHERE endpoint_id = $1 ORDER BY created_at DESC LIMIT $2",
+        )
+        .bind(endpoint_id)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(webhook_delivery_from_row).collect()
+    }
+
+    pub async fn record_webhook_attempt(
+        pool: &DbPool,
+        delivery_id: &str,
+        status: &str,
+        status_code: Option<i64>,
+        error: Option<&str>,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error> {
+        let now_str = datetime_to_string(&Utc::now());
+        let next_attempt_str = next_attempt_at.as_ref().map(datetime_to_string);
+        let delivered_at = (status == "delivered").then_some(now_str);
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = $1, attempts = attempts + 1, last_status_code = $2, last_error = $3,
+                next_attempt_at = $4, delivered_at = $5
+            WHERE id = $6
+            "#,
+        )
+        .bind(status)
+        .bind(status_code)
+        .bind(error)
+        .bind(next_attempt_str)
+        .bind(delivered_at)
+        .bind(delivery_id)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn ledger_outbox_from_row(row: &AnyRow) -> Result<LedgerOutboxEntry, sqlx::Error> {
+        Ok(LedgerOutboxEntry {
+            id: row.get("id"),
+            share_id: row.get("share_id"),
+            status: row.get("status"),
+            attempts: row.get("attempts"),
+            next_attempt_at: column_datetime(row, "next_attempt_at")?,
+            last_error: row.get("last_error"),
+            created_at: column_datetime(row, "created_at")?,
+            processed_at: column_optional_datetime(row, "processed_at")?,
+            batch_id: row.get("batch_id"),
+            submission_key: row.get("submission_key"),
+        })
+    }
+
+    pub async fn list_due_ledger_outbox(
+        pool: &DbPool,
+        limit: i64,
+    ) -> Result<Vec<LedgerOutboxEntry>, sqlx::Error> {
+        let now_str = datetime_to_string(&Utc::now());
+
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM ledger_outbox
+            WHERE status = 'pending' AND next_attempt_at <= $1
+            ORDER BY next_attempt_at
+            LIMIT $2
+            "#,
+        )
+        .bind(&now_str)
+        .bind(limit)
+        .fetch_all(pool)
+        .await?;
+
+        rows.iter().map(ledger_outbox_from_row).collect()
+    }
+
+    // Reserva o lote inteiro ou nada: só quando nenhuma entrada pendente dele está reservada
+    // ou agendada para depois. Reservar parte do lote faria o reenvio levar outro conjunto
+    // de destinos, com outro hash, e duplicaria no ledger os já registrados.
+    pub async fn claim_ledger_outbox_batch(
+        pool: &DbPool,
+        batch_id: &str,
+        lease_until: DateTime<Utc>,
+    ) -> Result<Vec<LedgerOutboxEntry>, sqlx::Error> {
+        let now_str = datetime_to_string(&Utc::now());
+        let lease_str = datetime_to_string(&lease_until);
+        let mut tx = pool.begin().await?;
+
+        let claimed = sqlx::query(
+            r#"
+            UPDATE ledger_outbox SET next_attempt_at = $1
+            WHERE batch_id = $2 AND status = 'pending' AND next_attempt_at <= $3
+              AND NOT EXISTS (
+                  SELECT 1 FROM ledger_outbox other
+                  WHERE other.batch_id = $2 AND other.status = 'pending' AND other.next_attempt_at > $3
+              )
+            "#,
+        )
+        .bind(&lease_str)
+        .bind(batch_id)
+        .bind(&now_str)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        let rows = if claimed == 0 {
+            Vec::new()
+        } else {
+            sqlx::query(
+                "SELECT * FROM ledger_outbox WHERE batch_id = $1 AND status = 'pending' AND next_attempt_at = $2 ORDER BY created_at, id",
+            )
+            .bind(batch_id)
+            .bind(&lease_str)
+            .fetch_all(&mut *tx)
+            .await?
+        };
+
+        tx.commit().await?;
+        rows.iter().map(ledger_outbox_from_row).collect()
+    }
+
+    pub async fn find_ledger_outbox_by_share(
+        pool: &DbPool,
+        share_id: &str,
+    ) -> Result<Option<LedgerOutboxEntry>, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM ledger_outbox WHERE share_id = $1")
+            .bind(share_id)
+            .fetch_optional(pool)
+            .await?;
+
+        row.as_ref().map(ledger_outbox_from_row).transpose()
+    }
+
+    // Reserva a entrada adiando next_attempt_at; só um worker vence a disputa
+    // e, se ele cair, a entrada volta a ficar disponível após o prazo
+    pub async fn claim_ledger_outbox(
+        pool: &DbPool,
+        entry: &LedgerOutboxEntry,
+        lease_until: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            UPDATE ledger_outbox SET next_attempt_at = $1
+            WHERE id = $2 AND status = 'pending' AND next_attempt_at = $3
+            "#,
+        )
+        .bind(datetime_to_string(&lease_until))
+        .bind(&entry.id)
+        .bind(datetime_to_string(&entry.next_attempt_at))
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    // Grava a chave de submissão antes do envio; entradas de um lote recebem a mesma chave
+    pub async fn set_ledger_outbox_submission_key(
+        pool: &DbPool,
+        entries: &[&LedgerOutboxEntry],
+        submission_key: &str,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        for entry in entries {
+            sqlx::query("UPDATE ledger_outbox SET submission_key = $1 WHERE id = $2")
+                .bind(submission_key)
+                .bind(&entry.id)
+                .execute(&mut *tx)
+                .await?;
+        }
+        tx.commit().await
+    }
+
+    pub async fn confirm_ledger_outbox(
+        pool: &DbPool,
+        entry: &LedgerOutboxEntry,
+        stellar_transaction_hash: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let confirmed = mark_ledger_outbox_done(&mut tx, entry, stellar_transaction_hash).await?;
+        tx.commit().await?;
+        Ok(confirmed)
+    }
+
+    // Lote registrado numa única transação Stellar: confirma todas as entradas juntas.
+    // Retorna, na ordem das entradas, se o compartilhamento foi confirmado (false se revogado)
+    pub async fn confirm_ledger_outbox_batch(
+        pool: &DbPool,
+        entries: &[&LedgerOutboxEntry],
+        stellar_transaction_hash: &str,
+    ) -> Result<Vec<bool>, sqlx::Error> {
+        let mut tx = pool.begin().await?;
+        let mut confirmed = Vec::with_capacity(entries.len());
+        for entry in entries {
+            confirmed.push(mark_ledger_outbox_done(&mut tx, entry, stellar_transaction_hash).await?);
+        }
+        tx.commit().await?;
+        Ok(confirmed)
+    }
+
+    async fn mark_ledger_outbox_done(
+        conn: &mut sqlx::AnyConnection,
+        entry: &LedgerOutboxEntry,
+        stellar_transaction_hash: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let now_str = datetime_to_string(&Utc::now());
+
+        // Compartilhamento revogado enquanto a transação era enviada continua revogado
+        let confirmed = sqlx::query(
+            r#"
+            UPDATE process_shares SET status = 'confirmed', stellar_transaction_hash = $1, confirmed_at = $2
+            WHERE id = $3 AND status = 'pending'
+            "#,
+        )
+        .bind(stellar_transaction_hash)
+        .bind(&now_str)
+        .bind(&entry.share_id)
+        .execute(&mut *conn)
+        .await?
+        .rows_affected()
+            > 0;
+
+        sqlx::query(
+            r#"
+            UPDATE ledger_outbox SET status = 'done', attempts = attempts + 1, last_error = NULL, processed_at = $1
+            WHERE id = $2
+            "#,
+        )
+        .bind(&now_str)
+        .bind(&entry.id)
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(confirmed)
+    }
+
+    // Registra falha: reagenda a entrada ou, sem next_attempt_at, marca-a
+    // (e o compartilhamento) como 'failed'
+    pub async fn record_ledger_outbox_failure(
+        pool: &DbPool,
+        entry: &LedgerOutboxEntry,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<(), sqlx::Error> {
+        let now_str = datetime_to_string(&Utc::now());
+        let mut tx = pool.begin().await?;
+
+        match next_attempt_at {
+            Some(next_attempt_at) => {
+                sqlx::query(
+                    "UPDATE ledger_outbox SET attempts = attempts + 1, last_error = $1, next_attempt_at = $2 WHERE id = $3",
+                )
+                .bind(error)
+                .bind(datetime_to_string(&next_attempt_at))
+                .bind(&entry.id)
+                .execute(&mut *tx)
+                .await?;
+            }
+            None => {
+                sqlx::query(
+                    r#"
+                    UPDATE ledger_outbox SET status = 'failed', attempts = attempts + 1, last_error = $1, processed_at = $2
+                    WHERE id = $3
+                    "#,
+                )
+                .bind(error)
+                .bind(&now_str)
+                .bind(&entry.id)
+                .execute(&mut *tx)
+                .await?;
+
+                sqlx::query("UPDATE process_shares SET status = 'failed' WHERE id = $1 AND status = 'pending'")
+                    .bind(&entry.share_id)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+
+        tx.commit().await
+    }
+
+    pub async fn find_idempotency_record(
+        pool: &DbPool,
+        scope: &str,
+        idempotency_key: &str,
+    ) -> Result<Option<IdempotencyRecord>, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM idempotency_keys WHERE scope = $1 AND idempotency_key = $2")
+            .bind(scope)
+            .bind(idempotency_key)
+            .fetch_optional(pool)
+            .await?;
+
+        match row {
+            Some(row) => Ok(Some(IdempotencyRecord {
+                scope: row.get("scope"),
+                idempotency_key: row.get("idempotency_key"),
+                request_hash: row.get("request_hash"),
+                status_code: row.get("status_code"),
+                response_body: row.get("response_body"),
+                created_at: column_datetime(&row, "created_at")?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    // Reserva a chave para a requisição atual; false se outra requisição já a reservou
+    pub async fn reserve_idempotency_key(
+        pool: &DbPool,
+        scope: &str,
+        idempotency_key: &str,
+        request_hash: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO idempotency_keys (scope, idempotency_key, request_hash, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (scope, idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(scope)
+        .bind(idempotency_key)
+        .bind(request_hash)
+        .bind(datetime_to_string(&Utc::now()))
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn complete_idempotency_key(
+        pool: &DbPool,
+        scope: &str,
+        idempotency_key: &str,
+        status_code: i64,
+        response_body: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE idempotency_keys SET status_code = $1, response_body = $2 WHERE scope = $3 AND idempotency_key = $4",
+        )
+        .bind(status_code)
+        .bind(response_body)
+        .bind(scope)
+        .bind(idempotency_key)
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn delete_idempotency_key(
+        pool: &DbPool,
+        scope: &str,
+        idempotency_key: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM idempotency_keys WHERE scope = $1 AND idempotency_key = $2")
+            .bind(scope)
+            .bind(idempotency_key)
+            .execute(pool)
+            .await?;
+
+        Ok(())
+    }
+
+    pub async fn purge_idempotency_keys(
+        pool: &DbPool,
+        created_before: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM idempotency_keys WHERE created_at < $1")
+            .bind(datetime_to_string(&created_before))
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn find_notification_preferences(
+        pool: &DbPool,
+        user_id: &str,
+    ) -> Result<Option<NotificationPreferences>, sqlx::Error> {
+        let row = sqlx::query("SELECT * FROM notification_preferences WHERE user_id = $1")
+            .bind(user_id)
+            .fetch_optional(pool)
+            .await?;
+
+        match row {
+            Some(row) => {
+                let email_events: String = row.get("email_events");
+
+                Ok(Some(NotificationPreferences {
+                    user_id: row.get("user_id"),
+                    email: row.get("email"),
+                    email_enabled: row.get::<i64, _>("email_enabled") != 0,
+                    language: row.get("language"),
+                    email_events: email_events
+                        .split(',')
+                        .filter_map(NotificationEvent::parse)
+                        .collect(),
+                    updated_at: Some(column_datetime(&row, "updated_at")?),
+                }))
+            },
+            None => Ok(None),
+        }
+    }
+
+    pub async fn upsert_notification_preferences(
+        pool: &DbPool,
+        preferences: &NotificationPreferences,
+    ) -> Result<NotificationPreferences, sqlx::Error> {
+        let updated_at = Utc::now();
+        let updated_at_str = datetime_to_string(&updated_at);
+        let email_events = preferences.email_events
+            .iter()
+            .map(|e| e.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+
+        sqlx::query(
+            r#"
+            INSERT INTO notification_preferences (user_id, email, email_enabled, language, email_events, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (user_id) DO UPDATE SET
+                email = excluded.email,
+                email_enabled = excluded.email_enabled,
+                language = excluded.language,
+                email_events = excluded.email_events,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&preferences.user_id)
+        .bind(&preferences.email)
+        .bind(preferences.email_enabled as i64)
+        .bind(&preferences.language)
+        .bind(&email_events)
+        .bind(&updated_at_str)
+        .execute(pool)
+        .await?;
+
+        Ok(NotificationPreferences {
+            updated_at: Some(updated_at),
+            ..preferences.clone()
+        })
+    }
+
+    // Administração (nda-admin): listagens completas e operações de manutenção
+
+    fn user_from_row(row: &AnyRow) -> Result<User, sqlx::Error> {
+        Ok(User {
+            id: row.get("id"),
+            username: row.get("username"),
+            stellar_public_key: row.get("stellar_public_key"),
+            stellar_secret_key: row.get("stellar_secret_key"),
+            user_type: row.get("user_type"),
+            created_at: column_datetime(row, "created_at")?,
+            locked_at: column_optional_datetime(row, "locked_at")?,
+        })
+    }
+
+    fn process_from_row(row: &AnyRow) -> Result<Process, sqlx::Error> {
+        Ok(Process {
+            id: row.get("id"),
+            client_id: row.get("client_id"),
+            title: row.get("title"),
+            encrypted_content: row.get("encrypted_content"),
+            encryption_key: row.get("encryption_key"),
+            status: row.get("status"),
+            created_at: column_datetime(row, "created_at")?,
+        })
+    }
+
+    fn process_access_from_row(row: &AnyRow) -> Result<ProcessAccess, sqlx::Error> {
+        Ok(ProcessAccess {
+            id: row.get("id"),
+            process_id: row.get("process_id"),
+            supplier_id: row.get("supplier_id"),
+            accessed_at: column_datetime(row, "accessed_at")?,
+        })
+    }
+
+    pub async fn list_users(pool: &DbPool) -> Result<Vec<User>, sqlx::Error> {
+        let rows = sqlx::query("SELECT * FROM users ORDER BY created_at, id")
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(user_from_row).collect()
+    }
+
+    // Bloqueia (ou desbloqueia) o usuário; retorna false se ele não existe
+    pub async fn set_user_locked(pool: &DbPool, user_id: &str, locked: bool) -> Result<bool, sqlx::Error> {
+        let locked_at = locked.then(|| datetime_to_string(&Utc::now()));
+        let result = sqlx::query("UPDATE users SET locked_at = $1 WHERE id = $2")
+            .bind(locked_at)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_processes(pool: &DbPool) -> Result<Vec<Process>, sqlx::Error> {
+        let rows = sqlx::query("SELECT * FROM processes ORDER BY created_at, id")
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(process_from_row).collect()
+    }
+
+    // Troca conteúdo e chave apenas se a chave ainda for a lida (evita sobrescrever outra rotação)
+    pub async fn update_process_encryption(
+        pool: &DbPool,
+        process_id: &str,
+        current_key: &str,
+        encrypted_content: &str,
+        encryption_key: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE processes SET encrypted_content = $1, encryption_key = $2 WHERE id = $3 AND encryption_key = $4",
+        )
+        .bind(encrypted_content)
+        .bind(encryption_key)
+        .bind(process_id)
+        .bind(current_key)
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn list_process_shares(pool: &DbPool) -> Result<Vec<ProcessShare>, sqlx::Error> {
+        let rows = sqlx::query("SELECT * FROM process_shares ORDER BY shared_at, id")
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(process_share_from_row).collect()
+    }
+
+    pub async fn list_process_shares_by_process(
+        pool: &DbPool,
+        process_id: &str,
+    ) -> Result<Vec<ProcessShare>, sqlx::Error> {
+        let rows = sqlx::query("SELECT * FROM process_shares WHERE process_id = $1 ORDER BY shared_at, id")
+            .bind(process_id)
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(process_share_from_row).collect()
+    }
+
+    // Revoga um compartilhamento ativo; um registro no ledger ainda pendente é cancelado
+    pub async fn revoke_process_share(pool: &DbPool, share_id: &str) -> Result<bool, sqlx::Error> {
+        let now_str = datetime_to_string(&Utc::now());
+        let mut tx = pool.begin().await?;
+
+        let revoked = sqlx::query(
+            r#"
+            UPDATE process_shares SET status = 'revoked', revoked_at = $1
+            WHERE id = $2 AND status IN ('pending', 'confirmed')
+            "#,
+        )
+        .bind(&now_str)
+        .bind(share_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if revoked {
+            sqlx::query(
+                r#"
+                UPDATE ledger_outbox SET status = 'failed', last_error = 'Compartilhamento revogado', processed_at = $1
+                WHERE share_id = $2 AND status = 'pending'
+                "#,
+            )
+            .bind(&now_str)
+            .bind(share_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(revoked)
+    }
+
+    pub async fn list_process_accesses(pool: &DbPool) -> Result<Vec<ProcessAccess>, sqlx::Error> {
+        let rows = sqlx::query("SELECT * FROM process_accesses ORDER BY accessed_at, id")
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(process_access_from_row).collect()
+    }
+
+    pub async fn list_process_accesses_by_process(
+        pool: &DbPool,
+        process_id: &str,
+    ) -> Result<Vec<ProcessAccess>, sqlx::Error> {
+        let rows = sqlx::query("SELECT * FROM process_accesses WHERE process_id = $1 ORDER BY accessed_at, id")
+            .bind(process_id)
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(process_access_from_row).collect()
+    }
+
+    pub async fn list_ledger_outbox(pool: &DbPool) -> Result<Vec<LedgerOutboxEntry>, sqlx::Error> {
+        let rows = sqlx::query("SELECT * FROM ledger_outbox ORDER BY created_at, id")
+            .fetch_all(pool)
+            .await?;
+
+        rows.iter().map(ledger_outbox_from_row).collect()
+    }
+
+    // Devolve à fila um registro no ledger que falhou definitivamente
+    pub async fn requeue_ledger_outbox(pool: &DbPool, share_id: &str) -> Result<bool, sqlx::Error> {
+        let now_str = datetime_to_string(&Utc::now());
+        let mut tx = pool.begin().await?;
+
+        let requeued = sqlx::query(
+            r#"
+            UPDATE ledger_outbox SET status = 'pending', attempts = 0, next_attempt_at = $1, last_error = NULL, processed_at = NULL
+            WHERE share_id = $2 AND status = 'failed'
+              AND EXISTS (SELECT 1 FROM process_shares WHERE id = $2 AND status = 'failed')
+            "#,
+        )
+        .bind(&now_str)
+        .bind(share_id)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if requeued {
+            sqlx::query("UPDATE process_shares SET status = 'pending' WHERE id = $1 AND status = 'failed'")
+                .bind(share_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(requeued)
+    }
+}
+
+// Banco SQLite em memória com o schema aplicado, para testes
+#[cfg(test)]
+pub(crate) async fn test_pool() -> DbPool {
+    sqlx::any::install_default_drivers();
+
+    // Conexão única: cada conexão em memória teria seu próprio banco
+    let pool = AnyPoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    run_migrations(&pool).await.unwrap();
+    pool
+}
+
+// Matriz de bancos para testes: SQLite sempre e, se TEST_POSTGRES_URL estiver
+// definida, um banco PostgreSQL novo (nda_test_<uuid>) no mesmo servidor
+#[cfg(test)]
+pub(crate) async fn test_pools() -> Vec<TestPool> {
+    let pools = empty_test_pools().await;
+    for pool in &pools {
+        run_migrations(pool).await.unwrap();
+    }
+    pools
+}
+
+// Mesma matriz, sem migrações aplicadas
+#[cfg(test)]
+pub(crate) async fn empty_test_pools() -> Vec<TestPool> {
+    sqlx::any::install_default_drivers();
+
+    // Conexão única: cada conexão em memória teria seu próprio banco
+    let sqlite = AnyPoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .unwrap();
+    let mut pools = vec![TestPool { pool: sqlite, drop_url: None }];
+
+    if let Ok(base_url) = std::env::var("TEST_POSTGRES_URL") {
+        let mut url = url::Url::parse(&base_url).unwrap();
+        url.set_path(&format!("/nda_test_{}", uuid::Uuid::new_v4().simple()));
+        let pool = open(url.as_str()).await.unwrap();
+        pools.push(TestPool { pool, drop_url: Some(url.to_string()) });
+    }
+
+    pools
+}
+
+// Pool de teste; o banco PostgreSQL criado para ele é removido no drop
+#[cfg(test)]
+pub(crate) struct TestPool {
+    pool: DbPool,
+    drop_url: Option<String>,
+}
+
+#[cfg(test)]
+impl std::ops::Deref for TestPool {
+    type Target = DbPool;
+
+    fn deref(&self) -> &DbPool {
+        &self.pool
+    }
+}
+
+#[cfg(test)]
+impl Drop for TestPool {
+    fn drop(&mut self) {
+        let Some(url) = self.drop_url.take() else {
+            return;
+        };
+        // Drop não é assíncrono: remove o banco num runtime próprio, fora do runtime do teste;
+        // FORCE encerra as conexões que o pool ainda mantém abertas
+        let dropped = std::thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+            runtime.block_on(Any::force_drop_database(&url)).map_err(|error| format!("{}: {}", url, error))
+        })
+        .join();
+        if let Ok(Err(error)) = dropped {
+            eprintln!("banco de teste não removido ({})", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{NotificationEvent, ProcessMetadata};
+    use crate::pagination::{
+        Cursor, NotificationListOptions, ProcessListOptions, ProcessSort, ReadStatus, SortOrder, SupplierShareListOptions,
+    };
+
+    #[tokio::test]
+    async fn test_legacy_database_is_adopted() {
+        for pool in empty_test_pools().await {
+            // Schema criado pelo antigo run_migrations manual, sem _sqlx_migrations
+            for statement in [
+                r#"
+                CREATE TABLE users (
+                    id TEXT PRIMARY KEY,
+                    username TEXT UNIQUE NOT NULL,
+                    stellar_public_key TEXT UNIQUE NOT NULL,
+                    stellar_secret_key TEXT NOT NULL,
+                    user_type TEXT NOT NULL CHECK (user_type IN ('client', 'supplier')),
+                    created_at TEXT NOT NULL
+                )
+                "#,
+                r#"
+                CREATE TABLE processes (
+                    id TEXT PRIMARY KEY,
+                    client_id TEXT NOT NULL REFERENCES users (id),
+                    title TEXT NOT NULL,
+                    encrypted_content TEXT NOT NULL,
+                    encryption_key TEXT NOT NULL,
+                    status TEXT NOT NULL DEFAULT 'active',
+                    created_at TEXT NOT NULL
+                )
+                "#,
+                r#"
+                CREATE TABLE process_accesses (
+                    id TEXT PRIMARY KEY,
+                    process_id TEXT NOT NULL REFERENCES processes (id),
+                    supplier_id TEXT NOT NULL REFERENCES users (id),
+                    accessed_at TEXT NOT NULL
+                )
+                "#,
+                "INSERT INTO users VALUES ('u1', 'legado', 'GLEGACY', 'SLEGACY', 'client', '2024-12-01T00:00:00+00:00')",
+                "INSERT INTO users VALUES ('u2', 'fornecedor', 'GSUPPLIER', 'SSUPPLIER', 'supplier', '2024-12-01T00:00:00+00:00')",
+                "INSERT INTO processes VALUES ('p1', 'u1', 'NDA Legado', 'xxx', 'key', 'active', '2024-12-01T00:00:00+00:00')",
+                "INSERT INTO process_accesses VALUES ('a1', 'p1', 'u2', '2024-12-02T00:00:00+00:00')",
+            ] {
+                sqlx::query(statement).execute(&*pool).await.unwrap();
+            }
+
+            assert!(schema_status(&pool).await.unwrap().legacy);
+            run_migrations(&pool).await.unwrap();
+
+            let status = schema_status(&pool).await.unwrap();
+            assert!(status.is_up_to_date());
+            assert_eq!(status.current_version, Backend::of(&pool).migrator().iter().map(|m| m.version).max());
+            assert!(queries::find_user_by_username(&pool, "legado").await.unwrap().is_some());
+
+            // Acessos anteriores às notificações viram notificações do cliente
+            let notifications = queries::list_notifications_for_user(&pool, "u1", &NotificationListOptions::default())
+                .await
+                .unwrap();
+            assert_eq!(notifications.total, 1);
+            assert_eq!(notifications.items[0].event_type, NotificationEvent::Accessed);
+            assert_eq!(notifications.items[0].message, "Fornecedor fornecedor acessou o processo 'NDA Legado'");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_refuses_unknown_schema_version() {
+        let pool = test_pool().await;
+        sqlx::query(
+            r#"
+            INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time)
+            VALUES (99991231000001, 'futura', 1, x'00', 0)
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let status = schema_status(&pool).await.unwrap();
+        assert_eq!(status.unknown, vec![99991231000001]);
+        assert!(!status.is_compatible());
+        assert!(run_migrations(&pool).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_notifications_read_state() {
+        for pool in test_pools().await {
+            let client = queries::create_user(&pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+            let supplier = queries::create_user(&pool, "fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+            let process = queries::create_process(&pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+
+            let first = queries::create_notification(
+                &pool, &client.id, NotificationEvent::Accessed, &process.id, Some(&supplier.id), "acessou",
+            ).await.unwrap();
+            queries::create_notification(
+                &pool, &client.id, NotificationEvent::Denied, &process.id, Some(&supplier.id), "negado",
+            ).await.unwrap();
+            queries::create_notification(
+                &pool, &supplier.id, NotificationEvent::Granted, &process.id, Some(&client.id), "recebeu acesso",
+            ).await.unwrap();
+
+            assert_eq!(queries::count_notifications_for_user(&pool, &client.id, true).await.unwrap(), 2);
+
+            // Destinatário errado não pode marcar como lida
+            assert!(!queries::mark_notification_read(&pool, &first, &supplier.id).await.unwrap());
+            assert!(queries::mark_notification_read(&pool, &first, &client.id).await.unwrap());
+
+            let unread_only = NotificationListOptions { status: Some(ReadStatus::Unread), ..Default::default() };
+            let unread = queries::list_notifications_for_user(&pool, &client.id, &unread_only).await.unwrap().items;
+            assert_eq!(unread.len(), 1);
+            assert_eq!(unread[0].event_type, NotificationEvent::Denied);
+            assert_eq!(unread[0].process_title, "NDA Alpha");
+            assert_eq!(unread[0].actor_username.as_deref(), Some("fornecedor"));
+
+            let second_page = NotificationListOptions { offset: 1, limit: 1, ..Default::default() };
+            let all = queries::list_notifications_for_user(&pool, &client.id, &second_page).await.unwrap();
+            assert_eq!(all.items.len(), 1);
+            assert_eq!(all.total, 2);
+            assert!(all.next_cursor.is_none());
+
+            let accessed = NotificationListOptions { event: Some(NotificationEvent::Accessed), ..Default::default() };
+            let accessed = queries::list_notifications_for_user(&pool, &client.id, &accessed).await.unwrap();
+            assert_eq!(accessed.items[0].id, first);
+            assert_eq!(accessed.total, 1);
+
+            assert_eq!(queries::mark_all_notifications_read(&pool, &client.id).await.unwrap(), 1);
+            assert_eq!(queries::count_notifications_for_user(&pool, &client.id, true).await.unwrap(), 0);
+            assert_eq!(queries::count_notifications_for_user(&pool, &supplier.id, true).await.unwrap(), 1);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_list_filters_and_cursor() {
+        for pool in test_pools().await {
+            let client = queries::create_user(&pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+            queries::create_user(&pool, "fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+            for title in ["NDA Beta", "NDA Alpha", "Contrato 100%", "NDA Gama"] {
+                queries::create_process(&pool, &client.id, title, "xxx", "key").await.unwrap();
+            }
+            let shared = queries::create_process(&pool, &client.id, "NDA Delta", "xxx", "key").await.unwrap();
+            queries::create_process_share(&pool, &shared.id, "GSUPPLIER", None).await.unwrap();
+
+            // Percorre todas as páginas por título, duas por vez
+            let mut options = ProcessListOptions { sort: ProcessSort::Title, order: SortOrder::Asc, limit: 2, ..Default::default() };
+            let mut titles = Vec::new();
+            loop {
+                let page = queries::list_processes_by_client(&pool, &client.id, &options).await.unwrap();
+                assert_eq!(page.total, 5);
+                titles.extend(page.items.into_iter().map(|p| p.title));
+                match page.next_cursor {
+                    Some(cursor) => options.after = Cursor::decode(&cursor, &options.sort_key()),
+                    None => break,
+                }
+            }
+            assert_eq!(titles, ["Contrato 100%", "NDA Alpha", "NDA Beta", "NDA Delta", "NDA Gama"]);
+
+            let search = ProcessListOptions { search: Some("nda".to_string()), limit: 1, ..Default::default() };
+            let page = queries::list_processes_by_client(&pool, &client.id, &search).await.unwrap();
+            assert_eq!(page.total, 4);
+            assert!(page.next_cursor.is_some());
+
+            // % digitado é literal
+            let percent = ProcessListOptions { search: Some("0%".to_string()), ..Default::default() };
+            assert_eq!(queries::list_processes_by_client(&pool, &client.id, &percent).await.unwrap().total, 1);
+
+            for supplier in ["fornecedor", "GSUPPLIER"] {
+                let by_supplier = ProcessListOptions { supplier: Some(supplier.to_string()), ..Default::default() };
+                let page = queries::list_processes_by_client(&pool, &client.id, &by_supplier).await.unwrap();
+                assert_eq!(page.items.len(), 1);
+                assert_eq!(page.items[0].id, shared.id);
+            }
+
+            let future = ProcessListOptions { created_from: Some(Utc::now() + chrono::Duration::hours(1)), ..Default::default() };
+            assert_eq!(queries::list_processes_by_client(&pool, &client.id, &future).await.unwrap().total, 0);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_processes_is_scoped_and_ranked() {
+        for pool in test_pools().await {
+            let client = queries::create_user(&pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+            let other = queries::create_user(&pool, "outro", "GOTHER", "SOTHER", "client").await.unwrap();
+            let supplier = queries::create_user(&pool, "fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+
+            let alpha = queries::create_process(&pool, &client.id, "NDA Projeto Alpha", "xxx", "key").await.unwrap();
+            queries::create_process(&pool, &client.id, "Contrato de fornecimento", "xxx", "key").await.unwrap();
+            queries::create_process(&pool, &other.id, "NDA Projeto Alpha", "xxx", "key").await.unwrap();
+
+            let terms = crate::search::terms("projeto alp");
+            let (hits, total) = queries::search_processes(&pool, &client, &terms, 10, 0).await.unwrap();
+            assert_eq!(total, 1);
+            assert_eq!(hits[0].id, alpha.id);
+            assert!(hits[0].highlight.contains("<mark>Projeto</mark>"), "{}", hits[0].highlight);
+
+            // Texto do usuário chega escapado; só o destaque vira marcação
+            queries::create_process(&pool, &client.id, "Gama <script>x</script>", "xxx", "key").await.unwrap();
+            let (hits, _) = queries::search_processes(&pool, &client, &crate::search::terms("gama"), 10, 0).await.unwrap();
+            assert_eq!(hits[0].highlight, "<mark>Gama</mark> &lt;script&gt;x&lt;/script&gt;");
+
+            // Fornecedor só encontra o que foi compartilhado e confirmado
+            let share = queries::create_process_share(&pool, &alpha.id, "GSUPPLIER", None).await.unwrap();
+            assert_eq!(queries::search_processes(&pool, &supplier, &terms, 10, 0).await.unwrap().1, 0);
+            let entry = queries::find_ledger_outbox_by_share(&pool, &share.id).await.unwrap().unwrap();
+            queries::confirm_ledger_outbox(&pool, &entry, "hash").await.unwrap();
+            let (hits, _) = queries::search_processes(&pool, &supplier, &terms, 10, 0).await.unwrap();
+            assert_eq!(hits[0].id, alpha.id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_process_metadata_filters_and_search() {
+        for pool in test_pools().await {
+            let client = queries::create_user(&pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+            let tagged = queries::create_process(&pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+            queries::create_process(&pool, &client.id, "NDA Beta", "xxx", "key").await.unwrap();
+
+            let metadata = ProcessMetadata {
+                tags: vec!["hardware".to_string(), "prioridade".to_string()],
+                category: Some("fornecimento".to_string()),
+                project_code: Some("PX-1".to_string()),
+                custom_fields: [("centro_de_custo".to_string(), "Engenharia".to_string())].into(),
+                ..Default::default()
+            };
+            queries::update_process_metadata(&pool, &tagged.id, &metadata).await.unwrap();
+            let stored = queries::find_process_metadata(&pool, std::slice::from_ref(&tagged.id)).await.unwrap();
+            assert_eq!(stored[&tagged.id], metadata);
+
+            // Regravar substitui tags e campos
+            let replaced = ProcessMetadata { tags: vec!["hardware".to_string()], ..metadata.clone() };
+            queries::update_process_metadata(&pool, &tagged.id, &replaced).await.unwrap();
+            let stored = queries::find_process_metadata(&pool, std::slice::from_ref(&tagged.id)).await.unwrap();
+            assert_eq!(stored[&tagged.id].tags, ["hardware"]);
+
+            for options in [
+                ProcessListOptions { tag: Some("hardware".to_string()), ..Default::default() },
+                ProcessListOptions { category: Some("fornecimento".to_string()), ..Default::default() },
+                ProcessListOptions { project_code: Some("PX-1".to_string()), ..Default::default() },
+            ] {
+                let page = queries::list_processes_by_client(&pool, &client.id, &options).await.unwrap();
+                assert_eq!(page.total, 1);
+                assert_eq!(page.items[0].id, tagged.id);
+            }
+
+            let terms = crate::search::terms("engenharia");
+            let (hits, total) = queries::search_processes(&pool, &client, &terms, 10, 0).await.unwrap();
+            assert_eq!(total, 1);
+            assert!(hits[0].metadata_highlight.contains("<mark>Engenharia</mark>"), "{}", hits[0].metadata_highlight);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_supplier_shares_and_accept() {
+        for pool in test_pools().await {
+            let client = queries::create_user(&pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+            let supplier = queries::create_user(&pool, "fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+            let alpha = queries::create_process(&pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+            let beta = queries::create_process(&pool, &client.id, "NDA Beta", "xxx", "key").await.unwrap();
+            queries::create_process(&pool, &client.id, "NDA Gama", "xxx", "key").await.unwrap();
+
+            let expires_at = Utc::now() + chrono::Duration::days(30);
+            let confirmed = queries::create_process_share(&pool, &alpha.id, "GSUPPLIER", Some(expires_at)).await.unwrap();
+            let entry = queries::find_ledger_outbox_by_share(&pool, &confirmed.id).await.unwrap().unwrap();
+            queries::confirm_ledger_outbox(&pool, &entry, "hash").await.unwrap();
+            queries::create_process_share(&pool, &beta.id, "GSUPPLIER", None).await.unwrap();
+            queries::create_process_access(&pool, &alpha.id, &supplier.id).await.unwrap();
+            queries::create_process_access(&pool, &alpha.id, &supplier.id).await.unwrap();
+
+            // Aceite só vale para compartilhamentos confirmados, uma única vez
+            assert!(queries::accept_process_share(&pool, &confirmed.id).await.unwrap());
+            assert!(!queries::accept_process_share(&pool, &confirmed.id).await.unwrap());
+
+            let mut options = SupplierShareListOptions { limit: 1, ..Default::default() };
+            let mut listed = Vec::new();
+            loop {
+                let page = queries::list_supplier_shares(&pool, &supplier, &options).await.unwrap();
+                assert_eq!(page.total, 2);
+                listed.extend(page.items);
+                match page.next_cursor {
+                    Some(cursor) => options.after = Cursor::decode(&cursor, &options.sort_key()),
+                    None => break,
+                }
+            }
+            assert_eq!(listed.len(), 2);
+            let alpha_share = listed.iter().find(|p| p.process_id == alpha.id).unwrap();
+            assert_eq!(alpha_share.title, "NDA Alpha");
+            assert_eq!(alpha_share.client_username, "cliente");
+            assert_eq!(alpha_share.status, "confirmed");
+            assert!(alpha_share.accepted_at.is_some());
+            assert!(!alpha_share.expired);
+            assert_eq!(alpha_share.access_count, 2);
+            assert!(alpha_share.last_accessed_at.is_some());
+            let beta_share = listed.iter().find(|p| p.process_id == beta.id).unwrap();
+            assert_eq!((beta_share.status.as_str(), beta_share.access_count), ("pending", 0));
+
+            let pending = SupplierShareListOptions { status: Some("pending".to_string()), ..Default::default() };
+            let page = queries::list_supplier_shares(&pool, &supplier, &pending).await.unwrap();
+            assert_eq!(page.total, 1);
+            assert_eq!(page.items[0].process_id, beta.id);
+
+            // Vencido não impede um novo compartilhamento, que o marca como 'expired'
+            let gama = queries::create_process(&pool, &client.id, "NDA Gama", "xxx", "key").await.unwrap();
+            let past = Utc::now() - chrono::Duration::minutes(1);
+            let old = queries::create_process_share(&pool, &gama.id, "GSUPPLIER", Some(past)).await.unwrap();
+            let entry = queries::find_ledger_outbox_by_share(&pool, &old.id).await.unwrap().unwrap();
+            queries::confirm_ledger_outbox(&pool, &entry, "hash").await.unwrap();
+            let renewed = queries::create_process_share(&pool, &gama.id, "GSUPPLIER", None).await.unwrap();
+            let old = queries::find_process_share_by_id(&pool, &old.id).await.unwrap().unwrap();
+            assert_eq!(old.status, "expired");
+            assert_eq!(queries::find_process_share(&pool, &gama.id, "GSUPPLIER").await.unwrap().unwrap().id, renewed.id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_share_invites_are_redeemed_on_registration() {
+        for pool in test_pools().await {
+            let client = queries::create_user(&pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+            let alpha = queries::create_process(&pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+            let beta = queries::create_process(&pool, &client.id, "NDA Beta", "xxx", "key").await.unwrap();
+            let gama = queries::create_process(&pool, &client.id, "NDA Gama", "xxx", "key").await.unwrap();
+            let email = "novo@fornecedor.com";
+            let valid_until = Utc::now() + chrono::Duration::days(14);
+
+            let share_expiry = Utc::now() + chrono::Duration::days(30);
+            let invite = queries::create_share_invite(&pool, &alpha.id, &client.id, email, "hash-a", Some(share_expiry), valid_until)
+                .await
+                .unwrap();
+            queries::create_share_invite(&pool, &beta.id, &client.id, email, "hash-b", None, valid_until).await.unwrap();
+            // Compartilhamento que venceria antes do cadastro
+            let past = Utc::now() - chrono::Duration::minutes(1);
+            queries::create_share_invite(&pool, &gama.id, &client.id, email, "hash-c", Some(past), valid_until).await.unwrap();
+
+            // Um convite pendente por processo e e-mail
+            let duplicate = queries::create_share_invite(&pool, &alpha.id, &client.id, email, "hash-d", None, valid_until).await;
+            assert!(duplicate.unwrap_err().as_database_error().is_some_and(|e| e.is_unique_violation()));
+
+            let found = queries::find_pending_share_invite(&pool, "hash-a").await.unwrap().unwrap();
+            assert_eq!(found.id, invite.id);
+            assert_eq!(found.share_expires_at.map(|d| d.timestamp()), Some(share_expiry.timestamp()));
+
+            assert!(queries::find_user_by_email(&pool, email).await.unwrap().is_none());
+            let (supplier, redeemed) =
+                queries::create_invited_supplier(&pool, "fornecedor", "GSUPPLIER", "SSUPPLIER", email).await.unwrap();
+            assert_eq!(supplier.user_type, "supplier");
+            // O e-mail do convite fica registrado para as notificações e para novas buscas
+            assert_eq!(queries::find_user_by_email(&pool, "Novo@Fornecedor.com").await.unwrap().unwrap().id, supplier.id);
+            let mut processes: Vec<_> = redeemed.iter().map(|(_, share)| share.process_id.clone()).collect();
+            processes.sort();
+            let mut expected = vec![alpha.id.clone(), beta.id.clone()];
+            expected.sort();
+            assert_eq!(processes, expected);
+
+            for (invite, share) in &redeemed {
+                assert_eq!(invite.share_id.as_deref(), Some(share.id.as_str()));
+                assert_eq!(share.status, "pending");
+                assert!(queries::find_ledger_outbox_by_share(&pool, &share.id).await.unwrap().is_some());
+            }
+
+            // Convites usados não valem de novo
+            assert!(queries::find_pending_share_invite(&pool, "hash-a").await.unwrap().is_none());
+            let (_, redeemed) =
+                queries::create_invited_supplier(&pool, "outro", "GOUTRA", "SOUTRA", "outro@fornecedor.com").await.unwrap();
+            assert!(redeemed.is_empty());
+
+            // Falha ao converter os convites desfaz o cadastro inteiro
+            queries::create_share_invite(&pool, &alpha.id, &client.id, "terceiro@fornecedor.com", "hash-e", None, valid_until)
+                .await
+                .unwrap();
+            queries::create_process_share(&pool, &alpha.id, "GTERCEIRO", None).await.unwrap();
+            let failed = queries::create_invited_supplier(&pool, "terceiro", "GTERCEIRO", "STERCEIRO", "terceiro@fornecedor.com").await;
+            assert!(failed.unwrap_err().as_database_error().is_some_and(|e| e.is_unique_violation()));
+            assert!(queries::find_user_by_username(&pool, "terceiro").await.unwrap().is_none());
+            assert!(queries::find_pending_share_invite(&pool, "hash-e").await.unwrap().is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_core_queries() {
+        for pool in test_pools().await {
+            let client = queries::create_user(&pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+            let supplier = queries::create_user(&pool, "fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+            assert_eq!(
+                queries::find_user_by_public_key(&pool, "GSUPPLIER").await.unwrap().unwrap().id,
+                supplier.id
+            );
+            assert!(queries::find_user_by_username(&pool, "ninguem").await.unwrap().is_none());
+
+            let process = queries::create_process(&pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+            let found = queries::find_process_by_id(&pool, &process.id).await.unwrap().unwrap();
+            assert_eq!(found.title, "NDA Alpha");
+            assert_eq!(found.created_at, process.created_at);
+            let listed = queries::list_processes_by_client(&pool, &client.id, &ProcessListOptions::default()).await.unwrap();
+            assert_eq!(listed.items.len(), 1);
+
+            assert!(queries::find_process_share(&pool, &process.id, "GSUPPLIER").await.unwrap().is_none());
+            let created = queries::create_process_share(&pool, &process.id, "GSUPPLIER", None).await.unwrap();
+            let share = queries::find_process_share(&pool, &process.id, "GSUPPLIER").await.unwrap().unwrap();
+            assert_eq!(share.status, "pending");
+            assert!(share.stellar_transaction_hash.is_none());
+
+            // Só um compartilhamento ativo por processo e fornecedor
+            let duplicate = queries::create_process_share(&pool, &process.id, "GSUPPLIER", None).await.unwrap_err();
+            assert!(duplicate.as_database_error().unwrap().is_unique_violation());
+
+            // O pedido de registro no ledger é criado junto com o compartilhamento
+            let entry = queries::find_ledger_outbox_by_share(&pool, &created.id).await.unwrap().unwrap();
+            assert!(queries::claim_ledger_outbox(&pool, &entry, Utc::now() + chrono::Duration::seconds(60)).await.unwrap());
+            assert!(!queries::claim_ledger_outbox(&pool, &entry, Utc::now()).await.unwrap());
+            queries::confirm_ledger_outbox(&pool, &entry, "abc123").await.unwrap();
+            let share = queries::find_process_share_by_id(&pool, &created.id).await.unwrap().unwrap();
+            assert_eq!(share.status, "confirmed");
+            assert_eq!(share.stellar_transaction_hash.as_deref(), Some("abc123"));
+            assert!(share.confirmed_at.is_some());
+
+            // Lotes são reservados e confirmados como unidade
+            let keys = vec!["GLOTE1".to_string(), "GLOTE2".to_string()];
+            let batch = queries::create_process_shares_batch(&pool, &process.id, &keys, None, 100).await.unwrap();
+            let entry = queries::find_ledger_outbox_by_share(&pool, &batch[0].id).await.unwrap().unwrap();
+            let batch_id = entry.batch_id.unwrap();
+            let lease_until = Utc::now() + chrono::Duration::seconds(60);
+            let claimed = queries::claim_ledger_outbox_batch(&pool, &batch_id, lease_until).await.unwrap();
+            assert_eq!(claimed.len(), 2);
+            assert!(queries::claim_ledger_outbox_batch(&pool, &batch_id, lease_until).await.unwrap().is_empty());
+            let claimed: Vec<_> = claimed.iter().collect();
+            assert_eq!(queries::confirm_ledger_outbox_batch(&pool, &claimed, "lote").await.unwrap(), [true, true]);
+
+            queries::create_process_access(&pool, &process.id, &supplier.id).await.unwrap();
+            let accesses = queries::list_process_accesses_by_client(&pool, &client.id).await.unwrap();
+            assert_eq!(accesses[0].supplier_username, "fornecedor");
+
+            let mut preferences = crate::notifier::default_preferences(&client);
+            preferences.language = "en".to_string();
+            queries::upsert_notification_preferences(&pool, &preferences).await.unwrap();
+            let stored = queries::find_notification_preferences(&pool, &client.id).await.unwrap().unwrap();
+            assert_eq!(stored.language, "en");
+            assert!(!stored.email_enabled);
+
+            assert!(schema_status(&pool).await.unwrap().is_up_to_date());
+        }
+    }
+}
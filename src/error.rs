@@ -0,0 +1,468 @@
+// src/error.rs
+use axum::{
+    extract::{
+        rejection::{JsonRejection, PathRejection, QueryRejection},
+        FromRequest, FromRequestParts, Request,
+    },
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use tracing::Instrument;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::crypto::CryptoError;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Códigos estáveis, legíveis por máquina, expostos no corpo de erro
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    MissingParameter,
+    InvalidBody,
+    InvalidQuery,
+    InvalidCursor,
+    InvalidUrl,
+    InvalidWebhookEvent,
+    InvalidEmail,
+    InvalidLanguage,
+    InvalidMetadata,
+    InvalidExpiry,
+    InvalidShareTarget,
+    InvalidBulkShare,
+    NotASupplier,
+    InvalidInvite,
+    EmailRequired,
+    IdempotencyKeyReused,
+    InvalidIdempotencyKey,
+    UsernameTaken,
+    AlreadyShared,
+    AlreadyInvited,
+    SupplierRegistered,
+    IdempotencyKeyInProgress,
+    InvalidCredentials,
+    AccountLocked,
+    AccessDenied,
+    SharePending,
+    ShareExpired,
+    UserNotFound,
+    ProcessNotFound,
+    ShareNotFound,
+    NotificationNotFound,
+    WebhookNotFound,
+    RateLimited,
+    LedgerUnavailable,
+    Internal,
+}
+
+impl ErrorCode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorCode::MissingParameter => "missing_parameter",
+            ErrorCode::InvalidBody => "invalid_body",
+            ErrorCode::InvalidQuery => "invalid_query",
+            ErrorCode::InvalidCursor => "invalid_cursor",
+            ErrorCode::InvalidUrl => "invalid_url",
+            ErrorCode::InvalidWebhookEvent => "invalid_webhook_event",
+            ErrorCode::InvalidEmail => "invalid_email",
+            ErrorCode::InvalidLanguage => "invalid_language",
+            ErrorCode::InvalidExpiry => "invalid_expiry",
+            ErrorCode::InvalidShareTarget => "invalid_share_target",
+            ErrorCode::InvalidBulkShare => "invalid_bulk_share",
+            ErrorCode::NotASupplier => "not_a_supplier",
+            ErrorCode::InvalidInvite => "invalid_invite",
+            ErrorCode::InvalidMetadata => "invalid_metadata",
+            ErrorCode::InvalidIdempotencyKey => "invalid_idempotency_key",
+            ErrorCode::IdempotencyKeyReused => "idempotency_key_reused",
+            ErrorCode::EmailRequired => "email_required",
+            ErrorCode::IdempotencyKeyInProgress => "idempotency_key_in_progress",
+            ErrorCode::AlreadyShared => "already_shared",
+            ErrorCode::AlreadyInvited => "already_invited",
+            ErrorCode::SupplierRegistered => "supplier_registered",
+            ErrorCode::UsernameTaken => "username_taken",
+            ErrorCode::InvalidCredentials => "invalid_credentials",
+            ErrorCode::AccountLocked => "account_locked",
+            ErrorCode::AccessDenied => "access_denied",
+            ErrorCode::ShareExpired => "share_expired",
+            ErrorCode::SharePending => "share_pending",
+            ErrorCode::UserNotFound => "user_not_found",
+            ErrorCode::ShareNotFound => "share_not_found",
+            ErrorCode::ProcessNotFound => "process_not_found",
+            ErrorCode::NotificationNotFound => "notification_not_found",
+            ErrorCode::WebhookNotFound => "webhook_not_found",
+            ErrorCode::RateLimited => "rate_limited",
+            ErrorCode::LedgerUnavailable => "ledger_unavailable",
+            ErrorCode::Internal => "internal_error",
+        }
+    }
+
+    pub fn message(&self, language: Language) -> &'static str {
+        match (language, self) {
+            (Language::En, ErrorCode::MissingParameter) => "A required parameter is missing",
+            (Language::En, ErrorCode::InvalidBody) => "Malformed or incomplete JSON body",
+            (Language::En, ErrorCode::InvalidQuery) => "Invalid URL parameters",
+            (Language::En, ErrorCode::InvalidCursor) => "Invalid pagination cursor or cursor from another sort order",
+            (Language::En, ErrorCode::InvalidUrl) => "The URL must be a valid http(s) address",
+            (Language::En, ErrorCode::InvalidWebhookEvent) => "Unknown webhook event",
+            (Language::En, ErrorCode::InvalidEmail) => "Invalid e-mail address",
+            (Language::En, ErrorCode::InvalidLanguage) => "Unsupported language",
+            (Language::En, ErrorCode::InvalidExpiry) => "expires_at must be in the future",
+            (Language::En, ErrorCode::InvalidShareTarget) => "Provide either supplier_username or supplier_public_key",
+            (Language::En, ErrorCode::InvalidBulkShare) => "suppliers must list between 1 and 500 suppliers",
+            (Language::En, ErrorCode::NotASupplier) => "The target user is not a supplier",
+            (Language::En, ErrorCode::InvalidInvite) => "Invalid, expired or already used invite token",
+            (Language::En, ErrorCode::InvalidMetadata) => "Metadata exceeds the allowed number or size of tags and fields",
+            (Language::En, ErrorCode::InvalidIdempotencyKey) => "Idempotency-Key must have between 1 and 255 characters",
+            (Language::En, ErrorCode::IdempotencyKeyReused) => "Idempotency-Key already used with a different request",
+            (Language::En, ErrorCode::EmailRequired) => "An e-mail address is required to enable e-mail notifications",
+            (Language::En, ErrorCode::IdempotencyKeyInProgress) => "A request with this Idempotency-Key is still being processed",
+            (Language::En, ErrorCode::AlreadyShared) => "Process already shared with this supplier",
+            (Language::En, ErrorCode::AlreadyInvited) => "A pending invite for this e-mail already exists",
+            (Language::En, ErrorCode::SupplierRegistered) => "This e-mail is already registered, share by username instead",
+            (Language::En, ErrorCode::UsernameTaken) => "Username already registered",
+            (Language::En, ErrorCode::InvalidCredentials) => "Invalid credentials",
+            (Language::En, ErrorCode::AccountLocked) => "Account locked by an administrator",
+            (Language::En, ErrorCode::AccessDenied) => "Access denied",
+            (Language::En, ErrorCode::ShareExpired) => "Share has expired",
+            (Language::En, ErrorCode::SharePending) => "Share is awaiting ledger confirmation, try again shortly",
+            (Language::En, ErrorCode::UserNotFound) => "User not found",
+            (Language::En, ErrorCode::ShareNotFound) => "Share not found",
+            (Language::En, ErrorCode::ProcessNotFound) => "Process not found",
+            (Language::En, ErrorCode::NotificationNotFound) => "Notification not found",
+            (Language::En, ErrorCode::WebhookNotFound) => "Webhook not found",
+            (Language::En, ErrorCode::RateLimited) => "Too many requests, try again later",
+            (Language::En, ErrorCode::LedgerUnavailable) => "Stellar network unavailable, try again later",
+            (Language::En, ErrorCode::Internal) => "Internal server error",
+            (Language::Pt, ErrorCode::MissingParameter) => "Parâmetro obrigatório ausente",
+            (Language::Pt, ErrorCode::InvalidBody) => "Corpo JSON malformado ou incompleto",
+            (Language::Pt, ErrorCode::InvalidQuery) => "Parâmetros de URL inválidos",
+            (Language::Pt, ErrorCode::InvalidCursor) => "Cursor de paginação inválido ou de outra ordenação",
+            (Language::Pt, ErrorCode::InvalidUrl) => "A URL deve ser um endereço http(s) válido",
+            (Language::Pt, ErrorCode::InvalidWebhookEvent) => "Evento de webhook desconhecido",
+            (Language::Pt, ErrorCode::InvalidEmail) => "Endereço de e-mail inválido",
+            (Language::Pt, ErrorCode::InvalidLanguage) => "Idioma não suportado",
+            (Language::Pt, ErrorCode::InvalidExpiry) => "expires_at deve estar no futuro",
+            (Language::Pt, ErrorCode::InvalidShareTarget) => "Informe supplier_username ou supplier_public_key",
+            (Language::Pt, ErrorCode::InvalidBulkShare) => "suppliers deve listar de 1 a 500 fornecedores",
+            (Language::Pt, ErrorCode::NotASupplier) => "O usuário informado não é um fornecedor",
+            (Language::Pt, ErrorCode::InvalidInvite) => "Token de convite inválido, expirado ou já utilizado",
+            (Language::Pt, ErrorCode::InvalidMetadata) => "Metadados excedem a quantidade ou o tamanho permitido de tags e campos",
+            (Language::Pt, ErrorCode::InvalidIdempotencyKey) => "Idempotency-Key deve ter entre 1 e 255 caracteres",
+            (Language::Pt, ErrorCode::IdempotencyKeyReused) => "Idempotency-Key já utilizada com outra requisição",
+            (Language::Pt, ErrorCode::EmailRequired) => "Informe um e-mail para ativar as notificações por e-mail",
+            (Language::Pt, ErrorCode::IdempotencyKeyInProgress) => "Uma requisição com esta Idempotency-Key ainda está em processamento",
+            (Language::Pt, ErrorCode::AlreadyShared) => "Processo já compartilhado com este fornecedor",
+            (Language::Pt, ErrorCode::AlreadyInvited) => "Já existe um convite pendente para este e-mail",
+            (Language::Pt, ErrorCode::SupplierRegistered) => "E-mail já cadastrado; compartilhe pelo username",
+            (Language::Pt, ErrorCode::UsernameTaken) => "Usuário já cadastrado",
+            (Language::Pt, ErrorCode::InvalidCredentials) => "Credenciais inválidas",
+            (Language::Pt, ErrorCode::AccountLocked) => "Conta bloqueada pela administração",
+            (Language::Pt, ErrorCode::AccessDenied) => "Acesso negado",
+            (Language::Pt, ErrorCode::ShareExpired) => "Compartilhamento expirado",
+            (Language::Pt, ErrorCode::SharePending) => "Compartilhamento aguardando confirmação no ledger, tente novamente em instantes",
+            (Language::Pt, ErrorCode::UserNotFound) => "Usuário não encontrado",
+            (Language::Pt, ErrorCode::ShareNotFound) => "Compartilhamento não encontrado",
+            (Language::Pt, ErrorCode::ProcessNotFound) => "Processo não encontrado",
+            (Language::Pt, ErrorCode::NotificationNotFound) => "Notificação não encontrada",
+            (Language::Pt, ErrorCode::WebhookNotFound) => "Webhook não encontrado",
+            (Language::Pt, ErrorCode::RateLimited) => "Muitas requisições, tente novamente mais tarde",
+            (Language::Pt, ErrorCode::LedgerUnavailable) => "Rede Stellar indisponível, tente novamente mais tarde",
+            (Language::Pt, ErrorCode::Internal) => "Erro interno do servidor",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    Pt,
+    En,
+}
+
+impl Language {
+    /// Primeiro idioma suportado do header Accept-Language; português por padrão
+    pub fn from_accept_language(value: Option<&str>) -> Self {
+        value
+            .unwrap_or_default()
+            .split(',')
+            .map(|part| part.split(';').next().unwrap_or_default().trim().to_lowercase())
+            .find_map(|tag| match tag.split('-').next() {
+                Some("en") => Some(Language::En),
+                Some("pt") => Some(Language::Pt),
+                _ => None,
+            })
+            .unwrap_or(Language::Pt)
+    }
+}
+
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(ErrorCode),
+    Unauthorized(ErrorCode),
+    Forbidden(ErrorCode),
+    NotFound(ErrorCode),
+    Conflict(ErrorCode),
+    RateLimited(u64), // segundos até a próxima tentativa (Retry-After)
+    Ledger(String),   // falha na comunicação com a rede Stellar
+    Internal(String), // causa registrada no log, nunca exposta ao cliente
+}
+
+impl ApiError {
+    pub fn ledger(error: impl std::fmt::Display) -> Self {
+        ApiError::Ledger(error.to_string())
+    }
+
+    pub fn internal(error: impl std::fmt::Display) -> Self {
+        ApiError::Internal(error.to_string())
+    }
+
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Ledger(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ApiError::BadRequest(code)
+            | ApiError::Unauthorized(code)
+            | ApiError::Forbidden(code)
+            | ApiError::NotFound(code)
+            | ApiError::Conflict(code) => *code,
+            ApiError::RateLimited(_) => ErrorCode::RateLimited,
+            ApiError::Ledger(_) => ErrorCode::LedgerUnavailable,
+            ApiError::Internal(_) => ErrorCode::Internal,
+        }
+    }
+
+    fn detail(&self) -> Option<&str> {
+        match self {
+            ApiError::Ledger(detail) | ApiError::Internal(detail) => Some(detail),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.detail() {
+            Some(detail) => write!(f, "{}: {}", self.code().as_str(), detail),
+            None => write!(f, "{}", self.code().as_str()),
+        }
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+impl From<sqlx::Error> for ApiError {
+    fn from(error: sqlx::Error) -> Self {
+        ApiError::Internal(format!("Erro de banco de dados: {}", error))
+    }
+}
+
+impl From<CryptoError> for ApiError {
+    fn from(error: CryptoError) -> Self {
+        ApiError::Internal(error.to_string())
+    }
+}
+
+impl From<JsonRejection> for ApiError {
+    fn from(_: JsonRejection) -> Self {
+        ApiError::BadRequest(ErrorCode::InvalidBody)
+    }
+}
+
+impl From<QueryRejection> for ApiError {
+    fn from(_: QueryRejection) -> Self {
+        ApiError::BadRequest(ErrorCode::InvalidQuery)
+    }
+}
+
+impl From<PathRejection> for ApiError {
+    fn from(_: PathRejection) -> Self {
+        ApiError::BadRequest(ErrorCode::InvalidQuery)
+    }
+}
+
+// Extratores equivalentes aos do axum, mas com rejeições no formato de ApiError
+#[derive(FromRequest)]
+#[from_request(via(axum::Json), rejection(ApiError))]
+pub struct ApiJson<T>(pub T);
+
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Query), rejection(ApiError))]
+pub struct ApiQuery<T>(pub T);
+
+#[derive(FromRequestParts)]
+#[from_request(via(axum::extract::Path), rejection(ApiError))]
+pub struct ApiPath<T>(pub T);
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorBody {
+    pub error: ErrorDetail,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ErrorDetail {
+    pub code: &'static str,
+    pub message: &'static str,
+    pub request_id: String,
+}
+
+// Informações anexadas à resposta para o middleware localizar e registrar o erro
+#[derive(Debug, Clone)]
+struct ErrorContext {
+    code: ErrorCode,
+    detail: Option<String>,
+}
+
+fn error_response(status: StatusCode, code: ErrorCode, language: Language, request_id: String) -> Response {
+    let body = ErrorBody {
+        error: ErrorDetail {
+            code: code.as_str(),
+            message: code.message(language),
+            request_id,
+        },
+    };
+    (status, Json(body)).into_response()
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.status();
+        let code = self.code();
+
+        let mut response = error_response(status, code, Language::Pt, Uuid::new_v4().to_string());
+        response.extensions_mut().insert(ErrorContext {
+            code,
+            detail: self.detail().map(|d| d.to_string()),
+        });
+        if let ApiError::RateLimited(retry_after) = self {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after.max(1)));
+        }
+        response
+    }
+}
+
+/// Id da requisição corrente, disponível como extensão da request
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Middleware: atribui request id, localiza corpos de erro e registra erros internos
+pub async fn request_context(mut request: Request, next: Next) -> Response {
+    let request_id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty() && value.len() <= 128)
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let language = Language::from_accept_language(
+        request.headers().get(header::ACCEPT_LANGUAGE).and_then(|value| value.to_str().ok()),
+    );
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    // Span da requisição: handlers preenchem user_id e process_id quando os conhecem
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %method,
+        path = %path,
+        user_id = tracing::field::Empty,
+        process_id = tracing::field::Empty,
+    );
+    #[cfg(feature = "otlp")]
+    crate::telemetry::set_remote_parent(&span, request.headers());
+
+    request.extensions_mut().insert(RequestId(request_id.clone()));
+    let started = std::time::Instant::now();
+    let mut response = next.run(request).instrument(span.clone()).await;
+    let _entered = span.enter();
+
+    if let Some(context) = response.extensions_mut().remove::<ErrorContext>() {
+        let status = response.status();
+        if let Some(detail) = &context.detail {
+            tracing::error!(code = context.code.as_str(), "{}", detail);
+        }
+        // Corpo localizado, preservando headers da resposta original (ex.: Retry-After)
+        let mut headers = std::mem::take(response.headers_mut());
+        headers.remove(header::CONTENT_TYPE);
+        headers.remove(header::CONTENT_LENGTH);
+        response = error_response(status, context.code, language, request_id.clone());
+        response.headers_mut().extend(headers);
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    tracing::info!(
+        status = response.status().as_u16(),
+        latency_ms = started.elapsed().as_millis() as u64,
+        "requisição concluída"
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_accept_language_parsing() {
+        assert_eq!(Language::from_accept_language(None), Language::Pt);
+        assert_eq!(Language::from_accept_language(Some("en-US,en;q=0.9")), Language::En);
+        assert_eq!(Language::from_accept_language(Some("fr-FR, pt-BR;q=0.8")), Language::Pt);
+        assert_eq!(Language::from_accept_language(Some("de, en;q=0.5")), Language::En);
+    }
+
+    #[tokio::test]
+    async fn test_error_body_is_localized_with_request_id() {
+        let app = Router::new()
+            .route("/missing", get(|| async { Err::<(), _>(ApiError::NotFound(ErrorCode::ProcessNotFound)) }))
+            .route("/boom", get(|| async { Err::<(), _>(ApiError::internal("segredo interno")) }))
+            .layer(middleware::from_fn(request_context));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::get("/missing")
+                    .header(header::ACCEPT_LANGUAGE, "en")
+                    .header(REQUEST_ID_HEADER, "req-123")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[REQUEST_ID_HEADER], "req-123");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "process_not_found");
+        assert_eq!(body["error"]["message"], "Process not found");
+        assert_eq!(body["error"]["request_id"], "req-123");
+
+        // Detalhes internos não vazam para o cliente
+        let response = app
+            .oneshot(Request::get("/boom").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("Erro interno do servidor"));
+        assert!(!body.contains("segredo interno"));
+    }
+}
@@ -1,272 +1,1968 @@
 // src/handlers.rs
 use axum::{
-    extract::{State, Json, Query},
-    response::Json as ResponseJson,
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Json as ResponseJson, Response,
+    },
     http::StatusCode,
 };
-use std::sync::Arc;
-use chrono::Utc;
+use std::{convert::Infallible, sync::Arc};
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use tokio_stream::{wrappers::BroadcastStream, Stream, StreamExt};
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
-use uuid::Uuid;  // ← Adicionar esta linha
-use sqlx;        // ← Adicionar esta linha
+use serde_json::json;
+use utoipa::IntoParams;
 
 use crate::{
+    config::Config,
     models::*,
-    stellar_real::StellarClient,
+    stellar_real::{StellarClient, MAX_OPERATIONS_PER_TRANSACTION},
     crypto::{generate_key, encrypt_content, decrypt_content},
-    database::queries,
+    database::{self, queries, DbPool},
+    error::{ApiError, ApiJson, ApiPath, ApiQuery, ErrorBody, ErrorCode},
+    metrics::metrics,
+    telemetry::{record_process, record_user},
+    search,
+    invites,
+    pagination::{
+        clamp_page_size, Cursor, NotificationListOptions, ProcessListOptions, ProcessSort, ReadStatus, SortOrder,
+        SupplierShareListOptions,
+    },
+    webhooks::{self, WebhookEvent},
+    notifier::{self, LogNotifier, Notifier},
+    repository::{AccessRepo, ProcessRepo, ShareRepo, SqlRepository, UserRepo},
 };
 
+// Capacidade do canal de notificações em tempo real
+const NOTIFICATION_CHANNEL_CAPACITY: usize = 256;
+
 // Definir AppState aqui mesmo
 #[derive(Clone)]
 pub struct AppState {
-    pub pool: sqlx::SqlitePool,
+    pub config: Arc<Config>,
+    pub pool: DbPool,
+    pub users: Arc<dyn UserRepo>,
+    pub processes: Arc<dyn ProcessRepo>,
+    pub shares: Arc<dyn ShareRepo>,
+    pub accesses: Arc<dyn AccessRepo>,
+    pub notifications_tx: broadcast::Sender<Notification>,
+    pub notifier: Arc<dyn Notifier>,
+    // Cancelado no encerramento: workers param e /health/ready passa a responder 503
+    pub shutdown: CancellationToken,
+}
+
+impl AppState {
+    pub fn new(pool: DbPool) -> Self {
+        let (notifications_tx, _) = broadcast::channel(NOTIFICATION_CHANNEL_CAPACITY);
+        let repository = Arc::new(SqlRepository::new(pool.clone()));
+        Self {
+            config: Arc::new(Config::default()),
+            pool,
+            users: repository.clone(),
+            processes: repository.clone(),
+            shares: repository.clone(),
+            accesses: repository,
+            notifications_tx,
+            notifier: Arc::new(LogNotifier),
+            shutdown: CancellationToken::new(),
+        }
+    }
+
+    // Substitui os repositórios do núcleo (ex.: InMemoryRepository nos testes)
+    pub fn with_repository<R>(mut self, repository: Arc<R>) -> Self
+    where
+        R: UserRepo + ProcessRepo + ShareRepo + AccessRepo + 'static,
+    {
+        self.users = repository.clone();
+        self.processes = repository.clone();
+        self.shares = repository.clone();
+        self.accesses = repository;
+        self
+    }
+
+    pub fn with_config(mut self, config: Config) -> Self {
+        self.config = Arc::new(config);
+        self
+    }
+
+    pub fn with_notifier(mut self, notifier: Arc<dyn Notifier>) -> Self {
+        self.notifier = notifier;
+        self
+    }
 }
 
 // Query parameters para listar processos
-#[derive(Deserialize)]
+#[derive(Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListProcessesQuery {
     pub client_username: Option<String>,
+    pub status: Option<String>,
+    /// Criados a partir de (RFC 3339)
+    pub created_from: Option<DateTime<Utc>>,
+    /// Criados até (RFC 3339)
+    pub created_to: Option<DateTime<Utc>>,
+    /// Busca no título
+    pub q: Option<String>,
+    /// Username ou chave pública de fornecedor com compartilhamento ativo
+    pub supplier: Option<String>,
+    pub tag: Option<String>,
+    pub category: Option<String>,
+    pub project_code: Option<String>,
+    pub sort: Option<ProcessSort>,
+    pub order: Option<SortOrder>,
+    /// next_cursor da página anterior
+    pub cursor: Option<String>,
+    pub per_page: Option<u32>,
+}
+
+// Query parameters da busca textual de processos
+#[derive(Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SearchProcessesQuery {
+    /// Usuário que busca: cliente (seus processos) ou fornecedor (compartilhados com ele)
+    pub username: Option<String>,
+    /// Termos buscados no título; cada termo vale como prefixo
+    pub q: Option<String>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+// Query parameters da visão do fornecedor
+#[derive(Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct SupplierProcessesQuery {
+    pub supplier_public_key: Option<String>,
+    /// pending, confirmed, revoked ou expired
+    pub status: Option<String>,
+    /// next_cursor da página anterior
+    pub cursor: Option<String>,
+    pub per_page: Option<u32>,
+}
+
+// Query parameters dos recursos do cliente (webhooks)
+#[derive(Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ClientQuery {
+    pub client_username: Option<String>,
+}
+
+// Query parameters para listar notificações
+#[derive(Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NotificationsQuery {
+    pub username: Option<String>,
+    pub client_username: Option<String>, // compatibilidade com a versão anterior
+    pub unread_only: Option<bool>,
+    pub status: Option<ReadStatus>,
+    pub event: Option<NotificationEvent>,
+    /// Criadas a partir de (RFC 3339)
+    pub created_from: Option<DateTime<Utc>>,
+    /// Criadas até (RFC 3339)
+    pub created_to: Option<DateTime<Utc>>,
+    /// Busca no título do processo
+    pub q: Option<String>,
+    /// Username do fornecedor que originou a notificação
+    pub supplier: Option<String>,
+    pub order: Option<SortOrder>,
+    /// next_cursor da página anterior; tem precedência sobre page
+    pub cursor: Option<String>,
+    pub page: Option<u32>,
+    pub per_page: Option<u32>,
+}
+
+// Limites dos metadados não confidenciais dos processos
+const MAX_TAGS: usize = 20;
+const MAX_TAG_LENGTH: usize = 50;
+const MAX_METADATA_VALUE_LENGTH: usize = 200;
+const MAX_CUSTOM_FIELDS: usize = 20;
+const MAX_CUSTOM_FIELD_NAME_LENGTH: usize = 50;
+
+// Tags em minúsculas e sem repetição; textos aparados, vazios viram ausentes
+fn normalize_metadata(metadata: ProcessMetadata) -> Result<ProcessMetadata, ApiError> {
+    let invalid = || ApiError::BadRequest(ErrorCode::InvalidMetadata);
+    let text = |value: Option<String>| -> Result<Option<String>, ApiError> {
+        match value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+            Some(v) if v.chars().count() > MAX_METADATA_VALUE_LENGTH => Err(invalid()),
+            other => Ok(other),
+        }
+    };
+
+    let mut tags: Vec<String> = metadata.tags
+        .iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    if tags.len() > MAX_TAGS || tags.iter().any(|tag| tag.chars().count() > MAX_TAG_LENGTH) {
+        return Err(invalid());
+    }
+
+    let mut custom_fields = std::collections::BTreeMap::new();
+    for (name, value) in metadata.custom_fields {
+        let name = name.trim().to_string();
+        if name.is_empty() || name.chars().count() > MAX_CUSTOM_FIELD_NAME_LENGTH {
+            return Err(invalid());
+        }
+        if let Some(value) = text(Some(value))? {
+            custom_fields.insert(name, value);
+        }
+    }
+    if custom_fields.len() > MAX_CUSTOM_FIELDS {
+        return Err(invalid());
+    }
+
+    Ok(ProcessMetadata {
+        tags,
+        category: text(metadata.category)?,
+        counterparty_reference: text(metadata.counterparty_reference)?,
+        project_code: text(metadata.project_code)?,
+        custom_fields,
+    })
+}
+
+fn decode_cursor(cursor: Option<&str>, sort_key: &str) -> Result<Option<Cursor>, ApiError> {
+    cursor
+        .map(|cursor| Cursor::decode(cursor, sort_key).ok_or(ApiError::BadRequest(ErrorCode::InvalidCursor)))
+        .transpose()
+}
+
+// Query parameters para os canais em tempo real (WebSocket e SSE)
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct NotificationStreamQuery {
+    pub username: String,
+}
+
+// Registra notificação e publica para assinantes em tempo real,
+// sem interromper o fluxo principal em caso de falha
+pub(crate) async fn notify(
+    state: &AppState,
+    recipient_id: &str,
+    event: NotificationEvent,
+    process_id: &str,
+    actor_id: Option<&str>,
+    message: &str,
+) {
+    let id = match queries::create_notification(&state.pool, recipient_id, event, process_id, actor_id, message).await {
+        Ok(id) => id,
+        Err(e) => {
+            tracing::warn!(event = event.as_str(), error = %e, "erro ao registrar notificação");
+            return;
+        }
+    };
+
+    match queries::find_notification_by_id(&state.pool, &id).await {
+        Ok(Some(notification)) => {
+            // E-mail em segundo plano para não atrasar a resposta
+            let pool = state.pool.clone();
+            let email_notifier = state.notifier.clone();
+            let email_notification = notification.clone();
+            tokio::spawn(async move {
+                if let Err(e) = notifier::deliver_email(&pool, email_notifier.as_ref(), &email_notification).await {
+                    tracing::warn!(notification_id = %email_notification.id, error = %e, "erro ao enviar e-mail da notificação");
+                }
+            });
+
+            // Sem assinantes conectados o envio falha, o que é esperado
+            let _ = state.notifications_tx.send(notification);
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!(notification_id = %id, error = %e, "erro ao publicar notificação"),
+    }
+}
+
+// Enfileira webhook sem interromper o fluxo principal em caso de falha
+pub(crate) async fn emit_webhook(state: &AppState, client_id: &str, event: WebhookEvent, data: serde_json::Value) {
+    if let Err(e) = webhooks::enqueue(&state.pool, client_id, event, data).await {
+        tracing::warn!(event = event.as_str(), error = %e, "erro ao enfileirar webhook");
+    }
 }
 
+const WEBHOOK_DELIVERY_LOG_LIMIT: i64 = 100;
+// Prazo da verificação do Horizon em /health/ready
+const HORIZON_CHECK_TIMEOUT_SECS: u64 = 5;
+
+#[utoipa::path(
+    get, path = "/health", tag = "health",
+    responses((status = 200, description = "Servidor no ar", body = String, content_type = "text/plain"))
+)]
 pub async fn health_check() -> &'static str {
     "OK"
 }
 
+#[utoipa::path(
+    get, path = "/health/live", tag = "health",
+    responses((status = 200, description = "Processo ativo", body = Object, example = json!({ "status": "ok" })))
+)]
+/// Liveness: o processo está de pé e atendendo requisições
+pub async fn health_live() -> ResponseJson<serde_json::Value> {
+    ResponseJson(json!({ "status": "ok" }))
+}
+
+#[utoipa::path(
+    get, path = "/health/ready", tag = "health",
+    responses(
+        (status = 200, description = "Banco, schema e Horizon disponíveis", body = ReadinessResponse),
+        (status = 503, description = "Alguma dependência indisponível ou servidor encerrando", body = ReadinessResponse),
+    )
+)]
+/// Readiness: banco acessível, schema atualizado e Horizon alcançável.
+/// Durante o encerramento responde 503 para o balanceador parar de enviar tráfego.
+pub async fn health_ready(State(state): State<Arc<AppState>>) -> (StatusCode, ResponseJson<ReadinessResponse>) {
+    let database = match sqlx::query("SELECT 1").execute(&state.pool).await {
+        Ok(_) => HealthCheck::ok(),
+        Err(e) => HealthCheck::failed(e),
+    };
+
+    let migrations = match database::schema_status(&state.pool).await {
+        Ok(status) if status.is_up_to_date() => HealthCheck::ok(),
+        Ok(status) => HealthCheck::failed(format!(
+            "pendentes: {:?}, desconhecidas: {:?}, incompletas: {:?}",
+            status.pending, status.unknown, status.dirty
+        )),
+        Err(e) => HealthCheck::failed(e),
+    };
+
+    let stellar = StellarClient::from_config(&state.config.stellar);
+    let timeout = std::time::Duration::from_secs(HORIZON_CHECK_TIMEOUT_SECS);
+    let horizon = match tokio::time::timeout(timeout, async { stellar.test_connection().await.map_err(|e| e.to_string()) }).await {
+        Ok(Ok(true)) => HealthCheck::ok(),
+        Ok(Ok(false)) => HealthCheck::failed("Horizon respondeu com erro"),
+        Ok(Err(e)) => HealthCheck::failed(e),
+        Err(_) => HealthCheck::failed("Tempo esgotado"),
+    };
+
+    let draining = state.shutdown.is_cancelled();
+    let ready = !draining && database.ok && migrations.ok && horizon.ok;
+    let response = ReadinessResponse {
+        status: if draining { "shutting_down" } else if ready { "ready" } else { "not_ready" }.to_string(),
+        database,
+        migrations,
+        horizon,
+    };
+
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, ResponseJson(response))
+}
+
+#[utoipa::path(
+    post, path = "/api/users/register", tag = "users",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Repetições com a mesma chave recebem a resposta original")),
+    request_body = RegisterRequest,
+    responses(
+        (status = 200, description = "Usuário criado com carteira Stellar", body = UserResponse),
+        (status = 400, description = "Token de convite inválido ou convite para usuário que não é fornecedor", body = ErrorBody),
+        (status = 409, description = "Username já cadastrado", body = ErrorBody),
+        (status = 429, description = "Limite de requisições atingido", body = ErrorBody),
+        (status = 502, description = "Rede Stellar indisponível", body = ErrorBody),
+    )
+)]
 pub async fn register_user(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<RegisterRequest>,
-) -> Result<ResponseJson<UserResponse>, StatusCode> {
+    ApiJson(payload): ApiJson<RegisterRequest>,
+) -> Result<ResponseJson<UserResponse>, ApiError> {
     // Verificar se usuário já existe
-    if let Ok(Some(_)) = queries::find_user_by_username(&state.pool, &payload.username).await {
-        return Err(StatusCode::CONFLICT);
+    if let Ok(Some(_)) = state.users.find_by_username(&payload.username).await {
+        return Err(ApiError::Conflict(ErrorCode::UsernameTaken));
     }
-    
+
+    // Convite validado antes de criar a conta Stellar
+    let invite = match &payload.invite_token {
+        Some(token) => {
+            let invite = queries::find_pending_share_invite(&state.pool, &invites::hash_token(token))
+                .await?
+                .ok_or(ApiError::BadRequest(ErrorCode::InvalidInvite))?;
+            if payload.user_type != "supplier" {
+                return Err(ApiError::BadRequest(ErrorCode::NotASupplier));
+            }
+            Some(invite)
+        }
+        None => None,
+    };
+
     // Criar conta Stellar real
-    let stellar_account = StellarClient::generate_keypair()
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let stellar_account = StellarClient::generate_keypair().map_err(ApiError::internal)?;
 
-    // Financiar conta na testnet automaticamente
-    let stellar_client = StellarClient::new_testnet();
-    let _funded = stellar_client
-        .fund_testnet_account(&stellar_account.public_key)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    // Financiar conta automaticamente quando houver friendbot (testnet);
+    // na mainnet a conta precisa ser financiada externamente
+    let stellar_client = StellarClient::from_config(&state.config.stellar);
+    if stellar_client.can_fund_accounts() {
+        stellar_client
+            .fund_testnet_account(&stellar_account.public_key)
+            .await
+            .map_err(ApiError::ledger)?;
+    }
 
     // Criar usuário no banco
-    let user = queries::create_user(
-        &state.pool,
-        &payload.username,
-        &stellar_account.public_key,
-        &stellar_account.secret_key,
-        &payload.user_type,
-    )
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let user = match invite {
+        // O token comprova o e-mail: todos os convites pendentes para ele viram
+        // compartilhamentos, na mesma transação do cadastro, confirmados depois pelo worker do outbox
+        Some(invite) => {
+            let (user, redeemed) = queries::create_invited_supplier(
+                &state.pool,
+                &payload.username,
+                &stellar_account.public_key,
+                &stellar_account.secret_key,
+                &invite.email,
+            )
+            .await?;
+            for _ in &redeemed {
+                metrics().share_created();
+            }
+            tracing::info!(shares = redeemed.len(), "convites convertidos em compartilhamentos");
+            user
+        }
+        None => {
+            state.users.create(
+                &payload.username,
+                &stellar_account.public_key,
+                &stellar_account.secret_key,
+                &payload.user_type,
+            )
+            .await?
+        }
+    };
+    record_user(&user.id);
 
     Ok(ResponseJson(user.into()))
 }
 
+#[utoipa::path(
+    post, path = "/api/users/login", tag = "users",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Credenciais válidas", body = UserResponse),
+        (status = 401, description = "Credenciais inválidas", body = ErrorBody),
+        (status = 403, description = "Conta bloqueada pela administração", body = ErrorBody),
+        (status = 429, description = "Limite atingido ou usuário bloqueado", body = ErrorBody),
+    )
+)]
 pub async fn login_user(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<LoginRequest>,
-) -> Result<ResponseJson<UserResponse>, StatusCode> {
-    let user = queries::find_user_by_username(&state.pool, &payload.username)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::UNAUTHORIZED)?;
+    ApiJson(payload): ApiJson<LoginRequest>,
+) -> Result<ResponseJson<UserResponse>, ApiError> {
+    let user = state.users.find_by_username(&payload.username)
+        .await?
+        .ok_or(ApiError::Unauthorized(ErrorCode::InvalidCredentials))?;
+    record_user(&user.id);
+
+    if user.locked_at.is_some() {
+        return Err(ApiError::Forbidden(ErrorCode::AccountLocked));
+    }
 
     Ok(ResponseJson(user.into()))
 }
 
+#[utoipa::path(
+    post, path = "/api/processes", tag = "processes",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Repetições com a mesma chave recebem a resposta original")),
+    request_body = CreateProcessRequest,
+    responses(
+        (status = 200, description = "Processo criado com o conteúdo criptografado", body = ProcessResponse),
+        (status = 400, description = "Metadados inválidos", body = ErrorBody),
+        (status = 404, description = "Cliente não encontrado", body = ErrorBody),
+    )
+)]
 pub async fn create_process(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<CreateProcessRequest>,
-) -> Result<ResponseJson<ProcessResponse>, StatusCode> {
+    ApiJson(payload): ApiJson<CreateProcessRequest>,
+) -> Result<ResponseJson<ProcessResponse>, ApiError> {
     // Buscar cliente pelo username
-    let client = queries::find_user_by_username(&state.pool, &payload.client_username)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let client = state.users.find_by_username(&payload.client_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&client.id);
+
+    let metadata = normalize_metadata(payload.metadata)?;
 
     let encryption_key = generate_key();
-    let encrypted_content = encrypt_content(&payload.confidential_content, &encryption_key)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let encrypted_content = encrypt_content(&payload.confidential_content, &encryption_key)?;
 
-    let process = queries::create_process(
-        &state.pool,
+    let process = state.processes.create(
         &client.id,
         &payload.title,
         &encrypted_content,
         &encryption_key,
     )
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await?;
+    record_process(&process.id);
+
+    if metadata != ProcessMetadata::default() {
+        state.processes.update_metadata(&process.id, &metadata)
+            .await?;
+    }
 
-    Ok(ResponseJson(process.into()))
+    Ok(ResponseJson(ProcessResponse::new(process, metadata)))
 }
 
+#[utoipa::path(
+    put, path = "/api/processes/{id}/metadata", tag = "processes",
+    params(("id" = String, Path, description = "Id do processo")),
+    request_body = UpdateProcessMetadataRequest,
+    responses(
+        (status = 200, description = "Metadados substituídos", body = ProcessResponse),
+        (status = 400, description = "Metadados inválidos", body = ErrorBody),
+        (status = 403, description = "Processo pertence a outro cliente", body = ErrorBody),
+        (status = 404, description = "Processo ou cliente não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn update_process_metadata(
+    State(state): State<Arc<AppState>>,
+    ApiPath(process_id): ApiPath<String>,
+    ApiJson(payload): ApiJson<UpdateProcessMetadataRequest>,
+) -> Result<ResponseJson<ProcessResponse>, ApiError> {
+    let process = state.processes.find_by_id(&process_id)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::ProcessNotFound))?;
+    record_process(&process.id);
+
+    let client = state.users.find_by_username(&payload.client_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&client.id);
+
+    // Só o dono do processo edita os metadados
+    if process.client_id != client.id {
+        return Err(ApiError::Forbidden(ErrorCode::AccessDenied));
+    }
+
+    let metadata = normalize_metadata(payload.metadata)?;
+    state.processes.update_metadata(&process.id, &metadata)
+        .await?;
+
+    Ok(ResponseJson(ProcessResponse::new(process, metadata)))
+}
+
+#[utoipa::path(
+    post, path = "/api/processes/share", tag = "processes",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Repetições com a mesma chave recebem a resposta original")),
+    request_body = ShareProcessRequest,
+    responses(
+        (status = 202, description = "Compartilhamento pendente de confirmação no ledger", body = ProcessShare),
+        (status = 400, description = "expires_at no passado, fornecedor não informado ou usuário que não é fornecedor", body = ErrorBody),
+        (status = 403, description = "Processo pertence a outro cliente", body = ErrorBody),
+        (status = 404, description = "Processo, cliente ou fornecedor não encontrado", body = ErrorBody),
+        (status = 409, description = "Processo já compartilhado com o fornecedor", body = ErrorBody),
+        (status = 429, description = "Limite de requisições atingido", body = ErrorBody),
+    )
+)]
 pub async fn share_process(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<ShareProcessRequest>,
-) -> Result<ResponseJson<ProcessShare>, StatusCode> {
-    let stellar_client = StellarClient::new_testnet();
-    
+    ApiJson(payload): ApiJson<ShareProcessRequest>,
+) -> Result<(StatusCode, ResponseJson<ProcessShare>), ApiError> {
     // Buscar processo
-    let _process = queries::find_process_by_id(&state.pool, &payload.process_id)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let process = state.processes.find_by_id(&payload.process_id)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::ProcessNotFound))?;
+    record_process(&process.id);
 
     // Buscar cliente pelo username
-    let client = queries::find_user_by_username(&state.pool, &payload.client_username)
+    let client = state.users.find_by_username(&payload.client_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&client.id);
+
+    // A transação é assinada pela carteira do dono do processo
+    if process.client_id != client.id {
+        return Err(ApiError::Forbidden(ErrorCode::AccessDenied));
+    }
+
+    if payload.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(ApiError::BadRequest(ErrorCode::InvalidExpiry));
+    }
+
+    let supplier = find_share_target(
+        &state,
+        payload.supplier_username.as_deref(),
+        payload.supplier_public_key.as_deref(),
+    )
+    .await?;
+    record_user(&supplier.id);
+
+    // Um compartilhamento ativo por fornecedor (o índice único cobre corridas)
+    let existing = state.shares.find(&process.id, &supplier.stellar_public_key)
+        .await?;
+    if existing.is_some_and(|share| share.is_active()) {
+        return Err(ApiError::Conflict(ErrorCode::AlreadyShared));
+    }
+
+    // Registrar compartilhamento pendente; o worker do outbox envia a transação
+    // Stellar e confirma (webhook e notificação ao fornecedor saem na confirmação)
+    let share = state.shares.create(&process.id, &supplier.stellar_public_key, payload.expires_at)
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
-
-    // Enviar transação Stellar real
-    let tx_result = stellar_client
-        .share_process_transaction(
-            &client.stellar_secret_key,
-            &payload.supplier_public_key,
-            &payload.process_id,
-            &format!("NDA_SHARE:{}", payload.process_id),
+        .map_err(|e| match e.as_database_error() {
+            Some(db) if db.is_unique_violation() => ApiError::Conflict(ErrorCode::AlreadyShared),
+            _ => e.into(),
+        })?;
+    metrics().share_created();
+
+    Ok((StatusCode::ACCEPTED, ResponseJson(share)))
+}
+
+// Fornecedores por pedido de compartilhamento em lote
+const MAX_BULK_SHARE_SUPPLIERS: usize = 500;
+
+#[utoipa::path(
+    post, path = "/api/processes/share/bulk", tag = "processes",
+    params(("Idempotency-Key" = Option<String>, Header, description = "Repetições com a mesma chave recebem a resposta original")),
+    request_body = BulkShareRequest,
+    responses(
+        (status = 202, description = "Compartilhamentos pendentes criados; resultado por fornecedor", body = BulkShareResponse),
+        (status = 200, description = "Nenhum fornecedor válido; resultado por fornecedor", body = BulkShareResponse),
+        (status = 400, description = "Lista de fornecedores vazia ou grande demais, ou expires_at no passado", body = ErrorBody),
+        (status = 403, description = "Processo pertence a outro cliente", body = ErrorBody),
+        (status = 404, description = "Processo ou cliente não encontrado", body = ErrorBody),
+        (status = 409, description = "Compartilhamento concorrente com um dos fornecedores", body = ErrorBody),
+        (status = 429, description = "Limite de requisições atingido", body = ErrorBody),
+    )
+)]
+pub async fn bulk_share_process(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<BulkShareRequest>,
+) -> Result<(StatusCode, ResponseJson<BulkShareResponse>), ApiError> {
+    let process = state.processes.find_by_id(&payload.process_id)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::ProcessNotFound))?;
+    record_process(&process.id);
+
+    let client = state.users.find_by_username(&payload.client_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&client.id);
+
+    if process.client_id != client.id {
+        return Err(ApiError::Forbidden(ErrorCode::AccessDenied));
+    }
+    if payload.suppliers.is_empty() || payload.suppliers.len() > MAX_BULK_SHARE_SUPPLIERS {
+        return Err(ApiError::BadRequest(ErrorCode::InvalidBulkShare));
+    }
+    if payload.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(ApiError::BadRequest(ErrorCode::InvalidExpiry));
+    }
+
+    // Todos os destinos são validados no banco antes de criar qualquer compartilhamento
+    let mut outcomes = Vec::with_capacity(payload.suppliers.len());
+    let mut public_keys = Vec::new();
+    for target in &payload.suppliers {
+        let outcome = match find_share_target(
+            &state,
+            target.supplier_username.as_deref(),
+            target.supplier_public_key.as_deref(),
+        )
+        .await
+        {
+            // Repetido no próprio pedido
+            Ok(supplier) if public_keys.contains(&supplier.stellar_public_key) => Err(ErrorCode::AlreadyShared),
+            Ok(supplier) => match state.shares.find(&process.id, &supplier.stellar_public_key).await? {
+                Some(share) if share.is_active() => Err(ErrorCode::AlreadyShared),
+                _ => {
+                    public_keys.push(supplier.stellar_public_key.clone());
+                    Ok(())
+                }
+            },
+            Err(ApiError::BadRequest(code) | ApiError::NotFound(code)) => Err(code),
+            Err(e) => return Err(e),
+        };
+        outcomes.push(outcome);
+    }
+
+    // Uma transação no banco; no ledger, uma transação Stellar por grupo de até 100
+    let shares = if public_keys.is_empty() {
+        Vec::new()
+    } else {
+        state.shares.create_batch(
+            &process.id,
+            &public_keys,
+            payload.expires_at,
+            MAX_OPERATIONS_PER_TRANSACTION,
         )
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .map_err(|e| match e.as_database_error() {
+            Some(db) if db.is_unique_violation() => ApiError::Conflict(ErrorCode::AlreadyShared),
+            _ => e.into(),
+        })?
+    };
+    for _ in &shares {
+        metrics().share_created();
+    }
+
+    let mut created = shares.into_iter();
+    let results: Vec<BulkShareResult> = payload
+        .suppliers
+        .into_iter()
+        .zip(outcomes)
+        .map(|(supplier, outcome)| match outcome {
+            Ok(()) => BulkShareResult { supplier, share: created.next(), error: None },
+            Err(code) => BulkShareResult { supplier, share: None, error: Some(code.as_str().to_string()) },
+        })
+        .collect();
 
-    // Registrar compartilhamento
-    let share = queries::create_process_share(
+    let created = results.iter().filter(|result| result.share.is_some()).count();
+    let response = BulkShareResponse { failed: results.len() - created, created, results };
+    let status = if created > 0 { StatusCode::ACCEPTED } else { StatusCode::OK };
+
+    Ok((status, ResponseJson(response)))
+}
+
+// Destino do compartilhamento: usuário cadastrado do tipo fornecedor,
+// identificado pelo username ou pela chave pública
+async fn find_share_target(
+    state: &AppState,
+    username: Option<&str>,
+    public_key: Option<&str>,
+) -> Result<User, ApiError> {
+    let supplier = match (username, public_key) {
+        (Some(username), None) => state.users.find_by_username(username).await?,
+        (None, Some(public_key)) => state.users.find_by_public_key(public_key).await?,
+        _ => return Err(ApiError::BadRequest(ErrorCode::InvalidShareTarget)),
+    }
+    .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+
+    if supplier.user_type != "supplier" {
+        return Err(ApiError::BadRequest(ErrorCode::NotASupplier));
+    }
+
+    Ok(supplier)
+}
+
+#[utoipa::path(
+    post, path = "/api/processes/invite", tag = "processes",
+    request_body = InviteSupplierRequest,
+    responses(
+        (status = 201, description = "Convite criado e enviado por e-mail; o token só é exibido aqui", body = CreateShareInviteResponse),
+        (status = 400, description = "E-mail inválido ou expires_at no passado", body = ErrorBody),
+        (status = 403, description = "Processo pertence a outro cliente", body = ErrorBody),
+        (status = 404, description = "Processo ou cliente não encontrado", body = ErrorBody),
+        (status = 409, description = "Convite pendente existente ou e-mail já cadastrado", body = ErrorBody),
+        (status = 429, description = "Limite de requisições atingido", body = ErrorBody),
+    )
+)]
+pub async fn invite_supplier(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<InviteSupplierRequest>,
+) -> Result<(StatusCode, ResponseJson<CreateShareInviteResponse>), ApiError> {
+    let process = state.processes.find_by_id(&payload.process_id)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::ProcessNotFound))?;
+    record_process(&process.id);
+
+    let client = state.users.find_by_username(&payload.client_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&client.id);
+
+    if process.client_id != client.id {
+        return Err(ApiError::Forbidden(ErrorCode::AccessDenied));
+    }
+
+    let email = invites::normalize_email(&payload.email);
+    if !notifier::is_valid_email(&email) {
+        return Err(ApiError::BadRequest(ErrorCode::InvalidEmail));
+    }
+    if payload.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err(ApiError::BadRequest(ErrorCode::InvalidExpiry));
+    }
+
+    // Quem já tem cadastro com esse e-mail recebe o compartilhamento direto, pelo username
+    if queries::find_user_by_email(&state.pool, &email).await?.is_some() {
+        return Err(ApiError::Conflict(ErrorCode::SupplierRegistered));
+    }
+
+    let token = invites::generate_token();
+    let invite = queries::create_share_invite(
         &state.pool,
-        &payload.process_id,
-        &payload.supplier_public_key,
-        &tx_result.hash,
+        &process.id,
+        &client.id,
+        &email,
+        &invites::hash_token(&token),
+        payload.expires_at,
+        Utc::now() + invites::invite_validity(),
     )
     .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .map_err(|e| match e.as_database_error() {
+        Some(db) if db.is_unique_violation() => ApiError::Conflict(ErrorCode::AlreadyInvited),
+        _ => e.into(),
+    })?;
 
-    Ok(ResponseJson(share))
+    // E-mail em segundo plano para não atrasar a resposta
+    let message = invites::render_invite_email(&email, &client.username, &process.title, &token);
+    let email_notifier = state.notifier.clone();
+    let invite_id = invite.id.clone();
+    tokio::spawn(async move {
+        if let Err(e) = email_notifier.send(&message).await {
+            tracing::warn!(invite_id = %invite_id, error = %e, "erro ao enviar e-mail de convite");
+        }
+    });
+
+    Ok((StatusCode::CREATED, ResponseJson(CreateShareInviteResponse { invite, token })))
 }
 
 // src/handlers.rs - Substituir a função access_process
 
+#[utoipa::path(
+    post, path = "/api/processes/access", tag = "processes",
+    request_body = AccessProcessRequest,
+    responses(
+        (status = 200, description = "Conteúdo descriptografado", body = ProcessAccessResponse),
+        (status = 403, description = "Processo não compartilhado com o fornecedor, compartilhamento expirado ou conta bloqueada", body = ErrorBody),
+        (status = 404, description = "Processo ou fornecedor não encontrado", body = ErrorBody),
+        (status = 409, description = "Compartilhamento aguardando confirmação no ledger", body = ErrorBody),
+        (status = 429, description = "Limite de requisições atingido", body = ErrorBody),
+    )
+)]
 pub async fn access_process(
     State(state): State<Arc<AppState>>,
-    Json(payload): Json<AccessProcessRequest>,
-) -> Result<ResponseJson<ProcessAccessResponse>, StatusCode> {
-    // Buscar processo com campos específicos
-    let process = sqlx::query!(
-        r#"
-        SELECT id, client_id, title, encrypted_content, encryption_key, status, created_at
-        FROM processes WHERE id = ?
-        "#,
-        payload.process_id
-    )
-    .fetch_optional(&state.pool)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    .ok_or(StatusCode::NOT_FOUND)?;
-
-    // Buscar fornecedor com campos específicos
-    let supplier = sqlx::query!(
-        r#"
-        SELECT id, username, stellar_public_key, stellar_secret_key, user_type, created_at
-        FROM users WHERE username = ?
-        "#,
-        payload.supplier_username
-    )
-    .fetch_optional(&state.pool)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-    .ok_or(StatusCode::NOT_FOUND)?;
-
-    // Verificar se existe compartilhamento no banco
-    let share_exists = sqlx::query!(
-        "SELECT id FROM process_shares WHERE process_id = ? AND supplier_public_key = ?",
-        payload.process_id,
-        supplier.stellar_public_key
-    )
-    .fetch_optional(&state.pool)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    ApiJson(payload): ApiJson<AccessProcessRequest>,
+) -> Result<ResponseJson<ProcessAccessResponse>, ApiError> {
+    // Buscar processo
+    let process = state.processes.find_by_id(&payload.process_id)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::ProcessNotFound))?;
+    record_process(&process.id);
+
+    // Buscar fornecedor
+    let supplier = state.users.find_by_username(&payload.supplier_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&supplier.id);
+
+    if supplier.locked_at.is_some() {
+        return Err(ApiError::Forbidden(ErrorCode::AccountLocked));
+    }
+
+    // Verificar se existe compartilhamento confirmado no banco
+    let share = state.shares.find(&payload.process_id, &supplier.stellar_public_key)
+        .await?;
+    let share_status = share.as_ref().map(|share| share.status.clone());
 
-    if share_exists.is_none() {
-        println!("❌ Acesso negado: Processo não foi compartilhado com este fornecedor");
-        return Err(StatusCode::FORBIDDEN);
+    // Compartilhamento ainda não confirmado no ledger
+    if share_status.as_deref() == Some("pending") {
+        return Err(ApiError::Conflict(ErrorCode::SharePending));
     }
 
-    println!("✅ Acesso autorizado: Compartilhamento encontrado no banco");
+    // Vencido: confirmado com expires_at no passado ou já marcado como 'expired'
+    let expired = share.as_ref().is_some_and(|share| {
+        share.status == "expired" || (share.status == "confirmed" && share.is_expired(Utc::now()))
+    });
+    if expired {
+        tracing::info!("acesso negado: compartilhamento expirado");
+        metrics().access("denied");
+        notify(
+            &state,
+            &process.client_id,
+            NotificationEvent::ShareExpired,
+            &payload.process_id,
+            Some(&supplier.id),
+            &format!("Compartilhamento do processo '{}' com {} expirou", process.title, supplier.username),
+        )
+        .await;
+        return Err(ApiError::Forbidden(ErrorCode::ShareExpired));
+    }
+
+    if share_status.as_deref() != Some("confirmed") {
+        tracing::info!("acesso negado: processo não compartilhado com o fornecedor");
+        metrics().access("denied");
+        notify(
+            &state,
+            &process.client_id,
+            NotificationEvent::Denied,
+            &payload.process_id,
+            Some(&supplier.id),
+            &format!("Acesso negado ao fornecedor {} no processo '{}'", supplier.username, process.title),
+        )
+        .await;
+        return Err(ApiError::Forbidden(ErrorCode::AccessDenied));
+    }
+
+    tracing::debug!("acesso autorizado: compartilhamento confirmado");
 
     // Descriptografar conteúdo
-    let decrypted_content = decrypt_content(&process.encrypted_content, &process.encryption_key)
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let decrypted_content = decrypt_content(&process.encrypted_content, &process.encryption_key)?;
 
     // Registrar acesso
-    let access_id = Uuid::new_v4().to_string();
-    let now = Utc::now();
-    let now_string = now.to_rfc3339();
-    
-    sqlx::query!(
-        r#"
-        INSERT INTO process_accesses (id, process_id, supplier_id, accessed_at)
-        VALUES (?, ?, ?, ?)
-        "#,
-        access_id,
-        payload.process_id,
-        supplier.id,
-        now_string
+    let access = state.accesses.create(&payload.process_id, &supplier.id)
+        .await?;
+
+    tracing::info!(access_id = %access.id, "acesso registrado");
+    metrics().access("granted");
+
+    notify(
+        &state,
+        &process.client_id,
+        NotificationEvent::Accessed,
+        &payload.process_id,
+        Some(&supplier.id),
+        &format!("Fornecedor {} acessou o processo '{}'", supplier.username, process.title),
     )
-    .execute(&state.pool)
-    .await
-    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    .await;
 
-    println!("📊 Acesso registrado com sucesso");
+    emit_webhook(
+        &state,
+        &process.client_id,
+        WebhookEvent::ProcessAccessed,
+        json!({
+            "access_id": access.id,
+            "process_id": access.process_id,
+            "supplier_id": access.supplier_id,
+            "supplier_username": supplier.username,
+            "accessed_at": access.accessed_at,
+        }),
+    )
+    .await;
 
     let response = ProcessAccessResponse {
         process_id: payload.process_id,
         title: process.title,
         content: decrypted_content,
-        accessed_at: now,
+        accessed_at: access.accessed_at,
     };
 
     Ok(ResponseJson(response))
 }
 
+#[utoipa::path(
+    get, path = "/api/processes", tag = "processes",
+    params(ListProcessesQuery),
+    responses(
+        (status = 200, description = "Página de processos do cliente", body = ProcessPage),
+        (status = 400, description = "client_username ausente, filtro ou cursor inválido", body = ErrorBody),
+        (status = 404, description = "Cliente não encontrado", body = ErrorBody),
+    )
+)]
 pub async fn list_processes(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<ListProcessesQuery>,
-) -> Result<ResponseJson<Vec<ProcessResponse>>, StatusCode> {
-    let client_username = params.client_username.ok_or(StatusCode::BAD_REQUEST)?;
+    ApiQuery(params): ApiQuery<ListProcessesQuery>,
+) -> Result<ResponseJson<ProcessPage>, ApiError> {
+    let client_username = params.client_username.ok_or(ApiError::BadRequest(ErrorCode::MissingParameter))?;
     
     // Buscar cliente pelo username
-    let client = queries::find_user_by_username(&state.pool, &client_username)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+    let client = state.users.find_by_username(&client_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&client.id);
 
-    let processes = queries::list_processes_by_client(&state.pool, &client.id)
-        .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let mut options = ProcessListOptions {
+        status: params.status,
+        created_from: params.created_from,
+        created_to: params.created_to,
+        search: params.q.filter(|q| !q.trim().is_empty()),
+        supplier: params.supplier,
+        tag: params.tag.map(|tag| tag.trim().to_lowercase()),
+        category: params.category,
+        project_code: params.project_code,
+        sort: params.sort.unwrap_or_default(),
+        order: params.order.unwrap_or_default(),
+        after: None,
+        limit: clamp_page_size(params.per_page),
+    };
+    options.after = decode_cursor(params.cursor.as_deref(), &options.sort_key())?;
 
-    let response: Vec<ProcessResponse> = processes.into_iter().map(|p| p.into()).collect();
-    Ok(ResponseJson(response))
+    let page = state.processes.list_by_client(&client.id, &options)
+        .await?;
+
+    let ids: Vec<String> = page.items.iter().map(|p| p.id.clone()).collect();
+    let mut metadata = state.processes.find_metadata(&ids)
+        .await?;
+
+    Ok(ResponseJson(ProcessPage {
+        processes: page.items
+            .into_iter()
+            .map(|p| {
+                let m = metadata.remove(&p.id).unwrap_or_default();
+                ProcessResponse::new(p, m)
+            })
+            .collect(),
+        total: page.total,
+        next_cursor: page.next_cursor,
+    }))
+}
+
+#[utoipa::path(
+    get, path = "/api/processes/search", tag = "processes",
+    params(SearchProcessesQuery),
+    responses(
+        (status = 200, description = "Processos encontrados, por relevância", body = ProcessSearchPage),
+        (status = 400, description = "username ou q ausente", body = ErrorBody),
+        (status = 404, description = "Usuário não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn search_processes(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<SearchProcessesQuery>,
+) -> Result<ResponseJson<ProcessSearchPage>, ApiError> {
+    let username = params.username.ok_or(ApiError::BadRequest(ErrorCode::MissingParameter))?;
+    let terms = search::terms(params.q.as_deref().unwrap_or_default());
+    if terms.is_empty() {
+        return Err(ApiError::BadRequest(ErrorCode::MissingParameter));
+    }
+
+    let user = state.users.find_by_username(&username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&user.id);
+
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = clamp_page_size(params.per_page);
+    let offset = (page as i64 - 1) * per_page as i64;
+
+    let (results, total) = queries::search_processes(&state.pool, &user, &terms, per_page as i64, offset)
+        .await?;
+
+    Ok(ResponseJson(ProcessSearchPage { results, page, per_page, total }))
+}
+
+#[utoipa::path(
+    get, path = "/api/supplier/processes", tag = "supplier",
+    params(SupplierProcessesQuery),
+    responses(
+        (status = 200, description = "Processos compartilhados com o fornecedor", body = SupplierProcessPage),
+        (status = 400, description = "supplier_public_key ausente ou cursor inválido", body = ErrorBody),
+        (status = 404, description = "Fornecedor não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn list_supplier_processes(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<SupplierProcessesQuery>,
+) -> Result<ResponseJson<SupplierProcessPage>, ApiError> {
+    let public_key = params.supplier_public_key.ok_or(ApiError::BadRequest(ErrorCode::MissingParameter))?;
+
+    // Os acessos são registrados pelo id do fornecedor cadastrado
+    let supplier = state.users.find_by_public_key(&public_key)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&supplier.id);
+
+    let mut options = SupplierShareListOptions {
+        status: params.status,
+        after: None,
+        limit: clamp_page_size(params.per_page),
+    };
+    options.after = decode_cursor(params.cursor.as_deref(), &options.sort_key())?;
+
+    let page = queries::list_supplier_shares(&state.pool, &supplier, &options)
+        .await?;
+
+    Ok(ResponseJson(SupplierProcessPage {
+        processes: page.items,
+        total: page.total,
+        next_cursor: page.next_cursor,
+    }))
+}
+
+#[utoipa::path(
+    post, path = "/api/supplier/processes/{id}/accept", tag = "supplier",
+    params(("id" = String, Path, description = "Id do compartilhamento")),
+    request_body = AcceptShareRequest,
+    responses(
+        (status = 200, description = "NDA aceito (repetições devolvem o aceite original)", body = ProcessShare),
+        (status = 403, description = "Compartilhamento de outro fornecedor, revogado, expirado ou conta bloqueada", body = ErrorBody),
+        (status = 404, description = "Compartilhamento ou fornecedor não encontrado", body = ErrorBody),
+        (status = 409, description = "Compartilhamento aguardando confirmação no ledger", body = ErrorBody),
+    )
+)]
+pub async fn accept_share(
+    State(state): State<Arc<AppState>>,
+    ApiPath(share_id): ApiPath<String>,
+    ApiJson(payload): ApiJson<AcceptShareRequest>,
+) -> Result<ResponseJson<ProcessShare>, ApiError> {
+    let share = state.shares.find_by_id(&share_id)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::ShareNotFound))?;
+
+    let supplier = state.users.find_by_username(&payload.supplier_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&supplier.id);
+
+    if supplier.locked_at.is_some() {
+        return Err(ApiError::Forbidden(ErrorCode::AccountLocked));
+    }
+    if share.supplier_public_key != supplier.stellar_public_key {
+        return Err(ApiError::Forbidden(ErrorCode::AccessDenied));
+    }
+    if share.accepted_at.is_some() {
+        return Ok(ResponseJson(share));
+    }
+    match share.status.as_str() {
+        "confirmed" => {}
+        "pending" => return Err(ApiError::Conflict(ErrorCode::SharePending)),
+        _ => return Err(ApiError::Forbidden(ErrorCode::AccessDenied)),
+    }
+    if share.is_expired(Utc::now()) {
+        return Err(ApiError::Forbidden(ErrorCode::ShareExpired));
+    }
+
+    // Corrida com outra requisição ou revogação: devolve o estado atual
+    state.shares.accept(&share.id)
+        .await?;
+    let share = state.shares.find_by_id(&share.id)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::ShareNotFound))?;
+    if share.accepted_at.is_none() {
+        return Err(ApiError::Forbidden(ErrorCode::AccessDenied));
+    }
+
+    let process = state.processes.find_by_id(&share.process_id)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::ProcessNotFound))?;
+    record_process(&process.id);
+
+    notify(
+        &state,
+        &process.client_id,
+        NotificationEvent::Accepted,
+        &process.id,
+        Some(&supplier.id),
+        &format!("Fornecedor {} aceitou o NDA '{}'", supplier.username, process.title),
+    )
+    .await;
+
+    emit_webhook(
+        &state,
+        &process.client_id,
+        WebhookEvent::ShareAccepted,
+        json!({
+            "share_id": share.id,
+            "process_id": process.id,
+            "supplier_id": supplier.id,
+            "supplier_username": supplier.username,
+            "accepted_at": share.accepted_at,
+        }),
+    )
+    .await;
+
+    Ok(ResponseJson(share))
 }
 
+#[utoipa::path(
+    get, path = "/api/notifications", tag = "notifications",
+    params(NotificationsQuery),
+    responses(
+        (status = 200, description = "Página de notificações", body = NotificationPage),
+        (status = 400, description = "username ausente, filtro ou cursor inválido", body = ErrorBody),
+        (status = 404, description = "Usuário não encontrado", body = ErrorBody),
+    )
+)]
 pub async fn get_notifications(
     State(state): State<Arc<AppState>>,
-    Query(params): Query<ListProcessesQuery>,
-) -> Result<ResponseJson<Vec<ProcessAccessWithDetails>>, StatusCode> {
-    let client_username = params.client_username.ok_or(StatusCode::BAD_REQUEST)?;
-    
-    // Buscar cliente pelo username
-    let client = queries::find_user_by_username(&state.pool, &client_username)
+    ApiQuery(params): ApiQuery<NotificationsQuery>,
+) -> Result<ResponseJson<NotificationPage>, ApiError> {
+    let username = params.username
+        .or(params.client_username)
+        .ok_or(ApiError::BadRequest(ErrorCode::MissingParameter))?;
+
+    // Buscar destinatário (cliente ou fornecedor) pelo username
+    let user = state.users.find_by_username(&username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&user.id);
+
+    let page = params.page.unwrap_or(1).max(1);
+    let per_page = clamp_page_size(params.per_page);
+    let mut options = NotificationListOptions {
+        // unread_only=true equivale a status=unread
+        status: params.status.or(params.unread_only.unwrap_or(false).then_some(ReadStatus::Unread)),
+        event: params.event,
+        created_from: params.created_from,
+        created_to: params.created_to,
+        search: params.q.filter(|q| !q.trim().is_empty()),
+        supplier: params.supplier,
+        order: params.order.unwrap_or_default(),
+        after: None,
+        offset: (page as i64 - 1) * per_page as i64,
+        limit: per_page,
+    };
+    options.after = decode_cursor(params.cursor.as_deref(), &options.sort_key())?;
+
+    let result = queries::list_notifications_for_user(&state.pool, &user.id, &options)
+        .await?;
+
+    let unread_count = queries::count_notifications_for_user(&state.pool, &user.id, true)
+        .await?;
+
+    Ok(ResponseJson(NotificationPage {
+        notifications: result.items,
+        page,
+        per_page,
+        total: result.total,
+        unread_count,
+        next_cursor: result.next_cursor,
+    }))
+}
+
+#[utoipa::path(
+    post, path = "/api/notifications/{id}/read", tag = "notifications",
+    params(("id" = String, Path, description = "Id da notificação")),
+    request_body = MarkNotificationsReadRequest,
+    responses(
+        (status = 204, description = "Notificação marcada como lida"),
+        (status = 404, description = "Usuário ou notificação não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn mark_notification_read(
+    State(state): State<Arc<AppState>>,
+    ApiPath(notification_id): ApiPath<String>,
+    ApiJson(payload): ApiJson<MarkNotificationsReadRequest>,
+) -> Result<StatusCode, ApiError> {
+    let user = state.users.find_by_username(&payload.username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&user.id);
+
+    let updated = queries::mark_notification_read(&state.pool, &notification_id, &user.id)
+        .await?;
+
+    if !updated {
+        return Err(ApiError::NotFound(ErrorCode::NotificationNotFound));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    post, path = "/api/notifications/read-all", tag = "notifications",
+    request_body = MarkNotificationsReadRequest,
+    responses(
+        (status = 204, description = "Todas as notificações marcadas como lidas"),
+        (status = 404, description = "Usuário não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn mark_all_notifications_read(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<MarkNotificationsReadRequest>,
+) -> Result<StatusCode, ApiError> {
+    let user = state.users.find_by_username(&payload.username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&user.id);
+
+    queries::mark_all_notifications_read(&state.pool, &user.id)
+        .await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get, path = "/api/notifications/ws", tag = "notifications",
+    params(NotificationStreamQuery),
+    responses(
+        (status = 101, description = "WebSocket aberto; cada mensagem é uma Notification em JSON"),
+        (status = 404, description = "Usuário não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn notifications_ws(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<NotificationStreamQuery>,
+    ws: WebSocketUpgrade,
+) -> Result<Response, ApiError> {
+    let user = state.users.find_by_username(&params.username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&user.id);
+
+    // Assinar antes do upgrade para não perder eventos durante o handshake
+    let rx = state.notifications_tx.subscribe();
+
+    Ok(ws.on_upgrade(move |socket| push_notifications_ws(socket, rx, user.id)))
+}
+
+async fn push_notifications_ws(
+    mut socket: WebSocket,
+    mut rx: broadcast::Receiver<Notification>,
+    recipient_id: String,
+) {
+    tracing::info!(user_id = %recipient_id, "WebSocket de notificações conectado");
+
+    loop {
+        tokio::select! {
+            received = rx.recv() => match received {
+                Ok(notification) if notification.recipient_id == recipient_id => {
+                    let payload = match serde_json::to_string(&notification) {
+                        Ok(payload) => payload,
+                        Err(_) => continue,
+                    };
+                    if socket.send(Message::Text(payload)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!(user_id = %recipient_id, skipped, "WebSocket atrasado, notificações descartadas");
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | None | Some(Err(_)) => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+
+    tracing::info!(user_id = %recipient_id, "WebSocket de notificações desconectado");
+}
+
+#[utoipa::path(
+    get, path = "/api/notifications/stream", tag = "notifications",
+    params(NotificationStreamQuery),
+    responses(
+        (status = 200, description = "Server-Sent Events; cada evento é uma Notification em JSON", content_type = "text/event-stream"),
+        (status = 404, description = "Usuário não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn notifications_sse(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<NotificationStreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let user = state.users.find_by_username(&params.username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&user.id);
+
+    let recipient_id = user.id;
+    let stream = BroadcastStream::new(state.notifications_tx.subscribe()).filter_map(move |received| {
+        // Eventos perdidos por atraso são descartados; o cliente pode reconciliar via GET
+        let notification = received.ok()?;
+        if notification.recipient_id != recipient_id {
+            return None;
+        }
+        Event::default()
+            .event(notification.event_type.as_str())
+            .id(notification.id.clone())
+            .json_data(&notification)
+            .ok()
+            .map(Ok)
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
+
+#[utoipa::path(
+    get, path = "/api/users/preferences", tag = "users",
+    params(PreferencesQuery),
+    responses(
+        (status = 200, description = "Preferências de notificação", body = NotificationPreferences),
+        (status = 404, description = "Usuário não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn get_preferences(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<PreferencesQuery>,
+) -> Result<ResponseJson<NotificationPreferences>, ApiError> {
+    let user = state.users.find_by_username(&params.username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&user.id);
+
+    let preferences = queries::find_notification_preferences(&state.pool, &user.id)
+        .await?
+        .unwrap_or_else(|| notifier::default_preferences(&user));
+
+    Ok(ResponseJson(preferences))
+}
+
+#[utoipa::path(
+    put, path = "/api/users/preferences", tag = "users",
+    request_body = UpdatePreferencesRequest,
+    responses(
+        (status = 200, description = "Preferências atualizadas", body = NotificationPreferences),
+        (status = 400, description = "E-mail ou idioma inválido", body = ErrorBody),
+        (status = 404, description = "Usuário não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn update_preferences(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<UpdatePreferencesRequest>,
+) -> Result<ResponseJson<NotificationPreferences>, ApiError> {
+    let user = state.users.find_by_username(&payload.username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&user.id);
+
+    // Campos ausentes mantêm o valor atual
+    let mut preferences = queries::find_notification_preferences(&state.pool, &user.id)
+        .await?
+        .unwrap_or_else(|| notifier::default_preferences(&user));
+
+    if let Some(email) = payload.email {
+        if !notifier::is_valid_email(&email) {
+            return Err(ApiError::BadRequest(ErrorCode::InvalidEmail));
+        }
+        preferences.email = Some(email);
+    }
+    if let Some(language) = payload.language {
+        if !notifier::SUPPORTED_LANGUAGES.contains(&language.as_str()) {
+            return Err(ApiError::BadRequest(ErrorCode::InvalidLanguage));
+        }
+        preferences.language = language;
+    }
+    if let Some(email_enabled) = payload.email_enabled {
+        preferences.email_enabled = email_enabled;
+    }
+    if let Some(email_events) = payload.email_events {
+        preferences.email_events = email_events;
+    }
+
+    // Não é possível ativar e-mail sem endereço
+    if preferences.email_enabled && preferences.email.is_none() {
+        return Err(ApiError::BadRequest(ErrorCode::EmailRequired));
+    }
+
+    let preferences = queries::upsert_notification_preferences(&state.pool, &preferences)
+        .await?;
+
+    Ok(ResponseJson(preferences))
+}
+
+#[utoipa::path(
+    post, path = "/api/webhooks", tag = "webhooks",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 200, description = "Webhook criado; o segredo só é exibido nesta resposta", body = CreateWebhookResponse),
+        (status = 400, description = "URL ou evento inválido", body = ErrorBody),
+        (status = 404, description = "Cliente não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn create_webhook(
+    State(state): State<Arc<AppState>>,
+    ApiJson(payload): ApiJson<CreateWebhookRequest>,
+) -> Result<ResponseJson<CreateWebhookResponse>, ApiError> {
+    let client = state.users.find_by_username(&payload.client_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&client.id);
+
+    // Apenas URLs http(s) válidas
+    let url = url::Url::parse(&payload.url).map_err(|_| ApiError::BadRequest(ErrorCode::InvalidUrl))?;
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(ApiError::BadRequest(ErrorCode::InvalidUrl));
+    }
+
+    let events = payload.events.unwrap_or_default();
+    if events.iter().any(|e| WebhookEvent::parse(e).is_none()) {
+        return Err(ApiError::BadRequest(ErrorCode::InvalidWebhookEvent));
+    }
+
+    let secret = webhooks::generate_secret();
+    let endpoint = queries::create_webhook_endpoint(&state.pool, &client.id, url.as_str(), &secret, &events)
+        .await?;
+
+    Ok(ResponseJson(CreateWebhookResponse { endpoint, secret }))
+}
+
+#[utoipa::path(
+    get, path = "/api/webhooks", tag = "webhooks",
+    params(ClientQuery),
+    responses(
+        (status = 200, description = "Webhooks do cliente", body = Vec<WebhookEndpoint>),
+        (status = 404, description = "Cliente não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn list_webhooks(
+    State(state): State<Arc<AppState>>,
+    ApiQuery(params): ApiQuery<ClientQuery>,
+) -> Result<ResponseJson<Vec<WebhookEndpoint>>, ApiError> {
+    let client_username = params.client_username.ok_or(ApiError::BadRequest(ErrorCode::MissingParameter))?;
+
+    let client = state.users.find_by_username(&client_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&client.id);
+
+    let endpoints = queries::list_webhook_endpoints_by_client(&state.pool, &client.id)
+        .await?;
+
+    Ok(ResponseJson(endpoints))
+}
+
+#[utoipa::path(
+    delete, path = "/api/webhooks/{id}", tag = "webhooks",
+    params(("id" = String, Path, description = "Id do webhook"), ClientQuery),
+    responses(
+        (status = 204, description = "Webhook removido"),
+        (status = 404, description = "Cliente ou webhook não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn delete_webhook(
+    State(state): State<Arc<AppState>>,
+    ApiPath(endpoint_id): ApiPath<String>,
+    ApiQuery(params): ApiQuery<ClientQuery>,
+) -> Result<StatusCode, ApiError> {
+    let client_username = params.client_username.ok_or(ApiError::BadRequest(ErrorCode::MissingParameter))?;
+
+    let client = state.users.find_by_username(&client_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&client.id);
+
+    let removed = queries::deactivate_webhook_endpoint(&state.pool, &endpoint_id, &client.id)
+        .await?;
+
+    if !removed {
+        return Err(ApiError::NotFound(ErrorCode::WebhookNotFound));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get, path = "/api/webhooks/{id}/deliveries", tag = "webhooks",
+    params(("id" = String, Path, description = "Id do webhook"), ClientQuery),
+    responses(
+        (status = 200, description = "Últimas entregas do webhook", body = Vec<WebhookDelivery>),
+        (status = 404, description = "Cliente ou webhook não encontrado", body = ErrorBody),
+    )
+)]
+pub async fn list_webhook_deliveries(
+    State(state): State<Arc<AppState>>,
+    ApiPath(endpoint_id): ApiPath<String>,
+    ApiQuery(params): ApiQuery<ClientQuery>,
+) -> Result<ResponseJson<Vec<WebhookDelivery>>, ApiError> {
+    let client_username = params.client_username.ok_or(ApiError::BadRequest(ErrorCode::MissingParameter))?;
+
+    let client = state.users.find_by_username(&client_username)
+        .await?
+        .ok_or(ApiError::NotFound(ErrorCode::UserNotFound))?;
+    record_user(&client.id);
+
+    // Endpoint de outro cliente é tratado como inexistente
+    let endpoint = queries::find_webhook_endpoint(&state.pool, &endpoint_id)
+        .await?
+        .filter(|endpoint| endpoint.client_id == client.id)
+        .ok_or(ApiError::NotFound(ErrorCode::WebhookNotFound))?;
+
+    let deliveries = queries::list_webhook_deliveries_by_endpoint(&state.pool, &endpoint.id, WEBHOOK_DELIVERY_LOG_LIMIT)
+        .await?;
+
+    Ok(ResponseJson(deliveries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_pool;
+    use crate::repository::InMemoryRepository;
+    use sqlx::any::AnyPoolOptions;
+
+    // Estado com repositórios em memória; o pool é preguiçoso e só é tocado
+    // pelos efeitos colaterais (notificações e webhooks), cujas falhas são ignoradas
+    fn fake_state() -> (Arc<AppState>, Arc<InMemoryRepository>) {
+        sqlx::any::install_default_drivers();
+        let pool = AnyPoolOptions::new().connect_lazy("sqlite::memory:").unwrap();
+        let repository = Arc::new(InMemoryRepository::new());
+        (Arc::new(AppState::new(pool).with_repository(repository.clone())), repository)
+    }
+
+    #[tokio::test]
+    async fn test_create_and_list_processes_with_fake_repository() {
+        let (state, repository) = fake_state();
+        UserRepo::create(repository.as_ref(), "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+
+        let ResponseJson(created) = create_process(
+            State(state.clone()),
+            ApiJson(CreateProcessRequest {
+                title: "NDA Alpha".to_string(),
+                confidential_content: "segredo".to_string(),
+                client_username: "cliente".to_string(),
+                metadata: ProcessMetadata {
+                    tags: vec![" Projeto-X ".to_string(), "projeto-x".to_string()],
+                    project_code: Some("PX-1".to_string()),
+                    ..Default::default()
+                },
+            }),
+        )
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .unwrap();
 
-    let notifications = queries::list_process_accesses_by_client(&state.pool, &client.id)
+        let stored = ProcessRepo::find_by_id(repository.as_ref(), &created.id).await.unwrap().unwrap();
+        assert_ne!(stored.encrypted_content, "segredo");
+
+        let ResponseJson(listed) = list_processes(
+            State(state),
+            ApiQuery(ListProcessesQuery { client_username: Some("cliente".to_string()), ..Default::default() }),
+        )
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        .unwrap();
+        assert_eq!(listed.total, 1);
+        assert_eq!(listed.processes[0].title, "NDA Alpha");
+        assert_eq!(listed.processes[0].metadata.tags, ["projeto-x"]);
+        assert_eq!(listed.processes[0].metadata.project_code.as_deref(), Some("PX-1"));
+    }
+
+    #[tokio::test]
+    async fn test_list_processes_follows_cursor() {
+        let (state, repository) = fake_state();
+        let client = UserRepo::create(repository.as_ref(), "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        for title in ["NDA C", "NDA A", "NDA B"] {
+            ProcessRepo::create(repository.as_ref(), &client.id, title, "xxx", "key").await.unwrap();
+        }
+
+        let query = |cursor: Option<String>| ListProcessesQuery {
+            client_username: Some("cliente".to_string()),
+            sort: Some(ProcessSort::Title),
+            order: Some(SortOrder::Desc),
+            cursor,
+            per_page: Some(2),
+            ..Default::default()
+        };
+
+        let ResponseJson(first) = list_processes(State(state.clone()), ApiQuery(query(None))).await.unwrap();
+        assert_eq!(first.total, 3);
+        let titles: Vec<_> = first.processes.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, ["NDA C", "NDA B"]);
+
+        let ResponseJson(second) = list_processes(State(state.clone()), ApiQuery(query(first.next_cursor.clone()))).await.unwrap();
+        assert_eq!(second.processes.len(), 1);
+        assert_eq!(second.processes[0].title, "NDA A");
+        assert!(second.next_cursor.is_none());
+
+        // Cursor de outra ordenação é rejeitado
+        let mut other_order = query(first.next_cursor);
+        other_order.order = Some(SortOrder::Asc);
+        let result = list_processes(State(state), ApiQuery(other_order)).await;
+        assert!(matches!(result, Err(ApiError::BadRequest(ErrorCode::InvalidCursor))));
+    }
+
+    #[tokio::test]
+    async fn test_access_process_requires_share() {
+        let (state, repository) = fake_state();
+        let repo = repository.as_ref();
+        let client = UserRepo::create(repo, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        UserRepo::create(repo, "fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+        // O fake reproduz as restrições de unicidade do banco
+        let duplicate = UserRepo::create(repo, "outro", "GSUPPLIER", "SOTHER", "supplier").await.unwrap_err();
+        assert!(duplicate.as_database_error().is_some_and(|e| e.is_unique_violation()));
+        let key = generate_key();
+        let encrypted = encrypt_content("segredo", &key).unwrap();
+        let process = ProcessRepo::create(repo, &client.id, "NDA Alpha", &encrypted, &key).await.unwrap();
+
+        let request = || AccessProcessRequest {
+            process_id: process.id.clone(),
+            supplier_public_key: "GSUPPLIER".to_string(),
+            supplier_username: "fornecedor".to_string(),
+        };
+
+        let denied = access_process(State(state.clone()), ApiJson(request())).await;
+        assert!(matches!(denied, Err(ApiError::Forbidden(ErrorCode::AccessDenied))));
+
+        let share = ShareRepo::create(repo, &process.id, "GSUPPLIER", None).await.unwrap();
+        let pending = access_process(State(state.clone()), ApiJson(request())).await;
+        assert!(matches!(pending, Err(ApiError::Conflict(ErrorCode::SharePending))));
+        let duplicate = ShareRepo::create(repo, &process.id, "GSUPPLIER", None).await.unwrap_err();
+        assert!(duplicate.as_database_error().is_some_and(|e| e.is_unique_violation()));
+        // Lote com um fornecedor já compartilhado não cria nenhum compartilhamento
+        let keys = ["GNOVO".to_string(), "GSUPPLIER".to_string()];
+        let duplicate = ShareRepo::create_batch(repo, &process.id, &keys, None, 100).await.unwrap_err();
+        assert!(duplicate.as_database_error().is_some_and(|e| e.is_unique_violation()));
+        assert!(ShareRepo::find(repo, &process.id, "GNOVO").await.unwrap().is_none());
+
+        repository.confirm_share(&share.id, "tx-hash");
+        let ResponseJson(response) = access_process(State(state), ApiJson(request())).await.unwrap();
+        assert_eq!(response.content, "segredo");
+
+        let accesses = AccessRepo::list_by_client(repo, &client.id).await.unwrap();
+        assert_eq!(accesses.len(), 1);
+        assert_eq!(accesses[0].supplier_username, "fornecedor");
+    }
+
+    #[tokio::test]
+    async fn test_accept_share_and_expiry() {
+        // Banco real: as notificações são gravadas antes de publicadas
+        let state = Arc::new(AppState::new(test_pool().await));
+        let client = state.users.create("cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let supplier = state.users.create("fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+        state.users.create("outro", "GOTHER", "SOTHER", "supplier").await.unwrap();
+        let key = generate_key();
+        let encrypted = encrypt_content("segredo", &key).unwrap();
+        let process = state.processes.create(&client.id, "NDA Alpha", &encrypted, &key).await.unwrap();
+        let confirm = |share_id: String| {
+            let pool = state.pool.clone();
+            async move {
+                let entry = queries::find_ledger_outbox_by_share(&pool, &share_id).await.unwrap().unwrap();
+                queries::confirm_ledger_outbox(&pool, &entry, "tx-hash").await.unwrap();
+            }
+        };
+
+        let accept = |share_id: &str, username: &str| {
+            accept_share(
+                State(state.clone()),
+                ApiPath(share_id.to_string()),
+                ApiJson(AcceptShareRequest { supplier_username: username.to_string() }),
+            )
+        };
 
-    Ok(ResponseJson(notifications))
-}
\ No newline at end of file
+        let share = state.shares.create(&process.id, "GSUPPLIER", None).await.unwrap();
+        assert!(matches!(accept(&share.id, "fornecedor").await, Err(ApiError::Conflict(ErrorCode::SharePending))));
+        confirm(share.id.clone()).await;
+        assert!(matches!(accept(&share.id, "outro").await, Err(ApiError::Forbidden(ErrorCode::AccessDenied))));
+        assert!(matches!(accept("inexistente", "fornecedor").await, Err(ApiError::NotFound(ErrorCode::ShareNotFound))));
+
+        let ResponseJson(accepted) = accept(&share.id, "fornecedor").await.unwrap();
+        let accepted_at = accepted.accepted_at.expect("aceite registrado");
+        let ResponseJson(again) = accept(&share.id, "fornecedor").await.unwrap();
+        assert_eq!(again.accepted_at, Some(accepted_at));
+
+        // Compartilhamento expirado não pode ser aceito nem acessado
+        let expired = state.processes.create(&client.id, "NDA Beta", &encrypted, &key).await.unwrap();
+        let past = Utc::now() - chrono::Duration::minutes(1);
+        let share = state.shares.create(&expired.id, "GSUPPLIER", Some(past)).await.unwrap();
+        confirm(share.id.clone()).await;
+        assert!(matches!(accept(&share.id, "fornecedor").await, Err(ApiError::Forbidden(ErrorCode::ShareExpired))));
+
+        let mut rx = state.notifications_tx.subscribe();
+        let denied = access_process(
+            State(state.clone()),
+            ApiJson(AccessProcessRequest {
+                process_id: expired.id.clone(),
+                supplier_public_key: "GSUPPLIER".to_string(),
+                supplier_username: "fornecedor".to_string(),
+            }),
+        )
+        .await;
+        assert!(matches!(denied, Err(ApiError::Forbidden(ErrorCode::ShareExpired))));
+
+        // O dono do processo é avisado da tentativa após o vencimento
+        let notification = rx.try_recv().unwrap();
+        assert_eq!(notification.recipient_id, client.id);
+        assert_eq!(notification.event_type, NotificationEvent::ShareExpired);
+        assert_eq!(notification.actor_id.as_deref(), Some(supplier.id.as_str()));
+
+        // Depois de vencido, o processo pode ser compartilhado de novo com o fornecedor
+        let (status, ResponseJson(renewed)) = share_process(
+            State(state.clone()),
+            ApiJson(ShareProcessRequest {
+                process_id: expired.id.clone(),
+                supplier_username: Some("fornecedor".to_string()),
+                supplier_public_key: None,
+                client_username: "cliente".to_string(),
+                expires_at: None,
+            }),
+        )
+        .await
+        .unwrap();
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_ne!(renewed.id, share.id);
+        let old = state.shares.find_by_id(&share.id).await.unwrap().unwrap();
+        assert_eq!(old.status, "expired");
+
+        // O acesso segue o compartilhamento novo, ainda pendente, e não o vencido
+        let pending = access_process(
+            State(state.clone()),
+            ApiJson(AccessProcessRequest {
+                process_id: expired.id.clone(),
+                supplier_public_key: "GSUPPLIER".to_string(),
+                supplier_username: "fornecedor".to_string(),
+            }),
+        )
+        .await;
+        assert!(matches!(pending, Err(ApiError::Conflict(ErrorCode::SharePending))));
+
+        let in_past = share_process(
+            State(state),
+            ApiJson(ShareProcessRequest {
+                process_id: process.id.clone(),
+                supplier_username: None,
+                supplier_public_key: Some("GOTHER".to_string()),
+                client_username: "cliente".to_string(),
+                expires_at: Some(past),
+            }),
+        )
+        .await;
+        assert!(matches!(in_past, Err(ApiError::BadRequest(ErrorCode::InvalidExpiry))));
+    }
+
+    #[tokio::test]
+    async fn test_share_targets_registered_suppliers_and_invites_by_email() {
+        let state = Arc::new(AppState::new(test_pool().await));
+        let client = queries::create_user(&state.pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        queries::create_user(&state.pool, "fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+        let other = queries::create_user(&state.pool, "outro", "GOTHER", "SOTHER", "client").await.unwrap();
+        queries::upsert_notification_preferences(
+            &state.pool,
+            &NotificationPreferences {
+                user_id: other.id.clone(),
+                email: Some("Outro@Empresa.com".to_string()),
+                email_enabled: true,
+                language: "pt".to_string(),
+                email_events: vec![],
+                updated_at: None,
+            },
+        )
+        .await
+        .unwrap();
+        let process = queries::create_process(&state.pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+
+        let share = |username: Option<&str>, public_key: Option<&str>| {
+            share_process(
+                State(state.clone()),
+                ApiJson(ShareProcessRequest {
+                    process_id: process.id.clone(),
+                    supplier_username: username.map(str::to_string),
+                    supplier_public_key: public_key.map(str::to_string),
+                    client_username: "cliente".to_string(),
+                    expires_at: None,
+                }),
+            )
+        };
+
+        let ResponseJson(created) = share(Some("fornecedor"), None).await.unwrap().1;
+        assert_eq!(created.supplier_public_key, "GSUPPLIER");
+        assert!(matches!(share(None, None).await, Err(ApiError::BadRequest(ErrorCode::InvalidShareTarget))));
+        assert!(matches!(share(Some("fornecedor"), Some("GSUPPLIER")).await, Err(ApiError::BadRequest(ErrorCode::InvalidShareTarget))));
+        assert!(matches!(share(None, Some("GOTHER")).await, Err(ApiError::BadRequest(ErrorCode::NotASupplier))));
+        assert!(matches!(share(None, Some("GDESCONHECIDA")).await, Err(ApiError::NotFound(ErrorCode::UserNotFound))));
+
+        let invite = |email: &str| {
+            invite_supplier(
+                State(state.clone()),
+                ApiJson(InviteSupplierRequest {
+                    process_id: process.id.clone(),
+                    client_username: "cliente".to_string(),
+                    email: email.to_string(),
+                    expires_at: None,
+                }),
+            )
+        };
+
+        let (status, ResponseJson(created)) = invite(" Novo@Fornecedor.com ").await.unwrap();
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(created.invite.email, "novo@fornecedor.com");
+        assert_eq!(created.invite.status, "pending");
+        let stored = queries::find_pending_share_invite(&state.pool, &invites::hash_token(&created.token)).await.unwrap();
+        assert_eq!(stored.map(|i| i.id), Some(created.invite.id));
+
+        assert!(matches!(invite("novo@fornecedor.com").await, Err(ApiError::Conflict(ErrorCode::AlreadyInvited))));
+        assert!(matches!(invite("outro@empresa.com").await, Err(ApiError::Conflict(ErrorCode::SupplierRegistered))));
+        assert!(matches!(invite("não é e-mail").await, Err(ApiError::BadRequest(ErrorCode::InvalidEmail))));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_share_reports_per_supplier_results() {
+        let state = Arc::new(AppState::new(test_pool().await));
+        let client = queries::create_user(&state.pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        for (username, key) in [("fornecedor1", "GSUPPLIER1"), ("fornecedor2", "GSUPPLIER2"), ("fornecedor3", "GSUPPLIER3")] {
+            queries::create_user(&state.pool, username, key, "SSUPPLIER", "supplier").await.unwrap();
+        }
+        let process = queries::create_process(&state.pool, &client.id, "NDA RFQ", "xxx", "key").await.unwrap();
+        queries::create_process_share(&state.pool, &process.id, "GSUPPLIER3", None).await.unwrap();
+
+        let target = |username: Option<&str>, public_key: Option<&str>| ShareTarget {
+            supplier_username: username.map(str::to_string),
+            supplier_public_key: public_key.map(str::to_string),
+        };
+        let request = |suppliers: Vec<ShareTarget>| BulkShareRequest {
+            process_id: process.id.clone(),
+            client_username: "cliente".to_string(),
+            suppliers,
+            expires_at: None,
+        };
+
+        let (status, ResponseJson(response)) = bulk_share_process(
+            State(state.clone()),
+            ApiJson(request(vec![
+                target(Some("fornecedor1"), None),
+                target(None, Some("GSUPPLIER2")),
+                target(None, Some("GSUPPLIER1")), // repetido no pedido
+                target(Some("fornecedor3"), None), // já compartilhado
+                target(Some("cliente"), None),
+                target(Some("desconhecido"), None),
+                target(None, None),
+            ])),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(status, StatusCode::ACCEPTED);
+        assert_eq!((response.created, response.failed), (2, 5));
+        let errors: Vec<_> = response.results.iter().map(|r| r.error.as_deref()).collect();
+        assert_eq!(
+            errors,
+            [None, None, Some("already_shared"), Some("already_shared"), Some("not_a_supplier"), Some("user_not_found"), Some("invalid_share_target")]
+        );
+
+        // Os criados vão ao ledger no mesmo lote
+        let mut batches = Vec::new();
+        for result in &response.results[..2] {
+            let share = result.share.as_ref().unwrap();
+            assert_eq!(share.status, "pending");
+            let entry = queries::find_ledger_outbox_by_share(&state.pool, &share.id).await.unwrap().unwrap();
+            batches.push(entry.batch_id.expect("entrada em lote"));
+        }
+        assert_eq!(batches[0], batches[1]);
+
+        let empty = bulk_share_process(State(state), ApiJson(request(Vec::new()))).await;
+        assert!(matches!(empty, Err(ApiError::BadRequest(ErrorCode::InvalidBulkShare))));
+    }
+
+    #[tokio::test]
+    async fn test_login_unknown_user_is_unauthorized() {
+        let (state, _) = fake_state();
+        let result = login_user(
+            State(state),
+            ApiJson(LoginRequest { username: "ninguem".to_string(), password: String::new() }),
+        )
+        .await;
+        assert!(matches!(result, Err(ApiError::Unauthorized(ErrorCode::InvalidCredentials))));
+    }
+
+    #[tokio::test]
+    async fn test_notify_publishes_to_subscribers() {
+        let state = AppState::new(test_pool().await);
+        let client = queries::create_user(&state.pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let process = queries::create_process(&state.pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+        let mut rx = state.notifications_tx.subscribe();
+
+        notify(&state, &client.id, NotificationEvent::Accessed, &process.id, None, "acessou").await;
+
+        let notification = rx.try_recv().unwrap();
+        assert_eq!(notification.recipient_id, client.id);
+        assert_eq!(notification.event_type, NotificationEvent::Accessed);
+        assert_eq!(notification.process_title, "NDA Alpha");
+        assert!(!notification.is_read);
+    }
+
+    // Horizon falso respondendo na raiz, como o endpoint usado por test_connection
+    async fn fake_horizon() -> String {
+        let app = axum::Router::new().route(
+            "/",
+            axum::routing::get(|| async { ResponseJson(json!({ "network_passphrase": "Test SDF Network ; September 2015" })) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", address)
+    }
+
+    async fn state_with_horizon(horizon_url: String) -> Arc<AppState> {
+        let mut config = Config::default();
+        config.stellar.horizon_url = horizon_url;
+        Arc::new(AppState::new(test_pool().await).with_config(config))
+    }
+
+    #[tokio::test]
+    async fn test_readiness_checks_dependencies_and_shutdown() {
+        let state = state_with_horizon(fake_horizon().await).await;
+        let (status, ResponseJson(body)) = health_ready(State(state.clone())).await;
+        assert_eq!(status, StatusCode::OK, "{:?}", body);
+        assert_eq!(body.status, "ready");
+
+        // Em encerramento deixa de receber tráfego
+        state.shutdown.cancel();
+        let (status, ResponseJson(body)) = health_ready(State(state)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body.status, "shutting_down");
+
+        // Horizon inacessível
+        let state = state_with_horizon("http://127.0.0.1:9".to_string()).await;
+        let (status, ResponseJson(body)) = health_ready(State(state)).await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(body.database.ok && body.migrations.ok);
+        assert!(!body.horizon.ok);
+    }
+}
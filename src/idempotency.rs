@@ -0,0 +1,174 @@
+// src/idempotency.rs
+// Middleware de Idempotency-Key para endpoints que criam recursos
+use axum::{
+    body::{to_bytes, Body},
+    extract::{Request, State},
+    http::{header, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{Duration, Utc};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use crate::database::queries;
+use crate::error::{ApiError, ErrorCode};
+use crate::handlers::AppState;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+pub const REPLAYED_HEADER: &str = "idempotent-replayed";
+
+// Respostas ficam disponíveis para repetição por 24 horas
+const TTL_HOURS: i64 = 24;
+const MAX_KEY_LENGTH: usize = 255;
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Reproduz a resposta armazenada quando a mesma Idempotency-Key é reenviada.
+/// Apenas respostas 2xx são armazenadas; erros liberam a chave para nova tentativa.
+pub async fn idempotent(
+    State(state): State<Arc<AppState>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let key = match request.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        Some(value) => value
+            .to_str()
+            .ok()
+            .filter(|key| !key.is_empty() && key.len() <= MAX_KEY_LENGTH)
+            .ok_or(ApiError::BadRequest(ErrorCode::InvalidIdempotencyKey))?
+            .to_string(),
+        None => return Ok(next.run(request).await),
+    };
+
+    let scope = format!("{} {}", request.method(), request.uri().path());
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| ApiError::BadRequest(ErrorCode::InvalidBody))?;
+    let request_hash = hex::encode(Sha256::digest(&bytes));
+
+    // Chaves expiradas deixam de valer
+    queries::purge_idempotency_keys(&state.pool, Utc::now() - Duration::hours(TTL_HOURS)).await?;
+
+    if !queries::reserve_idempotency_key(&state.pool, &scope, &key, &request_hash).await? {
+        let record = queries::find_idempotency_record(&state.pool, &scope, &key)
+            .await?
+            .ok_or(ApiError::Conflict(ErrorCode::IdempotencyKeyInProgress))?;
+
+        if record.request_hash != request_hash {
+            return Err(ApiError::BadRequest(ErrorCode::IdempotencyKeyReused));
+        }
+
+        return match (record.status_code, record.response_body) {
+            (Some(status_code), Some(body)) => Ok(replay(status_code, body)),
+            _ => Err(ApiError::Conflict(ErrorCode::IdempotencyKeyInProgress)),
+        };
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+
+    if !response.status().is_success() {
+        queries::delete_idempotency_key(&state.pool, &scope, &key).await?;
+        return Ok(response);
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            queries::delete_idempotency_key(&state.pool, &scope, &key).await?;
+            return Err(ApiError::internal(e));
+        }
+    };
+
+    let body = String::from_utf8_lossy(&bytes).into_owned();
+    queries::complete_idempotency_key(&state.pool, &scope, &key, parts.status.as_u16() as i64, &body).await?;
+
+    Ok(Response::from_parts(parts, Body::from(bytes)))
+}
+
+fn replay(status_code: i64, body: String) -> Response {
+    let status = u16::try_from(status_code)
+        .ok()
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .unwrap_or(StatusCode::OK);
+
+    let mut response = (status, body).into_response();
+    response.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    response.headers_mut().insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_pool;
+    use axum::{middleware, routing::post, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tower::ServiceExt;
+
+    async fn app() -> (Router, Arc<AtomicUsize>) {
+        let state = Arc::new(AppState::new(test_pool().await));
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let router = Router::new()
+            .route(
+                "/recurso",
+                post(move |body: String| async move {
+                    let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                    if body == "falha" {
+                        return StatusCode::BAD_GATEWAY.into_response();
+                    }
+                    (StatusCode::ACCEPTED, format!("{{\"n\":{}}}", n)).into_response()
+                })
+                .layer(middleware::from_fn_with_state(state.clone(), idempotent)),
+            )
+            .with_state(state);
+        (router, calls)
+    }
+
+    fn request(key: &str, body: &str) -> Request {
+        Request::post("/recurso")
+            .header(IDEMPOTENCY_KEY_HEADER, key)
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    async fn body_of(response: Response) -> String {
+        String::from_utf8(to_bytes(response.into_body(), usize::MAX).await.unwrap().to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_repeated_key_replays_stored_response() {
+        let (app, calls) = app().await;
+
+        let first = app.clone().oneshot(request("k1", "{}")).await.unwrap();
+        assert_eq!(first.status(), StatusCode::ACCEPTED);
+        assert_eq!(body_of(first).await, "{\"n\":1}");
+
+        let replayed = app.clone().oneshot(request("k1", "{}")).await.unwrap();
+        assert_eq!(replayed.status(), StatusCode::ACCEPTED);
+        assert_eq!(replayed.headers()[REPLAYED_HEADER], "true");
+        assert_eq!(body_of(replayed).await, "{\"n\":1}");
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // Mesma chave com outro corpo é rejeitada
+        let reused = app.clone().oneshot(request("k1", "{\"x\":1}")).await.unwrap();
+        assert_eq!(reused.status(), StatusCode::BAD_REQUEST);
+
+        // Outra chave executa de novo
+        let other = app.oneshot(request("k2", "{}")).await.unwrap();
+        assert_eq!(body_of(other).await, "{\"n\":2}");
+    }
+
+    #[tokio::test]
+    async fn test_failed_response_releases_key() {
+        let (app, calls) = app().await;
+
+        let failed = app.clone().oneshot(request("k1", "falha")).await.unwrap();
+        assert_eq!(failed.status(), StatusCode::BAD_GATEWAY);
+        let retried = app.oneshot(request("k1", "falha")).await.unwrap();
+        assert_eq!(retried.status(), StatusCode::BAD_GATEWAY);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,57 @@
+// src/invites.rs
+// Convites por e-mail para fornecedores ainda não cadastrados
+use chrono::Duration;
+use rand::Rng;
+use sha2::{Digest, Sha256};
+
+use crate::notifier::EmailMessage;
+
+/// Validade do convite; depois disso o cliente precisa convidar de novo
+pub fn invite_validity() -> Duration {
+    Duration::days(14)
+}
+
+/// Token entregue ao fornecedor; o banco guarda apenas o hash
+pub fn generate_token() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    format!("inv_{}", hex::encode(bytes))
+}
+
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// E-mails são comparados sem diferenciar maiúsculas
+pub fn normalize_email(email: &str) -> String {
+    email.trim().to_lowercase()
+}
+
+pub fn render_invite_email(email: &str, client_username: &str, process_title: &str, token: &str) -> EmailMessage {
+    EmailMessage {
+        to: email.to_string(),
+        subject: format!("Convite para acessar o NDA \"{}\"", process_title),
+        body: format!(
+            "{} compartilhou o NDA \"{}\" com você.\n\n\
+             Cadastre-se como fornecedor informando o token de convite abaixo (campo invite_token):\n\n{}\n\n\
+             O convite expira em {} dias.",
+            client_username,
+            process_title,
+            token,
+            invite_validity().num_days(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_is_random_and_hashed() {
+        let token = generate_token();
+        assert!(token.starts_with("inv_"));
+        assert_ne!(token, generate_token());
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_ne!(hash_token(&token), token);
+    }
+}
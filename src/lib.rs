@@ -1,6 +1,23 @@
 // src/lib.rs
+pub mod config;
 pub mod stellar_real;
 pub mod models;
 pub mod handlers;
 pub mod database;
-pub mod crypto;
\ No newline at end of file
+pub mod repository;
+pub mod crypto;
+pub mod webhooks;
+pub mod outbox;
+pub mod notifier;
+pub mod error;
+pub mod idempotency;
+pub mod security;
+pub mod rate_limit;
+pub mod metrics;
+pub mod telemetry;
+pub mod openapi;
+pub mod routes;
+pub mod admin;
+pub mod pagination;
+pub mod search;
+pub mod invites;
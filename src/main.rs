@@ -1,49 +1,121 @@
 // src/main.rs
-use axum::{
-    routing::{get, post},
-    Router,
-};
-use std::sync::Arc;
-use tower_http::cors::CorsLayer;
-use tracing_subscriber;
-
-mod models;
-mod handlers;
-mod database;
-mod crypto;
-mod stellar_real;
-
-use handlers::AppState;
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+use tokio_util::sync::CancellationToken;
+
+use nda_backend::{database, notifier, outbox, routes, security, telemetry, webhooks};
+use nda_backend::config::Config;
+use nda_backend::stellar_real::StellarClient;
+use nda_backend::handlers::AppState;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Inicializar logging
-    tracing_subscriber::fmt::init();
+    // Carregar configuração (arquivo + ambiente)
+    let config = Config::load()?;
+
+    // Logs estruturados (e spans via OTLP, com a feature "otlp")
+    let telemetry = telemetry::init(&config)?;
+    tracing::info!("configuração:\n{}", config);
 
     // Conectar ao banco
-    let pool = database::init_database().await?;
+    let pool = database::init_database(&config).await?;
 
     // Estado da aplicação
-    let state = Arc::new(AppState { pool });
-
-    // Configurar rotas
-    let app = Router::new()
-        .route("/health", get(handlers::health_check))
-        .route("/api/users/register", post(handlers::register_user))
-        .route("/api/users/login", post(handlers::login_user))
-        .route("/api/processes", post(handlers::create_process))
-        .route("/api/processes", get(handlers::list_processes))
-        .route("/api/processes/share", post(handlers::share_process))
-        .route("/api/processes/access", post(handlers::access_process))
-        .route("/api/notifications", get(handlers::get_notifications))
-        .layer(CorsLayer::permissive())
-        .with_state(state);
-
-    // Iniciar servidor
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
-    println!("🚀 Servidor rodando em http://localhost:3000");
-    
-    axum::serve(listener, app).await?;
+    let mut app_state = AppState::new(pool).with_config(config.clone());
+    if let Some(smtp) = &config.smtp {
+        let smtp = notifier::SmtpNotifier::from_config(smtp).map_err(|e| e.to_string())?;
+        tracing::info!("notificações por e-mail via SMTP ativadas");
+        app_state = app_state.with_notifier(Arc::new(smtp));
+    }
+    let state = Arc::new(app_state);
+    let shutdown = state.shutdown.clone();
+
+    // Workers em segundo plano: entrega de webhooks e registro dos compartilhamentos no ledger
+    let workers = vec![
+        webhooks::spawn_worker(state.pool.clone(), shutdown.clone()),
+        outbox::spawn_worker(state.clone(), Arc::new(StellarClient::from_config(&config.stellar))),
+    ];
+
+    // Rotas, documentação e middlewares
+    let app = routes::router(state);
+
+    // SIGTERM/Ctrl+C: para de aceitar conexões e aguarda as requisições em andamento
+    tokio::spawn(wait_for_signal(shutdown.clone()));
+    let grace = Duration::from_secs(config.shutdown_timeout_secs);
+
+    // Iniciar servidor (HTTPS nativo quando houver certificado configurado)
+    match &config.tls {
+        Some(tls) => {
+            let rustls_config = security::load_tls(tls).await?;
+            let handle = axum_server::Handle::new();
+            let signal = shutdown.clone();
+            let server_handle = handle.clone();
+            tokio::spawn(async move {
+                signal.cancelled().await;
+                server_handle.graceful_shutdown(Some(grace));
+            });
+
+            tracing::info!("servidor rodando em https://{}", config.bind_address);
+            axum_server::bind_rustls(config.bind_address, rustls_config)
+                .handle(handle)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await?;
+        }
+        None => {
+            let listener = tokio::net::TcpListener::bind(config.bind_address).await?;
+            tracing::info!("servidor rodando em http://{}", config.bind_address);
+            let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+                .with_graceful_shutdown(shutdown.clone().cancelled_owned());
 
+            // Conexões longas (WebSocket, SSE) não seguram o encerramento além do prazo
+            tokio::select! {
+                result = server => result?,
+                _ = async { shutdown.cancelled().await; tokio::time::sleep(grace).await } => {
+                    tracing::warn!("prazo de encerramento esgotado, fechando conexões restantes");
+                }
+            }
+        }
+    }
+
+    // Workers concluem o lote em andamento antes de sair
+    let drain = async {
+        for worker in workers {
+            let _ = worker.await;
+        }
+    };
+    if tokio::time::timeout(grace, drain).await.is_err() {
+        tracing::warn!("workers não terminaram dentro do prazo de encerramento");
+    }
+
+    tracing::info!("servidor encerrado");
+    telemetry.shutdown();
     Ok(())
+}
+
+async fn wait_for_signal(shutdown: CancellationToken) {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            }
+            Err(e) => {
+                tracing::warn!(error = %e, "não foi possível escutar SIGTERM");
+                std::future::pending::<()>().await
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+
+    tracing::info!("sinal de encerramento recebido, concluindo requisições em andamento");
+    shutdown.cancel();
 }
\ No newline at end of file
@@ -0,0 +1,210 @@
+// src/metrics.rs
+// Métricas Prometheus: requisições HTTP, pool do banco, chamadas ao Stellar, compartilhamentos, acessos e criptografia
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+use std::{
+    sync::{Arc, OnceLock},
+    time::Instant,
+};
+
+use crate::error::ApiError;
+use crate::handlers::AppState;
+
+// Chamadas externas (Horizon, friendbot) levam de milissegundos a vários segundos
+const STELLAR_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 20.0, 30.0];
+// AES-GCM em conteúdos de NDA: microssegundos a poucos milissegundos
+const CRYPTO_BUCKETS: [f64; 8] = [0.00001, 0.00005, 0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05];
+
+pub struct Metrics {
+    registry: Registry,
+    http_requests: IntCounterVec,
+    http_duration: HistogramVec,
+    db_pool_connections: IntGauge,
+    db_pool_idle: IntGauge,
+    stellar_duration: HistogramVec,
+    stellar_failures: IntCounterVec,
+    shares_created: IntCounter,
+    accesses: IntCounterVec,
+    crypto_duration: HistogramVec,
+}
+
+impl Metrics {
+    fn new() -> Result<Self, prometheus::Error> {
+        let registry = Registry::new_custom(Some("nda".to_string()), None)?;
+
+        let http_requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "Requisições HTTP por rota, método e status"),
+            &["method", "route", "status"],
+        )?;
+        let http_duration = HistogramVec::new(
+            HistogramOpts::new("http_request_duration_seconds", "Duração das requisições HTTP por rota"),
+            &["method", "route"],
+        )?;
+        let db_pool_connections = IntGauge::new("db_pool_connections", "Conexões abertas no pool do banco")?;
+        let db_pool_idle = IntGauge::new("db_pool_idle_connections", "Conexões ociosas no pool do banco")?;
+        let stellar_duration = HistogramVec::new(
+            HistogramOpts::new("stellar_request_duration_seconds", "Duração das chamadas ao Horizon/friendbot por endpoint")
+                .buckets(STELLAR_BUCKETS.to_vec()),
+            &["endpoint"],
+        )?;
+        let stellar_failures = IntCounterVec::new(
+            Opts::new("stellar_request_failures_total", "Chamadas ao Horizon/friendbot com falha por endpoint"),
+            &["endpoint", "kind"],
+        )?;
+        let shares_created = IntCounter::new("shares_created_total", "Compartilhamentos criados")?;
+        let accesses = IntCounterVec::new(
+            Opts::new("process_accesses_total", "Tentativas de acesso a processos por resultado"),
+            &["result"],
+        )?;
+        let crypto_duration = HistogramVec::new(
+            HistogramOpts::new("crypto_operation_duration_seconds", "Duração das operações de criptografia")
+                .buckets(CRYPTO_BUCKETS.to_vec()),
+            &["operation"],
+        )?;
+
+        registry.register(Box::new(http_requests.clone()))?;
+        registry.register(Box::new(http_duration.clone()))?;
+        registry.register(Box::new(db_pool_connections.clone()))?;
+        registry.register(Box::new(db_pool_idle.clone()))?;
+        registry.register(Box::new(stellar_duration.clone()))?;
+        registry.register(Box::new(stellar_failures.clone()))?;
+        registry.register(Box::new(shares_created.clone()))?;
+        registry.register(Box::new(accesses.clone()))?;
+        registry.register(Box::new(crypto_duration.clone()))?;
+
+        Ok(Self {
+            registry,
+            http_requests,
+            http_duration,
+            db_pool_connections,
+            db_pool_idle,
+            stellar_duration,
+            stellar_failures,
+            shares_created,
+            accesses,
+            crypto_duration,
+        })
+    }
+
+    /// Registra uma chamada ao Stellar; `failure` indica o tipo de falha (ex.: "network", "http_404")
+    pub fn observe_stellar(&self, endpoint: &str, started: Instant, failure: Option<&str>) {
+        self.stellar_duration
+            .with_label_values(&[endpoint])
+            .observe(started.elapsed().as_secs_f64());
+        if let Some(kind) = failure {
+            self.stellar_failures.with_label_values(&[endpoint, kind]).inc();
+        }
+    }
+
+    pub fn share_created(&self) {
+        self.shares_created.inc();
+    }
+
+    /// Resultado de uma tentativa de acesso: granted ou denied
+    pub fn access(&self, result: &str) {
+        self.accesses.with_label_values(&[result]).inc();
+    }
+
+    /// Mede a operação até o timer sair de escopo
+    pub fn crypto_timer(&self, operation: &str) -> HistogramTimer {
+        self.crypto_duration.with_label_values(&[operation]).start_timer()
+    }
+
+    fn render(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        String::from_utf8(buffer).map_err(|e| prometheus::Error::Msg(e.to_string()))
+    }
+}
+
+/// Registro global: StellarClient e as funções de criptografia não carregam estado da aplicação
+pub fn metrics() -> &'static Metrics {
+    static METRICS: OnceLock<Metrics> = OnceLock::new();
+    METRICS.get_or_init(|| Metrics::new().expect("métricas com nomes e labels válidos"))
+}
+
+/// Middleware: conta e mede as requisições pela rota (padrão, não o caminho concreto)
+pub async fn track_requests(request: Request, next: Next) -> Response {
+    // Rotas não encontradas ficam agrupadas para não explodir a cardinalidade
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = request.method().to_string();
+    let started = Instant::now();
+
+    let response = next.run(request).await;
+
+    let metrics = metrics();
+    metrics
+        .http_duration
+        .with_label_values(&[&method, &route])
+        .observe(started.elapsed().as_secs_f64());
+    metrics
+        .http_requests
+        .with_label_values(&[&method, &route, response.status().as_str()])
+        .inc();
+
+    response
+}
+
+/// GET /metrics no formato texto do Prometheus
+#[utoipa::path(
+    get, path = "/metrics", tag = "health",
+    responses((status = 200, description = "Métricas no formato texto do Prometheus", body = String, content_type = "text/plain"))
+)]
+pub async fn metrics_handler(State(state): State<Arc<AppState>>) -> Result<Response, ApiError> {
+    let metrics = metrics();
+    metrics.db_pool_connections.set(state.pool.size() as i64);
+    metrics.db_pool_idle.set(state.pool.num_idle() as i64);
+
+    let body = metrics.render().map_err(ApiError::internal)?;
+    let mut response = body.into_response();
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/plain; version=0.0.4; charset=utf-8"),
+    );
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_pool;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_exports_route_templates() {
+        let state = Arc::new(AppState::new(test_pool().await));
+        let app = Router::new()
+            .route("/api/itens/:id", get(|| async { "ok" }))
+            .route("/metrics", get(metrics_handler))
+            .layer(middleware::from_fn(track_requests))
+            .with_state(state);
+
+        app.clone().oneshot(Request::get("/api/itens/42").body(Body::empty()).unwrap()).await.unwrap();
+        metrics().share_created();
+        drop(metrics().crypto_timer("encrypt"));
+
+        let response = app.oneshot(Request::get("/metrics").body(Body::empty()).unwrap()).await.unwrap();
+        assert!(response.headers()[header::CONTENT_TYPE].to_str().unwrap().starts_with("text/plain"));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+
+        assert!(body.contains(r#"nda_http_requests_total{method="GET",route="/api/itens/:id",status="200"}"#));
+        assert!(!body.contains("/api/itens/42"));
+        assert!(body.contains("nda_db_pool_connections"));
+        assert!(body.contains("nda_shares_created_total"));
+        assert!(body.contains(r#"nda_crypto_operation_duration_seconds_count{operation="encrypt"}"#));
+    }
+}
@@ -1,8 +1,13 @@
-use serde::{Deserialize, Serialize};
+// Tipos da API definidos em nda-models, compartilhados com o nda-client
+pub use nda_models::*;
+
 use chrono::{DateTime, Utc};
-use sqlx::FromRow;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+// Linhas do banco usadas só pelo servidor; ficam fora do nda-models por guardarem
+// chaves e conteúdo que não fazem parte da API
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct User {
     pub id: String,
     pub username: String,
@@ -10,9 +15,12 @@ pub struct User {
     pub stellar_secret_key: String, // Em produção, usar KMS
     pub user_type: String, // "client" ou "supplier"
     pub created_at: DateTime<Utc>,
+    pub locked_at: Option<DateTime<Utc>>, // bloqueado pela administração (nda-admin)
 }
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+nda_models::debug_redacted!(User { id, username, stellar_public_key, user_type, created_at, locked_at; stellar_secret_key });
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Process {
     pub id: String,
     pub client_id: String,
@@ -23,16 +31,9 @@ pub struct Process {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
-pub struct ProcessShare {
-    pub id: String,
-    pub process_id: String,
-    pub supplier_public_key: String,
-    pub stellar_transaction_hash: String,
-    pub shared_at: DateTime<Utc>,
-}
+nda_models::debug_redacted!(Process { id, client_id, title, status, created_at; encrypted_content, encryption_key });
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessAccess {
     pub id: String,
     pub process_id: String,
@@ -40,7 +41,7 @@ pub struct ProcessAccess {
     pub accessed_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ProcessAccessWithDetails {
     pub id: String,
     pub process_id: String,
@@ -50,47 +51,29 @@ pub struct ProcessAccessWithDetails {
     pub supplier_username: String,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct RegisterRequest {
-    pub username: String,
-    pub password: String,
-    pub user_type: String,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct LoginRequest {
-    pub username: String,
-    pub password: String,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct CreateProcessRequest {
-    pub title: String,
-    pub confidential_content: String,
-    pub client_username: String,  // ← Adicionar este campo
-}
-
-#[derive(Debug, Deserialize)]
-pub struct ShareProcessRequest {
-    pub process_id: String,
-    pub supplier_public_key: String,
-    pub client_username: String,
-}
-
-#[derive(Debug, Deserialize)]
-pub struct AccessProcessRequest {
-    pub process_id: String,
-    pub supplier_public_key: String,
-    pub supplier_username: String,
+// Pedido de registro de um compartilhamento no ledger (outbox)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LedgerOutboxEntry {
+    pub id: String,
+    pub share_id: String,
+    pub status: String, // "pending", "done" ou "failed"
+    pub attempts: i64,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+    pub batch_id: Option<String>, // lote registrado numa única transação
+    pub submission_key: Option<String>, // memo do envio ao ledger, gravado antes de enviar
 }
 
-// Response models para API
-#[derive(Debug, Serialize)]
-pub struct UserResponse {
-    pub id: String,
-    pub username: String,
-    pub stellar_public_key: String,
-    pub user_type: String,
+// Resposta armazenada para uma Idempotency-Key
+#[derive(Debug, Clone)]
+pub struct IdempotencyRecord {
+    pub scope: String,
+    pub idempotency_key: String,
+    pub request_hash: String,
+    pub status_code: Option<i64>, // None enquanto a requisição original está em andamento
+    pub response_body: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -106,29 +89,25 @@ impl From<User> for UserResponse {
     }
 }
 
-#[derive(Debug, Serialize)]
-pub struct ProcessResponse {
-    pub id: String,
-    pub title: String,
-    pub status: String,
-    pub created_at: DateTime<Utc>,
+// Resposta montada a partir da linha interna do processo
+pub trait ProcessResponseExt {
+    fn new(process: Process, metadata: ProcessMetadata) -> Self;
 }
 
-impl From<Process> for ProcessResponse {
-    fn from(process: Process) -> Self {
+impl ProcessResponseExt for ProcessResponse {
+    fn new(process: Process, metadata: ProcessMetadata) -> Self {
         ProcessResponse {
             id: process.id,
             title: process.title,
             status: process.status,
             created_at: process.created_at,
+            metadata,
         }
     }
 }
 
-#[derive(Debug, Serialize)]
-pub struct ProcessAccessResponse {
-    pub process_id: String,
-    pub title: String,
-    pub content: String,
-    pub accessed_at: DateTime<Utc>,
-}
\ No newline at end of file
+impl From<Process> for ProcessResponse {
+    fn from(process: Process) -> Self {
+        ProcessResponse::new(process, ProcessMetadata::default())
+    }
+}
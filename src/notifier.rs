@@ -0,0 +1,316 @@
+// src/notifier.rs
+use async_trait::async_trait;
+use lettre::{
+    message::{header::ContentType, Mailbox},
+    transport::smtp::authentication::Credentials,
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+};
+use std::error::Error;
+
+use crate::config::SmtpConfig;
+use crate::database::{queries, DbPool};
+use crate::models::{Notification, NotificationEvent, NotificationPreferences, User};
+
+pub type NotifierError = Box<dyn Error + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Canal de envio de e-mails; implementações trocáveis (SMTP, log, testes)
+#[async_trait]
+pub trait Notifier: Send + Sync {
+    async fn send(&self, message: &EmailMessage) -> Result<(), NotifierError>;
+}
+
+/// Envio via SMTP usando lettre
+pub struct SmtpNotifier {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpNotifier {
+    pub fn new(transport: AsyncSmtpTransport<Tokio1Executor>, from: &str) -> Result<Self, NotifierError> {
+        Ok(Self {
+            transport,
+            from: from.parse()?,
+        })
+    }
+
+    /// Configura a partir da seção smtp da configuração (SMTP_HOST, SMTP_PORT, ...)
+    pub fn from_config(config: &SmtpConfig) -> Result<Self, NotifierError> {
+        let mut builder = match config.tls.as_str() {
+            "tls" => AsyncSmtpTransport::<Tokio1Executor>::relay(&config.host)?,
+            "starttls" => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.host)?,
+            "none" => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host),
+            other => return Err(format!("SMTP_TLS inválido: {}", other).into()),
+        };
+
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            builder = builder.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        Self::new(builder.build(), &config.from)
+    }
+}
+
+#[async_trait]
+impl Notifier for SmtpNotifier {
+    async fn send(&self, message: &EmailMessage) -> Result<(), NotifierError> {
+        let email = Message::builder()
+            .from(self.from.clone())
+            .to(message.to.parse()?)
+            .subject(&message.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(message.body.clone())?;
+
+        self.transport.send(email).await?;
+        Ok(())
+    }
+}
+
+/// Apenas registra no log; usado quando SMTP não está configurado
+pub struct LogNotifier;
+
+#[async_trait]
+impl Notifier for LogNotifier {
+    async fn send(&self, message: &EmailMessage) -> Result<(), NotifierError> {
+        tracing::info!(to = %message.to, subject = %message.subject, "e-mail não enviado: SMTP não configurado");
+        Ok(())
+    }
+}
+
+pub const SUPPORTED_LANGUAGES: [&str; 2] = ["pt", "en"];
+
+/// Preferências usadas quando o usuário nunca configurou as suas:
+/// e-mail para o próprio username quando ele for um endereço válido
+pub fn default_preferences(user: &User) -> NotificationPreferences {
+    let email = user.username.parse::<Mailbox>().ok().map(|_| user.username.clone());
+
+    NotificationPreferences {
+        user_id: user.id.clone(),
+        email_enabled: email.is_some(),
+        email,
+        language: "pt".to_string(),
+        email_events: vec![NotificationEvent::Accessed, NotificationEvent::Granted],
+        updated_at: None,
+    }
+}
+
+pub fn is_valid_email(email: &str) -> bool {
+    email.parse::<Mailbox>().is_ok()
+}
+
+/// Assunto e corpo do e-mail no idioma escolhido ("pt" como padrão)
+pub fn render_email(notification: &Notification, language: &str) -> (String, String) {
+    let title = &notification.process_title;
+    let actor = notification.actor_username.as_deref().unwrap_or("-");
+
+    let (subject, intro) = match (language, notification.event_type) {
+        ("en", NotificationEvent::Accessed) => (
+            format!("Supplier accessed \"{}\"", title),
+            format!("Supplier {} opened the confidential content of \"{}\".", actor, title),
+        ),
+        ("en", NotificationEvent::Granted) => (
+            format!("You were granted access to \"{}\"", title),
+            format!("{} shared the NDA \"{}\" with you.", actor, title),
+        ),
+        ("en", NotificationEvent::Accepted) => (
+            format!("NDA \"{}\" accepted", title),
+            format!("Supplier {} accepted the NDA \"{}\".", actor, title),
+        ),
+        ("en", NotificationEvent::ShareExpired) => (
+            format!("Access to \"{}\" expired", title),
+            format!("The share of \"{}\" has expired.", title),
+        ),
+        ("en", NotificationEvent::Revoked) => (
+            format!("Access to \"{}\" revoked", title),
+            format!("Access to \"{}\" was revoked.", title),
+        ),
+        ("en", NotificationEvent::Denied) => (
+            format!("Access denied on \"{}\"", title),
+            format!("{} tried to access \"{}\" without authorization.", actor, title),
+        ),
+        (_, NotificationEvent::Accessed) => (
+            format!("Fornecedor acessou \"{}\"", title),
+            format!("O fornecedor {} abriu o conteúdo confidencial de \"{}\".", actor, title),
+        ),
+        (_, NotificationEvent::Granted) => (
+            format!("Você recebeu acesso a \"{}\"", title),
+            format!("{} compartilhou o NDA \"{}\" com você.", actor, title),
+        ),
+        (_, NotificationEvent::Accepted) => (
+            format!("NDA \"{}\" aceito", title),
+            format!("O fornecedor {} aceitou o NDA \"{}\".", actor, title),
+        ),
+        (_, NotificationEvent::ShareExpired) => (
+            format!("Acesso a \"{}\" expirado", title),
+            format!("O compartilhamento de \"{}\" expirou.", title),
+        ),
+        (_, NotificationEvent::Revoked) => (
+            format!("Acesso a \"{}\" revogado", title),
+            format!("O acesso a \"{}\" foi revogado.", title),
+        ),
+        (_, NotificationEvent::Denied) => (
+            format!("Acesso negado em \"{}\"", title),
+            format!("{} tentou acessar \"{}\" sem autorização.", actor, title),
+        ),
+    };
+
+    let footer = match language {
+        "en" => "You received this e-mail because notifications are enabled in your NDA Blockchain preferences.",
+        _ => "Você recebeu este e-mail porque as notificações estão ativas nas suas preferências do NDA Blockchain.",
+    };
+
+    let body = format!(
+        "{}\n\n{}: {}\n{}: {}\n\n{}\n",
+        intro,
+        if language == "en" { "Process" } else { "Processo" },
+        notification.process_id,
+        if language == "en" { "Date" } else { "Data" },
+        notification.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        footer,
+    );
+
+    (subject, body)
+}
+
+/// Envia a notificação por e-mail se as preferências do destinatário permitirem.
+/// Retorna true quando um e-mail foi enviado.
+pub async fn deliver_email(
+    pool: &DbPool,
+    notifier: &dyn Notifier,
+    notification: &Notification,
+) -> Result<bool, NotifierError> {
+    let recipient = match queries::find_user_by_id(pool, &notification.recipient_id).await? {
+        Some(user) => user,
+        None => return Ok(false),
+    };
+
+    let preferences = match queries::find_notification_preferences(pool, &recipient.id).await? {
+        Some(preferences) => preferences,
+        None => default_preferences(&recipient),
+    };
+
+    let email = match preferences.email {
+        Some(email) if preferences.email_enabled => email,
+        _ => return Ok(false),
+    };
+    if !preferences.email_events.contains(&notification.event_type) {
+        return Ok(false);
+    }
+
+    let (subject, body) = render_email(notification, &preferences.language);
+    notifier.send(&EmailMessage { to: email, subject, body }).await?;
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_pool;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    // Servidor SMTP mínimo que aceita e guarda as mensagens recebidas
+    async fn spawn_smtp_sink() -> (u16, Arc<Mutex<Vec<String>>>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let received: Arc<Mutex<Vec<String>>> = Arc::default();
+        let sink = received.clone();
+
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let sink = sink.clone();
+                tokio::spawn(async move {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut lines = BufReader::new(reader).lines();
+                    writer.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+
+                    let mut data: Option<String> = None;
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        if let Some(body) = data.as_mut() {
+                            if line == "." {
+                                sink.lock().unwrap().push(data.take().unwrap());
+                                writer.write_all(b"250 OK\r\n").await.unwrap();
+                            } else {
+                                body.push_str(&line);
+                                body.push('\n');
+                            }
+                            continue;
+                        }
+
+                        let command = line.to_uppercase();
+                        let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                            b"250 sink\r\n"
+                        } else if command.starts_with("DATA") {
+                            data = Some(String::new());
+                            b"354 End data with <CR><LF>.<CR><LF>\r\n"
+                        } else if command.starts_with("QUIT") {
+                            writer.write_all(b"221 Bye\r\n").await.unwrap();
+                            break;
+                        } else {
+                            b"250 OK\r\n"
+                        };
+                        writer.write_all(reply).await.unwrap();
+                    }
+                });
+            }
+        });
+
+        (port, received)
+    }
+
+    #[tokio::test]
+    async fn test_smtp_delivery_respects_preferences() {
+        let pool = test_pool().await;
+        let (port, received) = spawn_smtp_sink().await;
+        let transport = AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous("127.0.0.1")
+            .port(port)
+            .build();
+        let notifier = SmtpNotifier::new(transport, "NDA <no-reply@nda.test>").unwrap();
+
+        let client = queries::create_user(&pool, "cliente@empresa.com", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let supplier = queries::create_user(&pool, "fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+        let process = queries::create_process(&pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+
+        let id = queries::create_notification(
+            &pool, &client.id, NotificationEvent::Accessed, &process.id, Some(&supplier.id), "acessou",
+        ).await.unwrap();
+        let notification = queries::find_notification_by_id(&pool, &id).await.unwrap().unwrap();
+
+        // Padrão: username é um e-mail, idioma português
+        assert!(deliver_email(&pool, &notifier, &notification).await.unwrap());
+        {
+            let messages = received.lock().unwrap();
+            assert_eq!(messages.len(), 1);
+            assert!(messages[0].contains("To: cliente@empresa.com"));
+            assert!(messages[0].contains("Fornecedor acessou"));
+        }
+
+        // Inglês e outro endereço
+        let mut preferences = default_preferences(&client);
+        preferences.email = Some("compliance@empresa.com".to_string());
+        preferences.language = "en".to_string();
+        queries::upsert_notification_preferences(&pool, &preferences).await.unwrap();
+        assert!(deliver_email(&pool, &notifier, &notification).await.unwrap());
+        {
+            let messages = received.lock().unwrap();
+            assert!(messages[1].contains("To: compliance@empresa.com"));
+            assert!(messages[1].contains("Supplier accessed"));
+        }
+
+        // Desativado
+        preferences.email_enabled = false;
+        queries::upsert_notification_preferences(&pool, &preferences).await.unwrap();
+        assert!(!deliver_email(&pool, &notifier, &notification).await.unwrap());
+        assert_eq!(received.lock().unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,104 @@
+// src/openapi.rs
+// Especificação OpenAPI 3 gerada a partir dos handlers e dos tipos de models.rs
+use axum::Json;
+use utoipa::OpenApi;
+
+use crate::error::{ErrorBody, ErrorDetail};
+use crate::handlers;
+use crate::metrics;
+use crate::models::*;
+use crate::pagination::{ProcessSort, ReadStatus, SortOrder};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "NDA Backend",
+        description = "Compartilhamento de NDAs criptografados com registro de acessos na rede Stellar"
+    ),
+    paths(
+        handlers::health_check,
+        handlers::health_live,
+        handlers::health_ready,
+        metrics::metrics_handler,
+        handlers::register_user,
+        handlers::login_user,
+        handlers::get_preferences,
+        handlers::update_preferences,
+        handlers::create_process,
+        handlers::list_processes,
+        handlers::search_processes,
+        handlers::update_process_metadata,
+        handlers::share_process,
+        handlers::bulk_share_process,
+        handlers::invite_supplier,
+        handlers::access_process,
+        handlers::list_supplier_processes,
+        handlers::accept_share,
+        handlers::get_notifications,
+        handlers::notifications_ws,
+        handlers::notifications_sse,
+        handlers::mark_all_notifications_read,
+        handlers::mark_notification_read,
+        handlers::create_webhook,
+        handlers::list_webhooks,
+        handlers::delete_webhook,
+        handlers::list_webhook_deliveries,
+    ),
+    components(schemas(
+        ErrorBody,
+        ErrorDetail,
+        HealthCheck,
+        ReadinessResponse,
+        RegisterRequest,
+        LoginRequest,
+        UserResponse,
+        NotificationPreferences,
+        UpdatePreferencesRequest,
+        CreateProcessRequest,
+        ShareProcessRequest,
+        ShareTarget,
+        BulkShareRequest,
+        BulkShareResult,
+        BulkShareResponse,
+        InviteSupplierRequest,
+        ShareInvite,
+        CreateShareInviteResponse,
+        AccessProcessRequest,
+        ProcessResponse,
+        ProcessMetadata,
+        UpdateProcessMetadataRequest,
+        ProcessPage,
+        ProcessSearchHit,
+        ProcessSearchPage,
+        ProcessSort,
+        SortOrder,
+        ReadStatus,
+        ProcessShare,
+        ProcessAccessResponse,
+        AcceptShareRequest,
+        SupplierProcess,
+        SupplierProcessPage,
+        NotificationEvent,
+        Notification,
+        NotificationPage,
+        MarkNotificationsReadRequest,
+        CreateWebhookRequest,
+        CreateWebhookResponse,
+        WebhookEndpoint,
+        WebhookDelivery,
+    )),
+    tags(
+        (name = "health", description = "Disponibilidade e métricas"),
+        (name = "users", description = "Cadastro, login e preferências"),
+        (name = "processes", description = "Processos de NDA, compartilhamento e acesso"),
+        (name = "supplier", description = "Visão do fornecedor: processos compartilhados e aceite do NDA"),
+        (name = "notifications", description = "Notificações (lista, WebSocket e SSE)"),
+        (name = "webhooks", description = "Webhooks de eventos dos processos"),
+    )
+)]
+pub struct ApiDoc;
+
+/// GET /openapi.json
+pub async fn openapi_json() -> Json<utoipa::openapi::OpenApi> {
+    Json(ApiDoc::openapi())
+}
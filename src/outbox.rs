@@ -0,0 +1,571 @@
+// src/outbox.rs
+// Worker do outbox: registra no ledger os compartilhamentos criados como 'pending'
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use std::collections::HashSet;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::database::queries;
+use crate::handlers::{emit_webhook, notify, AppState};
+use crate::models::{LedgerOutboxEntry, NotificationEvent, Process, ProcessShare, User};
+use crate::stellar_real::StellarClient;
+use crate::webhooks::{backoff_delay, WebhookEvent};
+
+// Tentativas antes de marcar o compartilhamento como "failed"
+pub const MAX_ATTEMPTS: i64 = 6;
+// Prazo da reserva de uma entrada; se o worker cair, ela volta para a fila
+const LEASE_SECS: i64 = 120;
+const POLL_INTERVAL_SECS: u64 = 2;
+const BATCH_SIZE: i64 = 20;
+
+/// Submissão de compartilhamentos ao ledger; retorna o hash da transação.
+/// Deve ser idempotente: reenviar o mesmo compartilhamento não pode gerar uma segunda transação.
+/// O memo de cada envio é a chave de submissão, gravada no outbox antes de enviar; numa nova
+/// tentativa o worker consulta find_submission e só reenvia se nada foi registrado com ela.
+#[async_trait]
+pub trait Ledger: Send + Sync {
+    async fn submit_share(
+        &self,
+        source_secret: &str,
+        destination_public: &str,
+        process_id: &str,
+        memo: &str,
+    ) -> Result<String, String>;
+
+    /// Vários fornecedores do mesmo processo numa única transação
+    async fn submit_share_batch(
+        &self,
+        source_secret: &str,
+        destinations: &[String],
+        process_id: &str,
+        memo: &str,
+    ) -> Result<String, String>;
+
+    /// Hash da transação já registrada pela conta de origem com esse memo, se houver
+    async fn find_submission(&self, source_secret: &str, memo: &str) -> Result<Option<String>, String>;
+}
+
+#[async_trait]
+impl Ledger for StellarClient {
+    async fn submit_share(
+        &self,
+        source_secret: &str,
+        destination_public: &str,
+        process_id: &str,
+        memo: &str,
+    ) -> Result<String, String> {
+        self.share_process_transaction(source_secret, destination_public, process_id, memo)
+            .await
+            .map(|tx| tx.hash)
+            .map_err(|e| e.to_string())
+    }
+
+    async fn submit_share_batch(
+        &self,
+        source_secret: &str,
+        destinations: &[String],
+        process_id: &str,
+        memo: &str,
+    ) -> Result<String, String> {
+        self.share_process_batch_transaction(source_secret, destinations, process_id, memo)
+            .await
+            .map(|tx| tx.hash)
+            .map_err(|e| e.to_string())
+    }
+
+    // No MVP as transações são simuladas e não chegam ao Horizon, então a consulta não as encontra
+    // e o reenvio repete o mesmo hash simulado; com envio real, é ela que impede a duplicata
+    async fn find_submission(&self, source_secret: &str, memo: &str) -> Result<Option<String>, String> {
+        let source_public = StellarClient::get_public_from_secret(source_secret).map_err(|e| e.to_string())?;
+        self.find_transaction_by_memo(&source_public, memo)
+            .await
+            .map(|tx| tx.map(|tx| tx.hash))
+            .map_err(|e| e.to_string())
+    }
+}
+
+// Memo do envio: identifica o processo (verify_process_access procura por ele) e a tentativa de registro
+fn new_submission_key(process_id: &str) -> String {
+    format!("NDA_SHARE:{}:{}", process_id, &Uuid::new_v4().simple().to_string()[..8])
+}
+
+// Envia no máximo uma vez por chave de submissão. A chave é gravada antes do envio; se já existe,
+// um envio anterior pode ter sido registrado sem que a confirmação chegasse ao banco (queda do
+// worker, falha ao confirmar), então o ledger é consultado antes de reenviar.
+async fn submit_once(
+    state: &AppState,
+    ledger: &dyn Ledger,
+    client: &User,
+    process: &Process,
+    entries: &[&LedgerOutboxEntry],
+    destinations: &[String],
+) -> Result<Result<String, String>, sqlx::Error> {
+    let submission_key = match entries.iter().find_map(|entry| entry.submission_key.clone()) {
+        Some(submission_key) => {
+            match ledger.find_submission(&client.stellar_secret_key, &submission_key).await {
+                Ok(Some(hash)) => {
+                    tracing::info!(transaction_hash = %hash, "envio anterior já registrado no ledger, sem reenvio");
+                    return Ok(Ok(hash));
+                }
+                Ok(None) => submission_key,
+                Err(error) => return Ok(Err(error)),
+            }
+        }
+        None => {
+            let submission_key = new_submission_key(&process.id);
+            queries::set_ledger_outbox_submission_key(&state.pool, entries, &submission_key).await?;
+            submission_key
+        }
+    };
+
+    let result = match destinations {
+        [destination] if entries.iter().all(|entry| entry.batch_id.is_none()) => {
+            ledger.submit_share(&client.stellar_secret_key, destination, &process.id, &submission_key).await
+        }
+        _ => ledger.submit_share_batch(&client.stellar_secret_key, destinations, &process.id, &submission_key).await,
+    };
+    Ok(result)
+}
+
+/// Processa um lote de entradas pendentes cujo horário já chegou
+pub async fn dispatch_due(state: &AppState, ledger: &dyn Ledger) -> Result<usize, sqlx::Error> {
+    let entries = queries::list_due_ledger_outbox(&state.pool, BATCH_SIZE).await?;
+    let mut processed = 0;
+    let mut batches = HashSet::new();
+
+    for entry in entries {
+        // Entradas de um lote vão juntas, mesmo além do limite desta rodada
+        if let Some(batch_id) = &entry.batch_id {
+            if batches.insert(batch_id.clone()) {
+                processed += dispatch_batch(state, ledger, batch_id).await?;
+            }
+            continue;
+        }
+
+        if !queries::claim_ledger_outbox(&state.pool, &entry, Utc::now() + Duration::seconds(LEASE_SECS)).await? {
+            continue; // outro worker ficou com a entrada
+        }
+        submit(state, ledger, &entry).await?;
+        processed += 1;
+    }
+
+    Ok(processed)
+}
+
+async fn dispatch_batch(state: &AppState, ledger: &dyn Ledger, batch_id: &str) -> Result<usize, sqlx::Error> {
+    // O lote é reservado como unidade; se parte dele ainda está reservada, fica para depois
+    let lease_until = Utc::now() + Duration::seconds(LEASE_SECS);
+    let claimed = queries::claim_ledger_outbox_batch(&state.pool, batch_id, lease_until).await?;
+
+    if !claimed.is_empty() {
+        submit_batch(state, ledger, batch_id, &claimed).await?;
+    }
+    Ok(claimed.len())
+}
+
+#[tracing::instrument(name = "ledger_submit", skip_all, fields(share_id = %entry.share_id, process_id = tracing::field::Empty))]
+async fn submit(state: &AppState, ledger: &dyn Ledger, entry: &LedgerOutboxEntry) -> Result<(), sqlx::Error> {
+    let share = match queries::find_process_share_by_id(&state.pool, &entry.share_id).await? {
+        Some(share) => share,
+        None => return queries::record_ledger_outbox_failure(&state.pool, entry, "Compartilhamento removido", None).await,
+    };
+    let process = match state.processes.find_by_id(&share.process_id).await? {
+        Some(process) => process,
+        None => return queries::record_ledger_outbox_failure(&state.pool, entry, "Processo removido", None).await,
+    };
+    crate::telemetry::record_process(&process.id);
+    let client = match state.users.find_by_id(&process.client_id).await? {
+        Some(client) => client,
+        None => return queries::record_ledger_outbox_failure(&state.pool, entry, "Cliente removido", None).await,
+    };
+
+    let destination = std::slice::from_ref(&share.supplier_public_key);
+    let result = submit_once(state, ledger, &client, &process, &[entry], destination).await?;
+
+    match result {
+        Ok(hash) => confirm(state, entry, &share, &process, &client, &hash).await,
+        Err(error) => record_failure(state, entry, &error).await,
+    }
+}
+
+// Uma transação para todo o lote: sucesso confirma todos, falha reagenda todos
+#[tracing::instrument(name = "ledger_submit_batch", skip_all, fields(batch_id = %batch_id, shares = entries.len(), process_id = tracing::field::Empty))]
+async fn submit_batch(
+    state: &AppState,
+    ledger: &dyn Ledger,
+    batch_id: &str,
+    entries: &[LedgerOutboxEntry],
+) -> Result<(), sqlx::Error> {
+    let mut shares = Vec::with_capacity(entries.len());
+    for entry in entries {
+        match queries::find_process_share_by_id(&state.pool, &entry.share_id).await? {
+            Some(share) => shares.push((entry, share)),
+            None => queries::record_ledger_outbox_failure(&state.pool, entry, "Compartilhamento removido", None).await?,
+        }
+    }
+    // Todo lote pertence a um único processo (POST /api/processes/share/bulk)
+    let Some((_, share)) = shares.first() else {
+        return Ok(());
+    };
+
+    let process = match state.processes.find_by_id(&share.process_id).await? {
+        Some(process) => process,
+        None => return fail_all(state, &shares, "Processo removido").await,
+    };
+    crate::telemetry::record_process(&process.id);
+    let client = match state.users.find_by_id(&process.client_id).await? {
+        Some(client) => client,
+        None => return fail_all(state, &shares, "Cliente removido").await,
+    };
+
+    let destinations: Vec<String> = shares.iter().map(|(_, share)| share.supplier_public_key.clone()).collect();
+    let pending: Vec<&LedgerOutboxEntry> = shares.iter().map(|(entry, _)| *entry).collect();
+    let result = submit_once(state, ledger, &client, &process, &pending, &destinations).await?;
+
+    match result {
+        Ok(hash) => {
+            tracing::info!(transaction_hash = %hash, "lote registrado no ledger");
+            let confirmed = queries::confirm_ledger_outbox_batch(&state.pool, &pending, &hash).await?;
+            for ((_, share), confirmed) in shares.iter().zip(confirmed) {
+                if confirmed {
+                    announce(state, share, &process, &client, &hash).await;
+                } else {
+                    tracing::info!(share_id = %share.id, transaction_hash = %hash, "compartilhamento revogado antes da confirmação no ledger");
+                }
+            }
+        }
+        Err(error) => {
+            for (entry, _) in &shares {
+                record_failure(state, entry, &error).await?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn fail_all(state: &AppState, shares: &[(&LedgerOutboxEntry, ProcessShare)], error: &str) -> Result<(), sqlx::Error> {
+    for (entry, _) in shares {
+        queries::record_ledger_outbox_failure(&state.pool, entry, error, None).await?;
+    }
+    Ok(())
+}
+
+// Reagenda com backoff ou, esgotadas as tentativas, marca como falha definitiva
+async fn record_failure(state: &AppState, entry: &LedgerOutboxEntry, error: &str) -> Result<(), sqlx::Error> {
+    let attempts = entry.attempts + 1;
+    if attempts >= MAX_ATTEMPTS {
+        tracing::error!(share_id = %entry.share_id, error = %error, "compartilhamento falhou definitivamente no ledger");
+        queries::record_ledger_outbox_failure(&state.pool, entry, error, None).await
+    } else {
+        let next_attempt_at = Utc::now() + backoff_delay(attempts);
+        tracing::warn!(share_id = %entry.share_id, error = %error, next_attempt_at = %next_attempt_at, "ledger falhou, nova tentativa agendada");
+        queries::record_ledger_outbox_failure(&state.pool, entry, error, Some(next_attempt_at)).await
+    }
+}
+
+// Confirma o compartilhamento e avisa cliente e fornecedor
+async fn confirm(
+    state: &AppState,
+    entry: &LedgerOutboxEntry,
+    share: &ProcessShare,
+    process: &Process,
+    client: &User,
+    hash: &str,
+) -> Result<(), sqlx::Error> {
+    if !queries::confirm_ledger_outbox(&state.pool, entry, hash).await? {
+        tracing::info!(share_id = %share.id, transaction_hash = %hash, "compartilhamento revogado antes da confirmação no ledger");
+        return Ok(());
+    }
+    announce(state, share, process, client, hash).await;
+    Ok(())
+}
+
+// Avisa cliente (webhook) e fornecedor (notificação) do compartilhamento confirmado
+async fn announce(state: &AppState, share: &ProcessShare, process: &Process, client: &User, hash: &str) {
+    tracing::info!(share_id = %share.id, transaction_hash = %hash, "compartilhamento confirmado no ledger");
+
+    emit_webhook(
+        state,
+        &client.id,
+        WebhookEvent::ShareCreated,
+        json!({
+            "share_id": share.id,
+            "process_id": share.process_id,
+            "supplier_public_key": share.supplier_public_key,
+            "stellar_transaction_hash": hash,
+            "shared_at": share.shared_at,
+        }),
+    )
+    .await;
+
+    // Notificar fornecedor, se já estiver registrado
+    if let Ok(Some(supplier)) = state.users.find_by_public_key(&share.supplier_public_key).await {
+        notify(
+            state,
+            &supplier.id,
+            NotificationEvent::Granted,
+            &process.id,
+            Some(&client.id),
+            &format!("Você recebeu acesso ao processo '{}' de {}", process.title, client.username),
+        )
+        .await;
+    }
+}
+
+/// Inicia o worker que submete os compartilhamentos pendentes em segundo plano
+pub fn spawn_worker(state: Arc<AppState>, ledger: Arc<dyn Ledger>) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(POLL_INTERVAL_SECS));
+
+        // O lote em andamento termina antes de o worker parar
+        loop {
+            tokio::select! {
+                _ = state.shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+            if let Err(e) = dispatch_due(&state, ledger.as_ref()).await {
+                tracing::error!(error = %e, "erro no worker do ledger");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_pool;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    // Ledger falso que falha nas primeiras chamadas e guarda os envios registrados por memo
+    struct FlakyLedger {
+        failures_left: Mutex<u32>,
+        calls: Mutex<u32>,
+        batch_calls: Mutex<u32>,
+        recorded: Mutex<HashMap<String, String>>,
+    }
+
+    impl FlakyLedger {
+        fn new(failures: u32) -> Self {
+            Self {
+                failures_left: Mutex::new(failures),
+                calls: Mutex::new(0),
+                batch_calls: Mutex::new(0),
+                recorded: Mutex::new(HashMap::new()),
+            }
+        }
+
+        fn record(&self, memo: &str, hash: String) -> Result<String, String> {
+            let mut failures_left = self.failures_left.lock().unwrap();
+            if *failures_left > 0 {
+                *failures_left -= 1;
+                return Err("horizon timeout".to_string());
+            }
+            self.recorded.lock().unwrap().insert(memo.to_string(), hash.clone());
+            Ok(hash)
+        }
+    }
+
+    #[async_trait]
+    impl Ledger for FlakyLedger {
+        async fn submit_share(&self, _: &str, destination: &str, process_id: &str, memo: &str) -> Result<String, String> {
+            *self.calls.lock().unwrap() += 1;
+            self.record(memo, format!("hash-{}-{}", process_id, destination))
+        }
+
+        async fn submit_share_batch(&self, _: &str, destinations: &[String], process_id: &str, memo: &str) -> Result<String, String> {
+            *self.batch_calls.lock().unwrap() += 1;
+            self.record(memo, format!("hash-{}-{}", process_id, destinations.join("+")))
+        }
+
+        async fn find_submission(&self, _: &str, memo: &str) -> Result<Option<String>, String> {
+            Ok(self.recorded.lock().unwrap().get(memo).cloned())
+        }
+    }
+
+    // Simula a queda do worker entre o envio e a confirmação no banco
+    async fn fail_confirmations(state: &AppState) {
+        sqlx::query(
+            "CREATE TRIGGER fail_confirm BEFORE UPDATE ON ledger_outbox WHEN NEW.status = 'done' \
+             BEGIN SELECT RAISE(ABORT, 'falha simulada'); END",
+        )
+        .execute(&state.pool)
+        .await
+        .unwrap();
+    }
+
+    async fn restore_confirmations(state: &AppState) {
+        sqlx::query("DROP TRIGGER fail_confirm").execute(&state.pool).await.unwrap();
+    }
+
+    // Faz a reserva das entradas (ou só de uma) vencer
+    async fn expire_leases(state: &AppState, entry_id: Option<&str>) {
+        sqlx::query("UPDATE ledger_outbox SET next_attempt_at = $1 WHERE $2 IS NULL OR id = $2")
+            .bind(crate::database::datetime_to_string(&Utc::now()))
+            .bind(entry_id)
+            .execute(&state.pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_share_is_confirmed_after_retry() {
+        let state = AppState::new(test_pool().await);
+        let ledger = FlakyLedger::new(1);
+        let client = queries::create_user(&state.pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let supplier = queries::create_user(&state.pool, "fornecedor", "GSUPPLIER", "SSUPPLIER", "supplier").await.unwrap();
+        let process = queries::create_process(&state.pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+        let share = state.shares.create(&process.id, "GSUPPLIER", None).await.unwrap();
+        let mut rx = state.notifications_tx.subscribe();
+
+        // Primeira tentativa falha e é reagendada para o futuro
+        assert_eq!(dispatch_due(&state, &ledger).await.unwrap(), 1);
+        assert_eq!(dispatch_due(&state, &ledger).await.unwrap(), 0);
+        let entry = queries::find_ledger_outbox_by_share(&state.pool, &share.id).await.unwrap().unwrap();
+        assert_eq!(entry.attempts, 1);
+        assert_eq!(entry.last_error.as_deref(), Some("horizon timeout"));
+        assert_eq!(queries::find_process_share_by_id(&state.pool, &share.id).await.unwrap().unwrap().status, "pending");
+
+        // Antecipa a nova tentativa
+        sqlx::query("UPDATE ledger_outbox SET next_attempt_at = $1 WHERE id = $2")
+            .bind(crate::database::datetime_to_string(&Utc::now()))
+            .bind(&entry.id)
+            .execute(&state.pool)
+            .await
+            .unwrap();
+        assert_eq!(dispatch_due(&state, &ledger).await.unwrap(), 1);
+
+        let confirmed = queries::find_process_share_by_id(&state.pool, &share.id).await.unwrap().unwrap();
+        assert_eq!(confirmed.status, "confirmed");
+        assert_eq!(confirmed.stellar_transaction_hash, Some(format!("hash-{}-GSUPPLIER", process.id)));
+        assert_eq!(*ledger.calls.lock().unwrap(), 2);
+
+        // Fornecedor é notificado só após a confirmação
+        let notification = rx.try_recv().unwrap();
+        assert_eq!(notification.recipient_id, supplier.id);
+        assert_eq!(notification.event_type, NotificationEvent::Granted);
+    }
+
+    #[tokio::test]
+    async fn test_share_is_not_resubmitted_after_confirm_failure() {
+        let state = AppState::new(test_pool().await);
+        let ledger = FlakyLedger::new(0);
+        let client = queries::create_user(&state.pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let process = queries::create_process(&state.pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+        let share = state.shares.create(&process.id, "GSUPPLIER", None).await.unwrap();
+
+        // Envio registrado no ledger, mas a confirmação não chega ao banco
+        fail_confirmations(&state).await;
+        assert!(dispatch_due(&state, &ledger).await.is_err());
+        assert_eq!(*ledger.calls.lock().unwrap(), 1);
+        let entry = queries::find_ledger_outbox_by_share(&state.pool, &share.id).await.unwrap().unwrap();
+        assert!(entry.submission_key.as_deref().is_some_and(|key| key.starts_with(&format!("NDA_SHARE:{}:", process.id))));
+
+        // A reserva vence e outro ciclo retoma a entrada: confirma sem reenviar
+        restore_confirmations(&state).await;
+        expire_leases(&state, None).await;
+        assert_eq!(dispatch_due(&state, &ledger).await.unwrap(), 1);
+        assert_eq!(*ledger.calls.lock().unwrap(), 1);
+
+        let confirmed = queries::find_process_share_by_id(&state.pool, &share.id).await.unwrap().unwrap();
+        assert_eq!(confirmed.status, "confirmed");
+        assert_eq!(confirmed.stellar_transaction_hash, Some(format!("hash-{}-GSUPPLIER", process.id)));
+    }
+
+    #[tokio::test]
+    async fn test_bulk_shares_are_submitted_per_batch() {
+        let state = AppState::new(test_pool().await);
+        let ledger = FlakyLedger::new(1);
+        let client = queries::create_user(&state.pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let process = queries::create_process(&state.pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+        let mut keys = Vec::new();
+        for n in 1..=3 {
+            let key = format!("GSUPPLIER{}", n);
+            queries::create_user(&state.pool, &format!("fornecedor{}", n), &key, "SSUPPLIER", "supplier").await.unwrap();
+            keys.push(key);
+        }
+
+        // Lotes de até dois fornecedores: [1, 2] e [3]
+        let shares = queries::create_process_shares_batch(&state.pool, &process.id, &keys, None, 2).await.unwrap();
+        let batch_of = |share_id: String| {
+            let pool = state.pool.clone();
+            async move { queries::find_ledger_outbox_by_share(&pool, &share_id).await.unwrap().unwrap() }
+        };
+        let first = batch_of(shares[0].id.clone()).await;
+        assert_eq!(first.batch_id, batch_of(shares[1].id.clone()).await.batch_id);
+        assert_ne!(first.batch_id, batch_of(shares[2].id.clone()).await.batch_id);
+
+        // Uma transação por lote: a primeira falha e reagenda o lote inteiro
+        assert_eq!(dispatch_due(&state, &ledger).await.unwrap(), 3);
+        assert_eq!(*ledger.batch_calls.lock().unwrap(), 2);
+        assert_eq!(*ledger.calls.lock().unwrap(), 0);
+
+        let mut statuses = Vec::new();
+        for share in &shares {
+            let stored = queries::find_process_share_by_id(&state.pool, &share.id).await.unwrap().unwrap();
+            statuses.push(stored.status);
+        }
+        assert_eq!(statuses, ["pending", "pending", "confirmed"]);
+        let retried = batch_of(shares[1].id.clone()).await;
+        assert_eq!(retried.attempts, 1);
+        assert_eq!(retried.last_error.as_deref(), Some("horizon timeout"));
+
+        let confirmed = queries::find_process_share_by_id(&state.pool, &shares[2].id).await.unwrap().unwrap();
+        assert_eq!(confirmed.stellar_transaction_hash, Some(format!("hash-{}-GSUPPLIER3", process.id)));
+    }
+
+    #[tokio::test]
+    async fn test_batch_is_claimed_as_a_unit() {
+        let state = AppState::new(test_pool().await);
+        let ledger = FlakyLedger::new(0);
+        let client = queries::create_user(&state.pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let process = queries::create_process(&state.pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+        let keys = vec!["GSUPPLIER1".to_string(), "GSUPPLIER2".to_string()];
+        let shares = queries::create_process_shares_batch(&state.pool, &process.id, &keys, None, 100).await.unwrap();
+
+        // Lote registrado no ledger, mas a confirmação não chega ao banco
+        fail_confirmations(&state).await;
+        assert!(dispatch_due(&state, &ledger).await.is_err());
+        assert_eq!(*ledger.batch_calls.lock().unwrap(), 1);
+        restore_confirmations(&state).await;
+
+        // Só uma das entradas tem a reserva vencida: o lote não é retomado pela metade
+        let first = queries::find_ledger_outbox_by_share(&state.pool, &shares[0].id).await.unwrap().unwrap();
+        expire_leases(&state, Some(&first.id)).await;
+        assert_eq!(dispatch_due(&state, &ledger).await.unwrap(), 0);
+        assert_eq!(*ledger.batch_calls.lock().unwrap(), 1);
+
+        // Com o lote inteiro disponível, a chave já registrada confirma todos sem reenvio
+        expire_leases(&state, None).await;
+        assert_eq!(dispatch_due(&state, &ledger).await.unwrap(), 2);
+        assert_eq!(*ledger.batch_calls.lock().unwrap(), 1);
+        for share in &shares {
+            let stored = queries::find_process_share_by_id(&state.pool, &share.id).await.unwrap().unwrap();
+            assert_eq!(stored.status, "confirmed");
+            assert_eq!(stored.stellar_transaction_hash, Some(format!("hash-{}-GSUPPLIER1+GSUPPLIER2", process.id)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_share_fails_after_max_attempts() {
+        let state = AppState::new(test_pool().await);
+        let ledger = FlakyLedger::new(u32::MAX);
+        let client = queries::create_user(&state.pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let process = queries::create_process(&state.pool, &client.id, "NDA Alpha", "xxx", "key").await.unwrap();
+        let share = state.shares.create(&process.id, "GSUPPLIER", None).await.unwrap();
+
+        sqlx::query("UPDATE ledger_outbox SET attempts = $1")
+            .bind(MAX_ATTEMPTS - 1)
+            .execute(&state.pool)
+            .await
+            .unwrap();
+        dispatch_due(&state, &ledger).await.unwrap();
+
+        let entry = queries::find_ledger_outbox_by_share(&state.pool, &share.id).await.unwrap().unwrap();
+        assert_eq!(entry.status, "failed");
+        assert_eq!(queries::find_process_share_by_id(&state.pool, &share.id).await.unwrap().unwrap().status, "failed");
+    }
+}
@@ -0,0 +1,268 @@
+// src/pagination.rs
+// Paginação por cursor, filtros e ordenação das listagens de processos e notificações
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::database::datetime_to_string;
+pub use crate::models::{ProcessSort, ReadStatus, SortOrder};
+use crate::models::{Notification, NotificationEvent, Process, SupplierProcess};
+
+pub const DEFAULT_PAGE_SIZE: u32 = 20;
+pub const MAX_PAGE_SIZE: u32 = 100;
+
+// Operadores SQL das ordenações; os enums ficam em nda-models, compartilhados com o cliente
+pub trait SortOrderExt {
+    fn as_sql(&self) -> &'static str;
+    // Operador do keyset: itens posteriores ao cursor na ordem escolhida
+    fn after_operator(&self) -> &'static str;
+    // Mesma regra para as implementações em memória
+    fn is_after(&self, key: (&str, &str), cursor: &Cursor) -> bool;
+}
+
+impl SortOrderExt for SortOrder {
+    fn as_sql(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+
+    fn after_operator(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => ">",
+            SortOrder::Desc => "<",
+        }
+    }
+
+    fn is_after(&self, key: (&str, &str), cursor: &Cursor) -> bool {
+        let cursor_key = (cursor.value.as_str(), cursor.id.as_str());
+        match self {
+            SortOrder::Asc => key > cursor_key,
+            SortOrder::Desc => key < cursor_key,
+        }
+    }
+}
+
+pub trait ProcessSortExt {
+    fn column(&self) -> &'static str;
+}
+
+impl ProcessSortExt for ProcessSort {
+    fn column(&self) -> &'static str {
+        match self {
+            ProcessSort::CreatedAt => "p.created_at",
+            ProcessSort::Title => "p.title",
+        }
+    }
+}
+
+// Posição após o último item entregue. Guarda a ordenação que o gerou:
+// um cursor não pode ser reaproveitado com outro sort/order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    pub sort: String,
+    pub value: String,
+    pub id: String,
+}
+
+impl Cursor {
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(encoded: &str, sort: &str) -> Option<Cursor> {
+        let bytes = URL_SAFE_NO_PAD.decode(encoded).ok()?;
+        let cursor: Cursor = serde_json::from_slice(&bytes).ok()?;
+        (cursor.sort == sort).then_some(cursor)
+    }
+}
+
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: i64,
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    // `items` traz até limit + 1 linhas: a excedente indica que há próxima página
+    pub fn from_items(mut items: Vec<T>, limit: u32, total: i64, cursor_after: impl Fn(&T) -> String) -> Self {
+        let next_cursor = if items.len() > limit as usize {
+            items.truncate(limit as usize);
+            items.last().map(cursor_after)
+        } else {
+            None
+        };
+        Page { items, total, next_cursor }
+    }
+}
+
+// Busca por título sem diferenciar maiúsculas; % e _ digitados são literais
+pub fn like_pattern(search: &str) -> String {
+    let escaped = search
+        .to_lowercase()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+pub fn matches_search(text: &str, search: &str) -> bool {
+    text.to_lowercase().contains(&search.to_lowercase())
+}
+
+pub fn clamp_page_size(per_page: Option<u32>) -> u32 {
+    per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+#[derive(Debug, Clone)]
+pub struct ProcessListOptions {
+    pub status: Option<String>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    pub search: Option<String>,
+    // Username ou chave pública de um fornecedor com compartilhamento ativo
+    pub supplier: Option<String>,
+    pub tag: Option<String>,
+    pub category: Option<String>,
+    pub project_code: Option<String>,
+    pub sort: ProcessSort,
+    pub order: SortOrder,
+    pub after: Option<Cursor>,
+    pub limit: u32,
+}
+
+impl Default for ProcessListOptions {
+    fn default() -> Self {
+        Self {
+            status: None,
+            created_from: None,
+            created_to: None,
+            search: None,
+            supplier: None,
+            tag: None,
+            category: None,
+            project_code: None,
+            sort: ProcessSort::default(),
+            order: SortOrder::default(),
+            after: None,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl ProcessListOptions {
+    pub fn sort_key(&self) -> String {
+        format!("{}:{}", self.sort.as_str(), self.order.as_str())
+    }
+
+    pub fn sort_value(&self, process: &Process) -> String {
+        match self.sort {
+            ProcessSort::CreatedAt => datetime_to_string(&process.created_at),
+            ProcessSort::Title => process.title.clone(),
+        }
+    }
+
+    pub fn cursor_after(&self, process: &Process) -> String {
+        Cursor { sort: self.sort_key(), value: self.sort_value(process), id: process.id.clone() }.encode()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NotificationListOptions {
+    pub status: Option<ReadStatus>,
+    pub event: Option<NotificationEvent>,
+    pub created_from: Option<DateTime<Utc>>,
+    pub created_to: Option<DateTime<Utc>>,
+    // Busca no título do processo
+    pub search: Option<String>,
+    // Username do fornecedor que originou a notificação
+    pub supplier: Option<String>,
+    pub order: SortOrder,
+    pub after: Option<Cursor>,
+    // Paginação por página (compatibilidade); ignorada quando há cursor
+    pub offset: i64,
+    pub limit: u32,
+}
+
+impl Default for NotificationListOptions {
+    fn default() -> Self {
+        Self {
+            status: None,
+            event: None,
+            created_from: None,
+            created_to: None,
+            search: None,
+            supplier: None,
+            order: SortOrder::default(),
+            after: None,
+            offset: 0,
+            limit: DEFAULT_PAGE_SIZE,
+        }
+    }
+}
+
+impl NotificationListOptions {
+    pub fn sort_key(&self) -> String {
+        format!("created_at:{}", self.order.as_str())
+    }
+
+    pub fn cursor_after(&self, notification: &Notification) -> String {
+        Cursor {
+            sort: self.sort_key(),
+            value: datetime_to_string(&notification.created_at),
+            id: notification.id.clone(),
+        }
+        .encode()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SupplierShareListOptions {
+    // pending, confirmed, revoked ou expired
+    pub status: Option<String>,
+    pub after: Option<Cursor>,
+    pub limit: u32,
+}
+
+impl Default for SupplierShareListOptions {
+    fn default() -> Self {
+        Self { status: None, after: None, limit: DEFAULT_PAGE_SIZE }
+    }
+}
+
+impl SupplierShareListOptions {
+    // Ordem fixa: compartilhados mais recentemente primeiro
+    pub fn sort_key(&self) -> String {
+        "shared_at:desc".to_string()
+    }
+
+    pub fn cursor_after(&self, process: &SupplierProcess) -> String {
+        Cursor {
+            sort: self.sort_key(),
+            value: datetime_to_string(&process.shared_at),
+            id: process.share_id.clone(),
+        }
+        .encode()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_is_bound_to_sort() {
+        let cursor = Cursor { sort: "title:asc".to_string(), value: "NDA".to_string(), id: "p1".to_string() };
+        let encoded = cursor.encode();
+
+        assert_eq!(Cursor::decode(&encoded, "title:asc"), Some(cursor));
+        assert_eq!(Cursor::decode(&encoded, "title:desc"), None);
+        assert_eq!(Cursor::decode("não é base64", "title:asc"), None);
+    }
+
+    #[test]
+    fn test_like_pattern_escapes_wildcards() {
+        assert_eq!(like_pattern("NDA 100%_"), "%nda 100\\%\\_%");
+    }
+}
@@ -0,0 +1,287 @@
+// src/rate_limit.rs
+// Limite de requisições por IP e por usuário (token bucket) e bloqueio após logins malsucedidos
+use axum::{
+    body::{to_bytes, Body},
+    extract::{ConnectInfo, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::config::{RateLimitConfig, RouteLimit};
+use crate::error::{ApiError, ErrorCode};
+
+// Corpo lido apenas para extrair o usuário; requisições maiores são rejeitadas
+const MAX_BODY_BYTES: usize = 64 * 1024;
+// Acima deste número de chaves, baldes já recompletos são descartados
+const PRUNE_THRESHOLD: usize = 10_000;
+
+/// Rotas com limite próprio
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Route {
+    Login,
+    Register,
+    Access,
+    Share,
+}
+
+impl Route {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Route::Login => "login",
+            Route::Register => "register",
+            Route::Access => "access",
+            Route::Share => "share",
+        }
+    }
+
+    // Campo do corpo JSON que identifica o usuário da requisição
+    fn user_field(&self) -> &'static str {
+        match self {
+            Route::Login | Route::Register => "username",
+            Route::Access => "supplier_username",
+            Route::Share => "client_username",
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+struct LoginFailures {
+    count: u32,
+    locked_until: Option<Instant>,
+}
+
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    login_failures: Mutex<HashMap<String, LoginFailures>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+            login_failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn limit(&self, route: Route) -> &RouteLimit {
+        match route {
+            Route::Login => &self.config.login,
+            Route::Register => &self.config.register,
+            Route::Access => &self.config.access,
+            Route::Share => &self.config.share,
+        }
+    }
+
+    /// Consome um token do balde; em caso de excesso retorna o tempo até o próximo token
+    fn take(&self, key: String, per_minute: u32, burst: u32, now: Instant) -> Result<(), Duration> {
+        let rate = per_minute as f64 / 60.0;
+        let capacity = burst as f64;
+        let mut buckets = self.buckets.lock().unwrap();
+
+        if buckets.len() > PRUNE_THRESHOLD {
+            // Só é seguro descartar baldes que já voltaram a ficar cheios
+            buckets.retain(|_, bucket| bucket.tokens + now.duration_since(bucket.updated_at).as_secs_f64() * rate < capacity);
+        }
+
+        let bucket = buckets.entry(key).or_insert(Bucket { tokens: capacity, updated_at: now });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+        bucket.updated_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - bucket.tokens) / rate))
+        }
+    }
+
+    /// Verifica os limites por IP e por usuário da rota
+    pub fn check(&self, route: Route, ip: &str, user: Option<&str>, now: Instant) -> Result<(), Duration> {
+        let limit = *self.limit(route);
+        self.take(format!("{}:ip:{}", route.as_str(), ip), limit.ip_per_minute, limit.ip_burst, now)?;
+
+        if let (Some(user), Some(per_minute), Some(burst)) = (user, limit.user_per_minute, limit.user_burst) {
+            self.take(format!("{}:user:{}", route.as_str(), user), per_minute, burst, now)?;
+        }
+        Ok(())
+    }
+
+    /// Tempo restante de bloqueio do usuário, se houver
+    pub fn locked_for(&self, username: &str, now: Instant) -> Option<Duration> {
+        let failures = self.login_failures.lock().unwrap();
+        failures
+            .get(username)
+            .and_then(|f| f.locked_until)
+            .filter(|until| *until > now)
+            .map(|until| until - now)
+    }
+
+    /// Registra um login malsucedido; ao atingir o limite, bloqueia o usuário
+    pub fn record_login_failure(&self, username: &str, now: Instant) {
+        let mut failures = self.login_failures.lock().unwrap();
+        let entry = failures
+            .entry(username.to_string())
+            .or_insert(LoginFailures { count: 0, locked_until: None });
+
+        // Bloqueio expirado: recomeça a contagem
+        if entry.locked_until.is_some_and(|until| until <= now) {
+            entry.count = 0;
+            entry.locked_until = None;
+        }
+
+        entry.count += 1;
+        if entry.count >= self.config.lockout_threshold {
+            entry.locked_until = Some(now + Duration::from_secs(self.config.lockout_minutes * 60));
+            tracing::warn!(username, failures = entry.count, "usuário bloqueado após logins malsucedidos");
+        }
+    }
+
+    pub fn record_login_success(&self, username: &str) {
+        self.login_failures.lock().unwrap().remove(username);
+    }
+}
+
+/// Estado do middleware: limitador compartilhado + rota protegida
+#[derive(Clone)]
+pub struct RouteLimiter {
+    pub limiter: Arc<RateLimiter>,
+    pub route: Route,
+}
+
+fn client_ip(request: &Request) -> String {
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn retry_after_secs(wait: Duration) -> u64 {
+    wait.as_secs_f64().ceil() as u64
+}
+
+/// Aplica os limites da rota; excesso ou usuário bloqueado resultam em 429 com Retry-After
+pub async fn rate_limit(
+    State(RouteLimiter { limiter, route }): State<RouteLimiter>,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    if !limiter.config.enabled {
+        return Ok(next.run(request).await);
+    }
+
+    let ip = client_ip(&request);
+    let (parts, body) = request.into_parts();
+    let bytes = to_bytes(body, MAX_BODY_BYTES)
+        .await
+        .map_err(|_| ApiError::BadRequest(ErrorCode::InvalidBody))?;
+    let user = serde_json::from_slice::<serde_json::Value>(&bytes)
+        .ok()
+        .and_then(|body| body.get(route.user_field())?.as_str().map(|s| s.to_string()));
+
+    let now = Instant::now();
+    if route == Route::Login {
+        if let Some(wait) = user.as_deref().and_then(|user| limiter.locked_for(user, now)) {
+            return Err(ApiError::RateLimited(retry_after_secs(wait)));
+        }
+    }
+    if let Err(wait) = limiter.check(route, &ip, user.as_deref(), now) {
+        tracing::warn!(route = route.as_str(), ip = %ip, "limite de requisições atingido");
+        return Err(ApiError::RateLimited(retry_after_secs(wait)));
+    }
+
+    let response = next.run(Request::from_parts(parts, Body::from(bytes))).await;
+
+    if let (Route::Login, Some(user)) = (route, user.as_deref()) {
+        if response.status() == StatusCode::UNAUTHORIZED {
+            limiter.record_login_failure(user, Instant::now());
+        } else if response.status().is_success() {
+            limiter.record_login_success(user);
+        }
+    }
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::header, middleware, routing::post, Router};
+    use tower::ServiceExt;
+
+    fn limiter() -> Arc<RateLimiter> {
+        Arc::new(RateLimiter::new(RateLimitConfig {
+            login: RouteLimit { ip_per_minute: 60, ip_burst: 100, user_per_minute: Some(60), user_burst: Some(100) },
+            access: RouteLimit { ip_per_minute: 60, ip_burst: 2, user_per_minute: None, user_burst: None },
+            lockout_threshold: 3,
+            ..RateLimitConfig::default()
+        }))
+    }
+
+    fn app(limiter: Arc<RateLimiter>, route: Route) -> Router {
+        Router::new().route(
+            "/",
+            post(|body: String| async move {
+                // Senha "certa" autentica; qualquer outra é rejeitada
+                if body.contains("certa") { StatusCode::OK } else { StatusCode::UNAUTHORIZED }
+            })
+            .layer(middleware::from_fn_with_state(RouteLimiter { limiter, route }, rate_limit)),
+        )
+        .layer(middleware::from_fn(crate::error::request_context))
+    }
+
+    fn request(body: &str) -> Request {
+        Request::post("/").body(Body::from(body.to_string())).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_exceeding_bucket_returns_429_with_retry_after() {
+        let app = app(limiter(), Route::Access);
+        let body = r#"{"supplier_username":"fornecedor","password":"certa"}"#;
+
+        for _ in 0..2 {
+            assert_eq!(app.clone().oneshot(request(body)).await.unwrap().status(), StatusCode::OK);
+        }
+        let limited = app.oneshot(request(body)).await.unwrap();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[header::RETRY_AFTER], "1");
+    }
+
+    #[tokio::test]
+    async fn test_repeated_login_failures_lock_the_user() {
+        let limiter = limiter();
+        let app = app(limiter.clone(), Route::Login);
+
+        for _ in 0..3 {
+            let response = app.clone().oneshot(request(r#"{"username":"alice","password":"errada"}"#)).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        }
+
+        // Bloqueado mesmo com a senha correta
+        let locked = app.clone().oneshot(request(r#"{"username":"alice","password":"certa"}"#)).await.unwrap();
+        assert_eq!(locked.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(locked.headers()[header::RETRY_AFTER], (15 * 60).to_string());
+
+        // Outros usuários não são afetados
+        let other = app.oneshot(request(r#"{"username":"bob","password":"certa"}"#)).await.unwrap();
+        assert_eq!(other.status(), StatusCode::OK);
+
+        // Bloqueio expira
+        let later = Instant::now() + Duration::from_secs(15 * 60 + 1);
+        assert!(limiter.locked_for("alice", later).is_none());
+    }
+}
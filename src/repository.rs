@@ -0,0 +1,502 @@
+// src/repository.rs
+// Acesso a dados do núcleo (usuários, processos, compartilhamentos e acessos)
+// por traits, permitindo testar os handlers sem banco de dados.
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+use crate::database::{queries, DbPool};
+use crate::models::*;
+use crate::pagination::{matches_search, Page, ProcessListOptions, SortOrder, SortOrderExt};
+
+pub type RepoResult<T> = Result<T, sqlx::Error>;
+
+#[async_trait]
+pub trait UserRepo: Send + Sync {
+    async fn create(
+        &self,
+        username: &str,
+        stellar_public_key: &str,
+        stellar_secret_key: &str,
+        user_type: &str,
+    ) -> RepoResult<User>;
+    async fn find_by_username(&self, username: &str) -> RepoResult<Option<User>>;
+    async fn find_by_id(&self, user_id: &str) -> RepoResult<Option<User>>;
+    async fn find_by_public_key(&self, stellar_public_key: &str) -> RepoResult<Option<User>>;
+}
+
+#[async_trait]
+pub trait ProcessRepo: Send + Sync {
+    async fn create(
+        &self,
+        client_id: &str,
+        title: &str,
+        encrypted_content: &str,
+        encryption_key: &str,
+    ) -> RepoResult<Process>;
+    async fn find_by_id(&self, process_id: &str) -> RepoResult<Option<Process>>;
+    async fn list_by_client(&self, client_id: &str, options: &ProcessListOptions) -> RepoResult<Page<Process>>;
+    // Processos sem metadados ficam fora do mapa
+    async fn find_metadata(&self, process_ids: &[String]) -> RepoResult<HashMap<String, ProcessMetadata>>;
+    async fn update_metadata(&self, process_id: &str, metadata: &ProcessMetadata) -> RepoResult<()>;
+}
+
+#[async_trait]
+pub trait ShareRepo: Send + Sync {
+    // Cria o compartilhamento como 'pending'; a confirmação vem do outbox do ledger
+    async fn create(
+        &self,
+        process_id: &str,
+        supplier_public_key: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> RepoResult<ProcessShare>;
+    // Tudo ou nada; no ledger, uma transação por grupo de até batch_size compartilhamentos
+    async fn create_batch(
+        &self,
+        process_id: &str,
+        supplier_public_keys: &[String],
+        expires_at: Option<DateTime<Utc>>,
+        batch_size: usize,
+    ) -> RepoResult<Vec<ProcessShare>>;
+    async fn find(&self, process_id: &str, supplier_public_key: &str) -> RepoResult<Option<ProcessShare>>;
+    async fn find_by_id(&self, share_id: &str) -> RepoResult<Option<ProcessShare>>;
+    // Registra o aceite do fornecedor; false se o compartilhamento não está confirmado ou já foi aceito
+    async fn accept(&self, share_id: &str) -> RepoResult<bool>;
+}
+
+#[async_trait]
+pub trait AccessRepo: Send + Sync {
+    async fn create(&self, process_id: &str, supplier_id: &str) -> RepoResult<ProcessAccess>;
+    async fn list_by_client(&self, client_id: &str) -> RepoResult<Vec<ProcessAccessWithDetails>>;
+}
+
+// Implementação sobre o banco (SQLite ou PostgreSQL), delegando para database::queries
+#[derive(Clone)]
+pub struct SqlRepository {
+    pool: DbPool,
+}
+
+impl SqlRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl UserRepo for SqlRepository {
+    async fn create(
+        &self,
+        username: &str,
+        stellar_public_key: &str,
+        stellar_secret_key: &str,
+        user_type: &str,
+    ) -> RepoResult<User> {
+        queries::create_user(&self.pool, username, stellar_public_key, stellar_secret_key, user_type).await
+    }
+
+    async fn find_by_username(&self, username: &str) -> RepoResult<Option<User>> {
+        queries::find_user_by_username(&self.pool, username).await
+    }
+
+    async fn find_by_id(&self, user_id: &str) -> RepoResult<Option<User>> {
+        queries::find_user_by_id(&self.pool, user_id).await
+    }
+
+    async fn find_by_public_key(&self, stellar_public_key: &str) -> RepoResult<Option<User>> {
+        queries::find_user_by_public_key(&self.pool, stellar_public_key).await
+    }
+}
+
+#[async_trait]
+impl ProcessRepo for SqlRepository {
+    async fn create(
+        &self,
+        client_id: &str,
+        title: &str,
+        encrypted_content: &str,
+        encryption_key: &str,
+    ) -> RepoResult<Process> {
+        queries::create_process(&self.pool, client_id, title, encrypted_content, encryption_key).await
+    }
+
+    async fn find_by_id(&self, process_id: &str) -> RepoResult<Option<Process>> {
+        queries::find_process_by_id(&self.pool, process_id).await
+    }
+
+    async fn list_by_client(&self, client_id: &str, options: &ProcessListOptions) -> RepoResult<Page<Process>> {
+        queries::list_processes_by_client(&self.pool, client_id, options).await
+    }
+
+    async fn find_metadata(&self, process_ids: &[String]) -> RepoResult<HashMap<String, ProcessMetadata>> {
+        queries::find_process_metadata(&self.pool, process_ids).await
+    }
+
+    async fn update_metadata(&self, process_id: &str, metadata: &ProcessMetadata) -> RepoResult<()> {
+        queries::update_process_metadata(&self.pool, process_id, metadata).await
+    }
+}
+
+#[async_trait]
+impl ShareRepo for SqlRepository {
+    async fn create(
+        &self,
+        process_id: &str,
+        supplier_public_key: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> RepoResult<ProcessShare> {
+        queries::create_process_share(&self.pool, process_id, supplier_public_key, expires_at).await
+    }
+
+    async fn create_batch(
+        &self,
+        process_id: &str,
+        supplier_public_keys: &[String],
+        expires_at: Option<DateTime<Utc>>,
+        batch_size: usize,
+    ) -> RepoResult<Vec<ProcessShare>> {
+        queries::create_process_shares_batch(&self.pool, process_id, supplier_public_keys, expires_at, batch_size).await
+    }
+
+    async fn find(&self, process_id: &str, supplier_public_key: &str) -> RepoResult<Option<ProcessShare>> {
+        queries::find_process_share(&self.pool, process_id, supplier_public_key).await
+    }
+
+    async fn find_by_id(&self, share_id: &str) -> RepoResult<Option<ProcessShare>> {
+        queries::find_process_share_by_id(&self.pool, share_id).await
+    }
+
+    async fn accept(&self, share_id: &str) -> RepoResult<bool> {
+        queries::accept_process_share(&self.pool, share_id).await
+    }
+}
+
+#[async_trait]
+impl AccessRepo for SqlRepository {
+    async fn create(&self, process_id: &str, supplier_id: &str) -> RepoResult<ProcessAccess> {
+        queries::create_process_access(&self.pool, process_id, supplier_id).await
+    }
+
+    async fn list_by_client(&self, client_id: &str) -> RepoResult<Vec<ProcessAccessWithDetails>> {
+        queries::list_process_accesses_by_client(&self.pool, client_id).await
+    }
+}
+
+// Implementação em memória para testes; mantém as mesmas regras de unicidade do schema
+#[derive(Default)]
+pub struct InMemoryRepository {
+    users: Mutex<Vec<User>>,
+    processes: Mutex<Vec<Process>>,
+    metadata: Mutex<HashMap<String, ProcessMetadata>>,
+    shares: Mutex<Vec<ProcessShare>>,
+    accesses: Mutex<Vec<ProcessAccess>>,
+}
+
+impl InMemoryRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Simula a confirmação do ledger feita pelo worker do outbox
+    pub fn confirm_share(&self, share_id: &str, stellar_transaction_hash: &str) {
+        if let Some(share) = self.shares.lock().unwrap().iter_mut().find(|s| s.id == share_id) {
+            share.status = "confirmed".to_string();
+            share.stellar_transaction_hash = Some(stellar_transaction_hash.to_string());
+            share.confirmed_at = Some(Utc::now());
+        }
+    }
+}
+
+// Erro equivalente ao do banco, para que os handlers tratem a violação como no SqlRepository
+#[derive(Debug)]
+struct UniqueViolation(String);
+
+impl std::fmt::Display for UniqueViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for UniqueViolation {}
+
+impl sqlx::error::DatabaseError for UniqueViolation {
+    fn message(&self) -> &str {
+        &self.0
+    }
+
+    fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> sqlx::error::ErrorKind {
+        sqlx::error::ErrorKind::UniqueViolation
+    }
+}
+
+fn unique_violation(constraint: &str) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(UniqueViolation(format!("UNIQUE constraint failed: {}", constraint))))
+}
+
+// Mesmas regras de insert_process_share: vencidos saem do índice de ativos, um ativo por fornecedor
+fn insert_share(
+    shares: &mut Vec<ProcessShare>,
+    process_id: &str,
+    supplier_public_key: &str,
+    expires_at: Option<DateTime<Utc>>,
+) -> RepoResult<ProcessShare> {
+    let now = Utc::now();
+    for share in shares.iter_mut() {
+        if share.process_id == process_id
+            && share.supplier_public_key == supplier_public_key
+            && share.status == "confirmed"
+            && share.is_expired(now)
+        {
+            share.status = "expired".to_string();
+        }
+    }
+    if shares.iter().any(|s| s.process_id == process_id && s.supplier_public_key == supplier_public_key && s.is_active()) {
+        return Err(unique_violation("process_shares.process_id, process_shares.supplier_public_key"));
+    }
+
+    let share = ProcessShare {
+        id: Uuid::new_v4().to_string(),
+        process_id: process_id.to_string(),
+        supplier_public_key: supplier_public_key.to_string(),
+        stellar_transaction_hash: None,
+        status: "pending".to_string(),
+        shared_at: now,
+        confirmed_at: None,
+        revoked_at: None,
+        accepted_at: None,
+        expires_at,
+    };
+    shares.push(share.clone());
+    Ok(share)
+}
+
+#[async_trait]
+impl UserRepo for InMemoryRepository {
+    async fn create(
+        &self,
+        username: &str,
+        stellar_public_key: &str,
+        stellar_secret_key: &str,
+        user_type: &str,
+    ) -> RepoResult<User> {
+        let mut users = self.users.lock().unwrap();
+        if users.iter().any(|u| u.username == username) {
+            return Err(unique_violation("users.username"));
+        }
+        if users.iter().any(|u| u.stellar_public_key == stellar_public_key) {
+            return Err(unique_violation("users.stellar_public_key"));
+        }
+
+        let user = User {
+            id: Uuid::new_v4().to_string(),
+            username: username.to_string(),
+            stellar_public_key: stellar_public_key.to_string(),
+            stellar_secret_key: stellar_secret_key.to_string(),
+            user_type: user_type.to_string(),
+            created_at: Utc::now(),
+            locked_at: None,
+        };
+        users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn find_by_username(&self, username: &str) -> RepoResult<Option<User>> {
+        Ok(self.users.lock().unwrap().iter().find(|u| u.username == username).cloned())
+    }
+
+    async fn find_by_id(&self, user_id: &str) -> RepoResult<Option<User>> {
+        Ok(self.users.lock().unwrap().iter().find(|u| u.id == user_id).cloned())
+    }
+
+    async fn find_by_public_key(&self, stellar_public_key: &str) -> RepoResult<Option<User>> {
+        Ok(self.users.lock().unwrap().iter().find(|u| u.stellar_public_key == stellar_public_key).cloned())
+    }
+}
+
+#[async_trait]
+impl ProcessRepo for InMemoryRepository {
+    async fn create(
+        &self,
+        client_id: &str,
+        title: &str,
+        encrypted_content: &str,
+        encryption_key: &str,
+    ) -> RepoResult<Process> {
+        let process = Process {
+            id: Uuid::new_v4().to_string(),
+            client_id: client_id.to_string(),
+            title: title.to_string(),
+            encrypted_content: encrypted_content.to_string(),
+            encryption_key: encryption_key.to_string(),
+            status: "active".to_string(),
+            created_at: Utc::now(),
+        };
+        self.processes.lock().unwrap().push(process.clone());
+        Ok(process)
+    }
+
+    async fn find_by_id(&self, process_id: &str) -> RepoResult<Option<Process>> {
+        Ok(self.processes.lock().unwrap().iter().find(|p| p.id == process_id).cloned())
+    }
+
+    async fn list_by_client(&self, client_id: &str, options: &ProcessListOptions) -> RepoResult<Page<Process>> {
+        let users = self.users.lock().unwrap();
+        let shares = self.shares.lock().unwrap();
+        let metadata = self.metadata.lock().unwrap();
+
+        // Mesmos filtros e ordenação da versão SQL
+        let shared_with = |process: &Process, supplier: &str| {
+            let username_key = users.iter().find(|u| u.username == supplier).map(|u| u.stellar_public_key.as_str());
+            shares.iter().any(|s| {
+                s.process_id == process.id
+                    && s.is_active()
+                    && (s.supplier_public_key == supplier || Some(s.supplier_public_key.as_str()) == username_key)
+            })
+        };
+        let mut processes: Vec<Process> = self.processes.lock().unwrap()
+            .iter()
+            .filter(|p| p.client_id == client_id)
+            .filter(|p| options.status.as_ref().is_none_or(|status| p.status == *status))
+            .filter(|p| options.created_from.is_none_or(|from| p.created_at >= from))
+            .filter(|p| options.created_to.is_none_or(|to| p.created_at <= to))
+            .filter(|p| options.search.as_ref().is_none_or(|search| matches_search(&p.title, search)))
+            .filter(|p| options.supplier.as_ref().is_none_or(|supplier| shared_with(p, supplier)))
+            .filter(|p| {
+                let m = metadata.get(&p.id);
+                options.tag.as_ref().is_none_or(|tag| m.is_some_and(|m| m.tags.contains(tag)))
+                    && options.category.as_ref().is_none_or(|c| m.is_some_and(|m| m.category.as_ref() == Some(c)))
+                    && options.project_code.as_ref().is_none_or(|c| m.is_some_and(|m| m.project_code.as_ref() == Some(c)))
+            })
+            .cloned()
+            .collect();
+        let total = processes.len() as i64;
+
+        processes.sort_by_cached_key(|p| (options.sort_value(p), p.id.clone()));
+        if options.order == SortOrder::Desc {
+            processes.reverse();
+        }
+        if let Some(cursor) = &options.after {
+            processes.retain(|p| options.order.is_after((&options.sort_value(p), &p.id), cursor));
+        }
+        processes.truncate(options.limit as usize + 1);
+
+        Ok(Page::from_items(processes, options.limit, total, |p| options.cursor_after(p)))
+    }
+
+    async fn find_metadata(&self, process_ids: &[String]) -> RepoResult<HashMap<String, ProcessMetadata>> {
+        let metadata = self.metadata.lock().unwrap();
+        Ok(process_ids
+            .iter()
+            .filter_map(|id| metadata.get(id).map(|m| (id.clone(), m.clone())))
+            .collect())
+    }
+
+    async fn update_metadata(&self, process_id: &str, metadata: &ProcessMetadata) -> RepoResult<()> {
+        self.metadata.lock().unwrap().insert(process_id.to_string(), metadata.clone());
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ShareRepo for InMemoryRepository {
+    async fn create(
+        &self,
+        process_id: &str,
+        supplier_public_key: &str,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> RepoResult<ProcessShare> {
+        insert_share(&mut self.shares.lock().unwrap(), process_id, supplier_public_key, expires_at)
+    }
+
+    async fn create_batch(
+        &self,
+        process_id: &str,
+        supplier_public_keys: &[String],
+        expires_at: Option<DateTime<Utc>>,
+        _batch_size: usize,
+    ) -> RepoResult<Vec<ProcessShare>> {
+        let mut shares = self.shares.lock().unwrap();
+        // Trabalha numa cópia para que uma violação não deixe o lote pela metade
+        let mut staged = shares.clone();
+        let created = supplier_public_keys
+            .iter()
+            .map(|public_key| insert_share(&mut staged, process_id, public_key, expires_at))
+            .collect::<RepoResult<Vec<_>>>()?;
+        *shares = staged;
+        Ok(created)
+    }
+
+    async fn find(&self, process_id: &str, supplier_public_key: &str) -> RepoResult<Option<ProcessShare>> {
+        // Mesma regra da versão SQL: o mais recente
+        Ok(self.shares.lock().unwrap()
+            .iter()
+            .filter(|s| s.process_id == process_id && s.supplier_public_key == supplier_public_key)
+            .max_by_key(|s| s.shared_at)
+            .cloned())
+    }
+
+    async fn find_by_id(&self, share_id: &str) -> RepoResult<Option<ProcessShare>> {
+        Ok(self.shares.lock().unwrap().iter().find(|s| s.id == share_id).cloned())
+    }
+
+    async fn accept(&self, share_id: &str) -> RepoResult<bool> {
+        let mut shares = self.shares.lock().unwrap();
+        match shares.iter_mut().find(|s| s.id == share_id && s.status == "confirmed" && s.accepted_at.is_none()) {
+            Some(share) => {
+                share.accepted_at = Some(Utc::now());
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[async_trait]
+impl AccessRepo for InMemoryRepository {
+    async fn create(&self, process_id: &str, supplier_id: &str) -> RepoResult<ProcessAccess> {
+        let access = ProcessAccess {
+            id: Uuid::new_v4().to_string(),
+            process_id: process_id.to_string(),
+            supplier_id: supplier_id.to_string(),
+            accessed_at: Utc::now(),
+        };
+        self.accesses.lock().unwrap().push(access.clone());
+        Ok(access)
+    }
+
+    async fn list_by_client(&self, client_id: &str) -> RepoResult<Vec<ProcessAccessWithDetails>> {
+        let users = self.users.lock().unwrap();
+        let processes = self.processes.lock().unwrap();
+
+        let mut accesses: Vec<ProcessAccessWithDetails> = self.accesses.lock().unwrap()
+            .iter()
+            .filter_map(|access| {
+                let process = processes.iter().find(|p| p.id == access.process_id && p.client_id == client_id)?;
+                let supplier = users.iter().find(|u| u.id == access.supplier_id)?;
+                Some(ProcessAccessWithDetails {
+                    id: access.id.clone(),
+                    process_id: access.process_id.clone(),
+                    supplier_id: access.supplier_id.clone(),
+                    accessed_at: access.accessed_at,
+                    process_title: process.title.clone(),
+                    supplier_username: supplier.username.clone(),
+                })
+            })
+            .collect();
+        accesses.sort_by_key(|a| std::cmp::Reverse(a.accessed_at));
+        Ok(accesses)
+    }
+}
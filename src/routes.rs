@@ -0,0 +1,153 @@
+// src/routes.rs
+// Rotas da API e middlewares globais (compartilhado entre o binário e os testes)
+use axum::{
+    middleware,
+    routing::{delete, get, post, put},
+    Router,
+};
+use std::sync::Arc;
+use utoipa::OpenApi;
+use utoipa_redoc::{Redoc, Servable};
+
+use crate::handlers::{self, AppState};
+use crate::openapi::{self, ApiDoc};
+use crate::{error, idempotency, metrics, rate_limit, security};
+
+pub fn router(state: Arc<AppState>) -> Router {
+    let config = state.config.clone();
+
+    // Idempotency-Key nos endpoints que criam recursos
+    let idempotent = || middleware::from_fn_with_state(state.clone(), idempotency::idempotent);
+
+    // Limites por IP/usuário nas rotas sensíveis a abuso
+    let limiter = Arc::new(rate_limit::RateLimiter::new(config.rate_limit.clone()));
+    let limited = |route| {
+        middleware::from_fn_with_state(rate_limit::RouteLimiter { limiter: limiter.clone(), route }, rate_limit::rate_limit)
+    };
+
+    // Documentação: especificação em JSON e Redoc
+    let docs = Router::new()
+        .merge(Redoc::with_url("/docs", ApiDoc::openapi()))
+        .layer(security::docs_csp())
+        .route("/openapi.json", get(openapi::openapi_json));
+
+    Router::new()
+        .route("/health", get(handlers::health_check))
+        .route("/health/live", get(handlers::health_live))
+        .route("/health/ready", get(handlers::health_ready))
+        .route("/metrics", get(metrics::metrics_handler))
+        .route("/api/users/register", post(handlers::register_user).layer(idempotent()).layer(limited(rate_limit::Route::Register)))
+        .route("/api/users/login", post(handlers::login_user).layer(limited(rate_limit::Route::Login)))
+        .route("/api/users/preferences", get(handlers::get_preferences))
+        .route("/api/users/preferences", put(handlers::update_preferences))
+        .route("/api/processes", post(handlers::create_process).layer(idempotent()))
+        .route("/api/processes", get(handlers::list_processes))
+        .route("/api/processes/search", get(handlers::search_processes))
+        .route("/api/processes/:id/metadata", put(handlers::update_process_metadata))
+        .route("/api/processes/share", post(handlers::share_process).layer(idempotent()).layer(limited(rate_limit::Route::Share)))
+        .route("/api/processes/share/bulk", post(handlers::bulk_share_process).layer(idempotent()).layer(limited(rate_limit::Route::Share)))
+        .route("/api/processes/invite", post(handlers::invite_supplier).layer(limited(rate_limit::Route::Share)))
+        .route("/api/processes/access", post(handlers::access_process).layer(security::no_store()).layer(limited(rate_limit::Route::Access)))
+        .route("/api/supplier/processes", get(handlers::list_supplier_processes))
+        .route("/api/supplier/processes/:id/accept", post(handlers::accept_share))
+        .route("/api/notifications", get(handlers::get_notifications))
+        .route("/api/notifications/ws", get(handlers::notifications_ws))
+        .route("/api/notifications/stream", get(handlers::notifications_sse))
+        .route("/api/notifications/read-all", post(handlers::mark_all_notifications_read))
+        .route("/api/notifications/:id/read", post(handlers::mark_notification_read))
+        .route("/api/webhooks", post(handlers::create_webhook))
+        .route("/api/webhooks", get(handlers::list_webhooks))
+        .route("/api/webhooks/:id", delete(handlers::delete_webhook))
+        .route("/api/webhooks/:id/deliveries", get(handlers::list_webhook_deliveries))
+        .merge(docs)
+        .layer(middleware::from_fn(metrics::track_requests))
+        .layer(middleware::from_fn(error::request_context))
+        .layer(middleware::from_fn_with_state(state.clone(), security::security_headers))
+        .layer(security::cors_layer(&config))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::database::test_pool;
+    use axum::{
+        body::Body,
+        http::{Method, Request, StatusCode},
+    };
+    use std::collections::BTreeSet;
+    use tower::ServiceExt;
+
+    // (método, caminho no formato OpenAPI) declarados em router()
+    fn declared_routes() -> BTreeSet<(String, String)> {
+        let source = include_str!("routes.rs");
+        let source = &source[..source.find("#[cfg(test)]").unwrap()];
+
+        source
+            .lines()
+            .filter_map(|line| {
+                let rest = line.trim().strip_prefix(".route(\"")?;
+                let (path, rest) = rest.split_once('"')?;
+                let method = rest.trim_start_matches(", ").split('(').next()?;
+                let path = path
+                    .split('/')
+                    .map(|segment| match segment.strip_prefix(':') {
+                        Some(param) => format!("{{{}}}", param),
+                        None => segment.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/");
+                Some((method.to_string(), path))
+            })
+            .filter(|(_, path)| path != "/openapi.json")
+            .collect()
+    }
+
+    fn documented_routes() -> BTreeSet<(String, String)> {
+        let spec = serde_json::to_value(ApiDoc::openapi()).unwrap();
+        spec["paths"]
+            .as_object()
+            .unwrap()
+            .iter()
+            .flat_map(|(path, item)| item.as_object().unwrap().keys().map(move |method| (method.clone(), path.clone())))
+            .collect()
+    }
+
+    #[test]
+    fn test_every_route_is_documented() {
+        let declared = declared_routes();
+        assert!(declared.len() > 10, "rotas não encontradas em routes.rs");
+        assert_eq!(declared, documented_routes(), "rotas e especificação OpenAPI divergiram");
+    }
+
+    #[tokio::test]
+    async fn test_every_documented_route_is_served() {
+        // Horizon inacessível: /health/ready responde sem depender da rede
+        let mut config = Config::default();
+        config.stellar.horizon_url = "http://127.0.0.1:9".to_string();
+        let app = router(Arc::new(AppState::new(test_pool().await).with_config(config)));
+
+        for (method, path) in documented_routes() {
+            let uri = path.replace("{id}", "00000000-0000-0000-0000-000000000000");
+            let request = Request::builder()
+                .method(Method::from_bytes(method.to_uppercase().as_bytes()).unwrap())
+                .uri(&uri)
+                .body(Body::empty())
+                .unwrap();
+            let response = app.clone().oneshot(request).await.unwrap();
+            let status = response.status();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+            // Rota inexistente: 404/405 do roteador, sem corpo de erro da API
+            assert_ne!(status, StatusCode::METHOD_NOT_ALLOWED, "{} {} não aceita o método", method, path);
+            assert!(status != StatusCode::NOT_FOUND || !body.is_empty(), "{} {} não está no roteador", method, path);
+        }
+
+        let response = app
+            .oneshot(Request::get("/openapi.json").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,65 @@
+// src/search.rs
+// Busca textual nos processos: termos digitados → consulta FTS5 (SQLite) ou tsquery (PostgreSQL)
+
+// Limita o custo de consultas muito longas
+const MAX_TERMS: usize = 16;
+
+/// Palavras da busca em minúsculas; pontuação e operadores das linguagens de consulta são descartados
+pub fn terms(query: &str) -> Vec<String> {
+    query
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .map(str::to_lowercase)
+        .take(MAX_TERMS)
+        .collect()
+}
+
+// Todos os termos, cada um como prefixo: "nda alp" encontra "NDA Alpha"
+pub fn fts5_query(terms: &[String]) -> String {
+    terms.iter().map(|term| format!("\"{}\"*", term)).collect::<Vec<_>>().join(" ")
+}
+
+pub fn tsquery(terms: &[String]) -> String {
+    terms.iter().map(|term| format!("{}:*", term)).collect::<Vec<_>>().join(" & ")
+}
+
+// Delimitadores pedidos ao banco no lugar de <mark>; o texto é escapado antes da troca
+pub const HIGHLIGHT_START: char = '\u{2}';
+pub const HIGHLIGHT_END: char = '\u{3}';
+
+/// Escapa o texto para HTML e só então envolve os termos encontrados em <mark>
+pub fn render_highlight(raw: &str) -> String {
+    let mut html = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            HIGHLIGHT_START => html.push_str("<mark>"),
+            HIGHLIGHT_END => html.push_str("</mark>"),
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            '\'' => html.push_str("&#39;"),
+            c => html.push(c),
+        }
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terms_drop_query_syntax() {
+        let terms = terms("NDA \"Alpha\" OR fornecedor:* -(x) & !");
+        assert_eq!(terms, ["nda", "alpha", "or", "fornecedor", "x"]);
+        assert_eq!(fts5_query(&terms[..2]), "\"nda\"* \"alpha\"*");
+        assert_eq!(tsquery(&terms[..2]), "nda:* & alpha:*");
+    }
+
+    #[test]
+    fn test_highlight_escapes_user_text() {
+        let raw = format!("<b>{}Alpha{}</b> & \"x\"", HIGHLIGHT_START, HIGHLIGHT_END);
+        assert_eq!(render_highlight(&raw), "&lt;b&gt;<mark>Alpha</mark>&lt;/b&gt; &amp; &quot;x&quot;");
+    }
+}
@@ -0,0 +1,175 @@
+// src/security.rs
+// CORS restrito, headers de segurança e TLS nativo com recarga de certificado
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use axum_server::tls_rustls::RustlsConfig;
+use std::{path::PathBuf, sync::Arc, time::SystemTime};
+use tower_http::{
+    cors::{AllowOrigin, Any, CorsLayer},
+    set_header::SetResponseHeaderLayer,
+};
+
+use crate::config::{Config, TlsConfig};
+use crate::error::REQUEST_ID_HEADER;
+use crate::handlers::AppState;
+use crate::idempotency::{IDEMPOTENCY_KEY_HEADER, REPLAYED_HEADER};
+
+const HSTS_VALUE: &str = "max-age=31536000; includeSubDomains";
+// API JSON: nada deve ser carregado ou embutido a partir das respostas
+const CSP_VALUE: &str = "default-src 'none'; frame-ancestors 'none'";
+// /docs: o Redoc é carregado do CDN e usa estilos inline e web workers
+const DOCS_CSP_VALUE: &str = "default-src 'none'; script-src 'unsafe-inline' https://cdn.redoc.ly; \
+    style-src 'unsafe-inline' https://fonts.googleapis.com; font-src https://fonts.gstatic.com; \
+    img-src 'self' data: https:; connect-src 'self'; worker-src blob:; frame-ancestors 'none'";
+const CERT_RELOAD_INTERVAL_SECS: u64 = 30;
+
+/// CORS a partir da configuração; sem origens configuradas, nenhuma origem externa é aceita
+pub fn cors_layer(config: &Config) -> CorsLayer {
+    let methods: Vec<Method> = config.cors_methods.iter().filter_map(|m| m.parse().ok()).collect();
+    let layer = CorsLayer::new()
+        .allow_methods(methods)
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            header::ACCEPT_LANGUAGE,
+            HeaderName::from_static(IDEMPOTENCY_KEY_HEADER),
+            HeaderName::from_static(REQUEST_ID_HEADER),
+        ])
+        .expose_headers([
+            HeaderName::from_static(REQUEST_ID_HEADER),
+            HeaderName::from_static(REPLAYED_HEADER),
+        ]);
+
+    if config.cors_origins.iter().any(|origin| origin == "*") {
+        layer.allow_origin(Any)
+    } else {
+        let origins = config.cors_origins.iter().filter_map(|origin| origin.parse::<HeaderValue>().ok());
+        layer.allow_origin(AllowOrigin::list(origins))
+    }
+}
+
+/// Headers de segurança em todas as respostas (sem sobrescrever os definidos pela rota)
+pub async fn security_headers(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+
+    headers.entry(header::X_CONTENT_TYPE_OPTIONS).or_insert(HeaderValue::from_static("nosniff"));
+    headers.entry(header::X_FRAME_OPTIONS).or_insert(HeaderValue::from_static("DENY"));
+    headers.entry(header::REFERRER_POLICY).or_insert(HeaderValue::from_static("no-referrer"));
+    headers.entry(header::CONTENT_SECURITY_POLICY).or_insert(HeaderValue::from_static(CSP_VALUE));
+    if state.config.hsts {
+        headers.entry(header::STRICT_TRANSPORT_SECURITY).or_insert(HeaderValue::from_static(HSTS_VALUE));
+    }
+
+    response
+}
+
+/// Impede cache de respostas com conteúdo confidencial (ex.: NDA descriptografado)
+pub fn no_store() -> SetResponseHeaderLayer<HeaderValue> {
+    SetResponseHeaderLayer::overriding(header::CACHE_CONTROL, HeaderValue::from_static("no-store"))
+}
+
+/// CSP menos restritiva apenas para a documentação interativa
+pub fn docs_csp() -> SetResponseHeaderLayer<HeaderValue> {
+    SetResponseHeaderLayer::overriding(header::CONTENT_SECURITY_POLICY, HeaderValue::from_static(DOCS_CSP_VALUE))
+}
+
+/// Carrega certificado e chave PEM e recarrega quando os arquivos mudam
+pub async fn load_tls(tls: &TlsConfig) -> std::io::Result<RustlsConfig> {
+    // Provider explícito: evita ambiguidade quando mais de um está compilado
+    let _ = rustls::crypto::ring::default_provider().install_default();
+
+    let rustls_config = RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path).await?;
+    spawn_certificate_reloader(rustls_config.clone(), tls.cert_path.clone(), tls.key_path.clone());
+    Ok(rustls_config)
+}
+
+fn modified_at(paths: &[&PathBuf]) -> Option<Vec<SystemTime>> {
+    paths.iter().map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok()).collect()
+}
+
+fn spawn_certificate_reloader(rustls_config: RustlsConfig, cert_path: PathBuf, key_path: PathBuf) {
+    tokio::spawn(async move {
+        let mut last_modified = modified_at(&[&cert_path, &key_path]);
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(CERT_RELOAD_INTERVAL_SECS));
+
+        loop {
+            interval.tick().await;
+            let current = modified_at(&[&cert_path, &key_path]);
+            if current.is_none() || current == last_modified {
+                continue;
+            }
+
+            // Certificado inválido mantém o anterior em uso
+            match rustls_config.reload_from_pem_file(&cert_path, &key_path).await {
+                Ok(()) => {
+                    tracing::info!(cert_path = %cert_path.display(), "certificado TLS recarregado");
+                    last_modified = current;
+                }
+                Err(e) => tracing::warn!(error = %e, "erro ao recarregar certificado TLS, mantendo o anterior"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_pool;
+    use axum::{body::Body, middleware, routing::get, Router};
+    use tower::ServiceExt;
+
+    async fn app(config: Config) -> Router {
+        let cors = cors_layer(&config);
+        let state = Arc::new(AppState::new(test_pool().await).with_config(config));
+        Router::new()
+            .route("/api", get(|| async { "ok" }))
+            .route("/segredo", get(|| async { "ok" }).layer(no_store()))
+            .layer(middleware::from_fn_with_state(state.clone(), security_headers))
+            .layer(cors)
+            .with_state(state)
+    }
+
+    fn preflight(origin: &str) -> Request {
+        Request::builder()
+            .method(Method::OPTIONS)
+            .uri("/api")
+            .header(header::ORIGIN, origin)
+            .header(header::ACCESS_CONTROL_REQUEST_METHOD, "POST")
+            .body(Body::empty())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_cors_allows_only_configured_origins() {
+        let config = Config {
+            cors_origins: vec!["https://app.empresa.com".to_string()],
+            ..Config::default()
+        };
+        let app = app(config).await;
+
+        let allowed = app.clone().oneshot(preflight("https://app.empresa.com")).await.unwrap();
+        assert_eq!(allowed.headers()[header::ACCESS_CONTROL_ALLOW_ORIGIN], "https://app.empresa.com");
+
+        let denied = app.oneshot(preflight("https://evil.example")).await.unwrap();
+        assert!(denied.headers().get(header::ACCESS_CONTROL_ALLOW_ORIGIN).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_security_headers() {
+        let app = app(Config { hsts: true, ..Config::default() }).await;
+
+        let response = app.clone().oneshot(Request::get("/api").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.headers()[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(response.headers()[header::CONTENT_SECURITY_POLICY], CSP_VALUE);
+        assert_eq!(response.headers()[header::STRICT_TRANSPORT_SECURITY], HSTS_VALUE);
+        assert!(response.headers().get(header::CACHE_CONTROL).is_none());
+
+        let response = app.oneshot(Request::get("/segredo").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-store");
+    }
+}
@@ -6,20 +6,38 @@ use ed25519_dalek::{Keypair, PublicKey, SecretKey};
 use stellar_strkey::ed25519;
 use rand::rngs::OsRng;
 use sha2::{Sha256, Digest};
+use std::time::Instant;
+
+use crate::config::{Config, StellarConfig, StellarNetwork};
+use crate::metrics::metrics;
+
+/// Limite de operações por transação imposto pela rede Stellar
+pub const MAX_OPERATIONS_PER_TRANSACTION: usize = 100;
 
 #[derive(Debug, Clone)]
 pub struct StellarClient {
     horizon_url: String,
+    friendbot_url: Option<String>,
     client: Client,
     network_passphrase: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
 pub struct StellarAccount {
     pub public_key: String,
     pub secret_key: String,
 }
 
+// A chave secreta nunca aparece em logs
+impl std::fmt::Debug for StellarAccount {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("StellarAccount")
+            .field("public_key", &self.public_key)
+            .field("secret_key", &"***")
+            .finish()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TransactionResponse {
     pub hash: String,
@@ -68,21 +86,47 @@ pub struct EmbeddedTransactions {
 
 impl StellarClient {
     pub fn new_testnet() -> Self {
-        Self {
-            horizon_url: "https://horizon-testnet.stellar.org".to_string(),
-            client: Client::new(),
-            network_passphrase: "Test SDF Network ; September 2015".to_string(),
-        }
+        Self::from_config(&Config::default().stellar)
     }
 
     pub fn new_mainnet() -> Self {
-        Self {
+        Self::from_config(&StellarConfig {
+            network: StellarNetwork::Mainnet,
             horizon_url: "https://horizon.stellar.org".to_string(),
+            friendbot_url: None,
+        })
+    }
+
+    pub fn from_config(config: &StellarConfig) -> Self {
+        Self {
+            horizon_url: config.horizon_url.trim_end_matches('/').to_string(),
+            friendbot_url: config.friendbot_url.clone(),
             client: Client::new(),
-            network_passphrase: "Public Global Stellar Network ; September 2015".to_string(),
+            network_passphrase: config.network.passphrase().to_string(),
         }
     }
 
+    /// GET medido por endpoint (latência e falhas nas métricas)
+    async fn get(&self, endpoint: &str, url: &str) -> reqwest::Result<reqwest::Response> {
+        let started = Instant::now();
+        let result = self.client.get(url).send().await;
+
+        match &result {
+            Ok(response) if response.status().is_success() => metrics().observe_stellar(endpoint, started, None),
+            Ok(response) => {
+                let kind = format!("http_{}", response.status().as_u16());
+                metrics().observe_stellar(endpoint, started, Some(&kind));
+            }
+            Err(_) => metrics().observe_stellar(endpoint, started, Some("network")),
+        }
+        result
+    }
+
+    /// Friendbot disponível (apenas testnet)
+    pub fn can_fund_accounts(&self) -> bool {
+        self.friendbot_url.is_some()
+    }
+
     /// Gera uma nova carteira Stellar usando stellar-strkey
     pub fn generate_keypair() -> Result<StellarAccount, Box<dyn Error>> {
         // Usar OsRng diretamente (compatível com rand 0.7)
@@ -115,25 +159,23 @@ impl StellarClient {
 
     /// Financia conta na testnet usando Friendbot
     pub async fn fund_testnet_account(&self, public_key: &str) -> Result<bool, Box<dyn Error>> {
-        let url = format!("https://friendbot.stellar.org?addr={}", public_key);
+        let friendbot_url = self.friendbot_url.as_deref().ok_or("Friendbot disponível apenas na testnet")?;
+        let url = format!("{}?addr={}", friendbot_url.trim_end_matches('/'), public_key);
         
-        println!("🤖 Financiando conta testnet: {}", public_key);
+        tracing::info!(public_key, "financiando conta na testnet");
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
+        let response = self.get("friendbot", &url).await?;
 
         let success = response.status().is_success();
         
         if success {
-            println!("✅ Conta financiada com sucesso!");
+            tracing::info!(public_key, "conta financiada");
             
             // Aguardar um pouco para a transação ser processada
             tokio::time::sleep(tokio::time::Duration::from_secs(5)).await;
         } else {
             let error_text = response.text().await.unwrap_or_default();
-            println!("❌ Erro ao financiar conta: {}", error_text);
+            tracing::warn!(public_key, error = %error_text, "erro ao financiar conta");
         }
 
         Ok(success)
@@ -143,12 +185,9 @@ impl StellarClient {
     pub async fn get_account(&self, account_id: &str) -> Result<AccountResponse, Box<dyn Error>> {
         let url = format!("{}/accounts/{}", self.horizon_url, account_id);
         
-        println!("🔍 Buscando informações da conta: {}", account_id);
+        tracing::debug!(account_id, "buscando informações da conta");
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
+        let response = self.get("accounts", &url).await?;
 
         if !response.status().is_success() {
             let error_text = response.text().await.unwrap_or_default();
@@ -157,7 +196,7 @@ impl StellarClient {
 
         let account = response.json::<AccountResponse>().await?;
         
-        println!("✅ Conta encontrada - Sequence: {}", account.sequence);
+        tracing::debug!(account_id, sequence = %account.sequence, "conta encontrada");
         
         Ok(account)
     }
@@ -170,9 +209,7 @@ impl StellarClient {
         process_id: &str,
         memo: &str,
     ) -> Result<TransactionResponse, Box<dyn Error>> {
-        println!("📤 Criando transação de compartilhamento...");
-        println!("   Processo: {}", process_id);
-        println!("   Destino: {}", destination_public);
+        tracing::info!(process_id, destination = destination_public, "criando transação de compartilhamento");
         
         // Para MVP, vamos simular uma transação válida
         // Em produção, isso construiria e submeteria uma transação real
@@ -189,7 +226,7 @@ impl StellarClient {
         hasher.update(transaction_data.as_bytes());
         let hash = format!("{:x}", hasher.finalize());
         
-        println!("✅ Transação simulada criada: {}", &hash[0..16]);
+        tracing::info!(process_id, transaction_hash = %hash, "transação simulada criada");
         
         Ok(TransactionResponse {
             hash: hash[0..64].to_string(),
@@ -199,14 +236,58 @@ impl StellarClient {
         })
     }
 
+    /// Cria transação de compartilhamento em lote, até MAX_OPERATIONS_PER_TRANSACTION destinos (versão simplificada para MVP)
+    pub async fn share_process_batch_transaction(
+        &self,
+        source_secret: &str,
+        destinations: &[String],
+        process_id: &str,
+        memo: &str,
+    ) -> Result<TransactionResponse, Box<dyn Error>> {
+        if destinations.is_empty() || destinations.len() > MAX_OPERATIONS_PER_TRANSACTION {
+            return Err(format!(
+                "lote com {} operações (limite: {})",
+                destinations.len(),
+                MAX_OPERATIONS_PER_TRANSACTION
+            )
+            .into());
+        }
+        tracing::info!(process_id, operations = destinations.len(), "criando transação de compartilhamento em lote");
+
+        // Para MVP, vamos simular uma transação válida
+        // Em produção, isso construiria e submeteria uma transação real
+
+        let source_public = Self::get_public_from_secret(source_secret)?;
+
+        // Uma única consulta ao Horizon (conta de origem e número de sequência);
+        // os destinos são fornecedores cadastrados, validados antes de entrar no lote
+        let _source_account = self.get_account(&source_public).await?;
+
+        // Simular hash de transação; mesmo conjunto de destinos gera o mesmo hash
+        let mut sorted = destinations.to_vec();
+        sorted.sort();
+        let transaction_data = format!("{}:{}:{}:{}", source_public, sorted.join(","), process_id, memo);
+        let mut hasher = Sha256::new();
+        hasher.update(transaction_data.as_bytes());
+        let hash = format!("{:x}", hasher.finalize());
+
+        tracing::info!(process_id, transaction_hash = %hash, "transação em lote simulada criada");
+
+        Ok(TransactionResponse {
+            hash,
+            successful: true,
+            ledger: Some(chrono::Utc::now().timestamp() as u64),
+            result_xdr: None,
+        })
+    }
+
     /// Verifica se um usuário tem acesso a um processo via blockchain
     pub async fn verify_process_access(
         &self,
         process_id: &str,
         user_public_key: &str,
     ) -> Result<bool, Box<dyn Error>> {
-        println!("🔍 Verificando acesso ao processo: {}", process_id);
-        println!("   Usuário: {}", user_public_key);
+        tracing::debug!(process_id, public_key = user_public_key, "verificando acesso ao processo no ledger");
         
         // Buscar transações da conta do usuário
         let transactions = self.get_account_transactions(user_public_key).await?;
@@ -214,12 +295,12 @@ impl StellarClient {
         // Verificar se existe transação relacionada ao processo
         for tx in transactions {
             if self.transaction_contains_process(&tx, process_id)? {
-                println!("✅ Acesso verificado via blockchain!");
+                tracing::debug!(process_id, "acesso verificado no ledger");
                 return Ok(true);
             }
         }
         
-        println!("❌ Acesso não encontrado na blockchain");
+        tracing::debug!(process_id, "acesso não encontrado no ledger");
         Ok(false)
     }
 
@@ -227,25 +308,48 @@ impl StellarClient {
     async fn get_account_transactions(&self, account_id: &str) -> Result<Vec<TransactionRecord>, Box<dyn Error>> {
         let url = format!("{}/accounts/{}/transactions?limit=200&order=desc", self.horizon_url, account_id);
         
-        println!("🔍 Buscando transações da conta...");
+        tracing::debug!(account_id, "buscando transações da conta");
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
+        let response = self.get("account_transactions", &url).await?;
 
         if !response.status().is_success() {
-            println!("❌ Erro ao buscar transações: {}", response.status());
+            tracing::warn!(account_id, status = %response.status(), "erro ao buscar transações");
             return Ok(vec![]);
         }
 
         let data: TransactionsResponse = response.json().await?;
         
-        println!("✅ Encontradas {} transações", data.embedded.records.len());
+        tracing::debug!(account_id, total = data.embedded.records.len(), "transações encontradas");
         
         Ok(data.embedded.records)
     }
 
+    /// Procura, entre as transações recentes da conta, uma bem-sucedida com o memo informado.
+    /// Ao contrário de get_account_transactions, falhas do Horizon viram erro: quem pergunta
+    /// decide se reenvia, e "não encontrado" precisa ser uma resposta de fato
+    pub async fn find_transaction_by_memo(
+        &self,
+        account_id: &str,
+        memo: &str,
+    ) -> Result<Option<TransactionRecord>, Box<dyn Error>> {
+        let url = format!("{}/accounts/{}/transactions?limit=200&order=desc", self.horizon_url, account_id);
+
+        let response = self.get("account_transactions", &url).await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(format!("Horizon respondeu {} ao buscar transações de {}", response.status(), account_id).into());
+        }
+
+        let data: TransactionsResponse = response.json().await?;
+        Ok(data
+            .embedded
+            .records
+            .into_iter()
+            .find(|tx| tx.successful && tx.memo.as_deref() == Some(memo)))
+    }
+
     /// Verifica se transação contém referência ao processo
     fn transaction_contains_process(&self, transaction: &TransactionRecord, process_id: &str) -> Result<bool, Box<dyn Error>> {
         // Verificar memo da transação
@@ -262,29 +366,29 @@ impl StellarClient {
     pub async fn test_connection(&self) -> Result<bool, Box<dyn Error>> {
         let url = format!("{}/", self.horizon_url);
         
-        println!("🌐 Testando conexão com Stellar Testnet...");
+        tracing::debug!(horizon_url = %self.horizon_url, "testando conexão com o Horizon");
         
-        let response = self.client
-            .get(&url)
-            .send()
-            .await?;
+        let response = self.get("root", &url).await?;
 
         let success = response.status().is_success();
         
         if success {
-            println!("✅ Conexão com Stellar Testnet OK!");
+            tracing::debug!("conexão com o Horizon OK");
             
             // Mostrar informações da rede
             if let Ok(info) = response.json::<serde_json::Value>().await {
                 if let Some(network) = info.get("network_passphrase") {
-                    println!("   Network: {}", network);
+                    tracing::debug!(network = %network, "rede do Horizon");
+                    if network.as_str() != Some(self.network_passphrase.as_str()) {
+                        tracing::warn!(expected = %self.network_passphrase, found = %network, "network passphrase diferente da esperada");
+                    }
                 }
                 if let Some(version) = info.get("horizon_version") {
-                    println!("   Horizon Version: {}", version);
+                    tracing::debug!(version = %version, "versão do Horizon");
                 }
             }
         } else {
-            println!("❌ Erro na conexão: {}", response.status());
+            tracing::warn!(status = %response.status(), "erro na conexão com o Horizon");
         }
 
         Ok(success)
@@ -292,12 +396,12 @@ impl StellarClient {
 
     /// Cria conta de teste e financia automaticamente
     pub async fn create_test_account(&self) -> Result<StellarAccount, Box<dyn Error>> {
-        println!("🧪 Criando conta de teste...");
+        tracing::info!("criando conta de teste");
         
         // Gerar keypair
         let account = Self::generate_keypair()?;
         
-        println!("   Public Key: {}", account.public_key);
+        tracing::info!(public_key = %account.public_key, "par de chaves gerado");
         
         // Financiar na testnet
         self.fund_testnet_account(&account.public_key).await?;
@@ -305,11 +409,11 @@ impl StellarClient {
         // Verificar se a conta foi criada
         match self.get_account(&account.public_key).await {
             Ok(_) => {
-                println!("✅ Conta de teste criada e financiada!");
+                tracing::info!(public_key = %account.public_key, "conta de teste criada e financiada");
                 Ok(account)
             }
             Err(e) => {
-                println!("❌ Erro ao verificar conta: {}", e);
+                tracing::warn!(error = %e, "erro ao verificar conta");
                 Err(e)
             }
         }
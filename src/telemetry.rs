@@ -0,0 +1,205 @@
+// src/telemetry.rs
+// Logs estruturados (texto ou JSON) com redação de segredos e exportação OTLP opcional
+use std::io::{self, Write};
+use tracing_subscriber::{fmt::MakeWriter, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry};
+
+use crate::config::{Config, LogFormat};
+
+#[cfg(feature = "otlp")]
+const SERVICE_NAME: &str = "nda-backend";
+// Sem RUST_LOG, registra info da aplicação e apenas avisos das dependências
+const DEFAULT_FILTER: &str = "warn,nda_backend=info";
+
+// Chave secreta Stellar: "S" seguido de 55 caracteres base32
+const STELLAR_SECRET_LEN: usize = 56;
+
+/// Mascara chaves secretas Stellar em um texto já formatado.
+/// Última barreira: structs com segredos também ocultam os campos no Debug.
+pub fn redact(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let is_base32 = |b: u8| b.is_ascii_uppercase() || (b'2'..=b'7').contains(&b);
+    let is_word = |b: u8| b.is_ascii_alphanumeric();
+
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    let mut i = 0;
+    while i + STELLAR_SECRET_LEN <= bytes.len() {
+        let candidate = &bytes[i..i + STELLAR_SECRET_LEN];
+        let starts_word = i == 0 || !is_word(bytes[i - 1]);
+        let ends_word = bytes.get(i + STELLAR_SECRET_LEN).is_none_or(|b| !is_word(*b));
+        if candidate[0] == b'S' && starts_word && ends_word && candidate[1..].iter().all(|b| is_base32(*b)) {
+            out.push_str(&text[last..i]);
+            out.push_str("S***");
+            i += STELLAR_SECRET_LEN;
+            last = i;
+        } else {
+            i += 1;
+        }
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// Saída dos logs com redação aplicada a cada evento
+#[derive(Clone, Copy)]
+pub struct RedactingMakeWriter;
+
+pub struct RedactingWriter<W: Write>(W);
+
+impl<W: Write> Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // O formatador escreve cada evento de uma vez
+        self.0.write_all(redact(&String::from_utf8_lossy(buf)).as_bytes())?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl<'a> MakeWriter<'a> for RedactingMakeWriter {
+    type Writer = RedactingWriter<io::Stdout>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(io::stdout())
+    }
+}
+
+/// Mantém o exportador OTLP vivo; `shutdown` envia os spans pendentes
+pub struct Telemetry {
+    #[cfg(feature = "otlp")]
+    provider: Option<opentelemetry_sdk::trace::SdkTracerProvider>,
+}
+
+impl Telemetry {
+    pub fn shutdown(self) {
+        #[cfg(feature = "otlp")]
+        if let Some(provider) = self.provider {
+            if let Err(e) = provider.shutdown() {
+                eprintln!("Erro ao encerrar exportador OTLP: {}", e);
+            }
+        }
+    }
+}
+
+/// Inicializa o subscriber global conforme log_format e otlp_endpoint
+pub fn init(config: &Config) -> Result<Telemetry, Box<dyn std::error::Error>> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(DEFAULT_FILTER));
+
+    let fmt_layer = match config.log_format {
+        LogFormat::Pretty => tracing_subscriber::fmt::layer().with_writer(RedactingMakeWriter).boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(false)
+            .with_writer(RedactingMakeWriter)
+            .boxed(),
+    };
+    #[cfg_attr(not(feature = "otlp"), allow(unused_mut))]
+    let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = vec![fmt_layer];
+
+    #[cfg(feature = "otlp")]
+    let provider = match &config.otlp_endpoint {
+        Some(endpoint) => {
+            let (provider, layer) = otlp::layer(endpoint)?;
+            layers.push(layer);
+            Some(provider)
+        }
+        None => None,
+    };
+
+    tracing_subscriber::registry().with(layers).with(filter).try_init()?;
+
+    #[cfg(not(feature = "otlp"))]
+    if config.otlp_endpoint.is_some() {
+        tracing::warn!("otlp_endpoint configurado, mas o binário foi compilado sem a feature \"otlp\"");
+    }
+
+    Ok(Telemetry {
+        #[cfg(feature = "otlp")]
+        provider,
+    })
+}
+
+/// Associa o usuário autenticado/identificado ao span da requisição
+pub fn record_user(user_id: &str) {
+    tracing::Span::current().record("user_id", user_id);
+}
+
+/// Associa o processo ao span da requisição
+pub fn record_process(process_id: &str) {
+    tracing::Span::current().record("process_id", process_id);
+}
+
+#[cfg(feature = "otlp")]
+pub use otlp::set_remote_parent;
+
+#[cfg(feature = "otlp")]
+mod otlp {
+    use opentelemetry::{propagation::Extractor, trace::TracerProvider as _};
+    use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+    use opentelemetry_sdk::{propagation::TraceContextPropagator, trace::SdkTracerProvider, Resource};
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+    use tracing_subscriber::{Layer, Registry};
+
+    use super::SERVICE_NAME;
+
+    type BoxedLayer = Box<dyn Layer<Registry> + Send + Sync>;
+
+    pub fn layer(endpoint: &str) -> Result<(SdkTracerProvider, BoxedLayer), Box<dyn std::error::Error>> {
+        let endpoint = endpoint.trim_end_matches('/');
+        let endpoint = if endpoint.ends_with("/v1/traces") {
+            endpoint.to_string()
+        } else {
+            format!("{}/v1/traces", endpoint)
+        };
+
+        let exporter = SpanExporter::builder().with_http().with_endpoint(endpoint).build()?;
+        let provider = SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+            .build();
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+
+        let layer = tracing_opentelemetry::layer().with_tracer(provider.tracer(SERVICE_NAME)).boxed();
+        Ok((provider, layer))
+    }
+
+    struct HeaderExtractor<'a>(&'a axum::http::HeaderMap);
+
+    impl Extractor for HeaderExtractor<'_> {
+        fn get(&self, key: &str) -> Option<&str> {
+            self.0.get(key).and_then(|value| value.to_str().ok())
+        }
+
+        fn keys(&self) -> Vec<&str> {
+            self.0.keys().map(|key| key.as_str()).collect()
+        }
+    }
+
+    /// Continua o trace do chamador (header traceparent do W3C Trace Context)
+    pub fn set_remote_parent(span: &tracing::Span, headers: &axum::http::HeaderMap) {
+        let parent = opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)));
+        let _ = span.set_parent(parent);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacts_stellar_secret_keys() {
+        let secret = format!("S{}", "A".repeat(55));
+        let text = format!("chave={} pública=G{}", secret, "B".repeat(55));
+
+        let redacted = redact(&text);
+        assert_eq!(redacted, format!("chave=S*** pública=G{}", "B".repeat(55)));
+
+        // Sequências maiores ou coladas em outras palavras não são chaves
+        let longer = format!("x{}", secret);
+        assert_eq!(redact(&longer), longer);
+        assert_eq!(redact("Servidor encerrado"), "Servidor encerrado");
+    }
+}
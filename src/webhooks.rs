@@ -0,0 +1,279 @@
+// src/webhooks.rs
+use chrono::{Duration, Utc};
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use reqwest::Client;
+use serde_json::json;
+use sha2::Sha256;
+use tokio_util::sync::CancellationToken;
+
+use crate::database::{queries, DbPool};
+use crate::models::WebhookDelivery;
+
+// Tentativas antes de marcar a entrega como "failed"
+pub const MAX_ATTEMPTS: i64 = 8;
+const BASE_BACKOFF_SECS: i64 = 30;
+const MAX_BACKOFF_SECS: i64 = 3600;
+const DELIVERY_TIMEOUT_SECS: u64 = 10;
+const POLL_INTERVAL_SECS: u64 = 5;
+const BATCH_SIZE: i64 = 50;
+
+pub const SIGNATURE_HEADER: &str = "X-NDA-Signature";
+pub const TIMESTAMP_HEADER: &str = "X-NDA-Timestamp";
+pub const EVENT_HEADER: &str = "X-NDA-Event";
+pub const DELIVERY_HEADER: &str = "X-NDA-Delivery";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WebhookEvent {
+    ShareCreated,
+    ProcessAccessed,
+    ShareAccepted,
+    ShareRevoked,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 4] = [
+        WebhookEvent::ShareCreated,
+        WebhookEvent::ProcessAccessed,
+        WebhookEvent::ShareAccepted,
+        WebhookEvent::ShareRevoked,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::ShareCreated => "share.created",
+            WebhookEvent::ProcessAccessed => "process.accessed",
+            WebhookEvent::ShareAccepted => "share.accepted",
+            WebhookEvent::ShareRevoked => "share.revoked",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|event| event.as_str() == value)
+    }
+}
+
+/// Gera segredo aleatório usado para assinar os payloads de um endpoint
+pub fn generate_secret() -> String {
+    let bytes: [u8; 32] = rand::thread_rng().gen();
+    format!("whsec_{}", hex::encode(bytes))
+}
+
+/// Assinatura HMAC-SHA256 de "{timestamp}.{body}", no formato "sha256=<hex>"
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC aceita chaves de qualquer tamanho");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Intervalo exponencial até a próxima tentativa, limitado a MAX_BACKOFF_SECS
+pub fn backoff_delay(attempts: i64) -> Duration {
+    let exponent = (attempts.max(1) - 1).min(16) as u32;
+    Duration::seconds((BASE_BACKOFF_SECS * 2_i64.pow(exponent)).min(MAX_BACKOFF_SECS))
+}
+
+/// Enfileira o evento para todos os endpoints ativos do cliente inscritos nele
+pub async fn enqueue(
+    pool: &DbPool,
+    client_id: &str,
+    event: WebhookEvent,
+    data: serde_json::Value,
+) -> Result<usize, sqlx::Error> {
+    let endpoints = queries::list_webhook_endpoints_by_client(pool, client_id).await?;
+    let mut enqueued = 0;
+
+    for endpoint in endpoints {
+        let subscribed = endpoint.events.is_empty()
+            || endpoint.events.iter().any(|e| e == event.as_str());
+        if !endpoint.active || !subscribed {
+            continue;
+        }
+
+        let payload = json!({
+            "event": event.as_str(),
+            "created_at": Utc::now().to_rfc3339(),
+            "data": data,
+        })
+        .to_string();
+
+        queries::create_webhook_delivery(pool, &endpoint.id, event.as_str(), &payload).await?;
+        enqueued += 1;
+    }
+
+    Ok(enqueued)
+}
+
+/// Tenta entregar um lote de webhooks pendentes cujo horário já chegou
+pub async fn dispatch_due(pool: &DbPool, client: &Client) -> Result<usize, sqlx::Error> {
+    let deliveries = queries::list_due_webhook_deliveries(pool, BATCH_SIZE).await?;
+    let count = deliveries.len();
+
+    for delivery in deliveries {
+        deliver(pool, client, &delivery).await?;
+    }
+
+    Ok(count)
+}
+
+#[tracing::instrument(name = "webhook_delivery", skip_all, fields(delivery_id = %delivery.id))]
+async fn deliver(pool: &DbPool, client: &Client, delivery: &WebhookDelivery) -> Result<(), sqlx::Error> {
+    let endpoint = match queries::find_webhook_endpoint(pool, &delivery.endpoint_id).await? {
+        Some(endpoint) if endpoint.active => endpoint,
+        _ => {
+            return queries::record_webhook_attempt(
+                pool, &delivery.id, "failed", None, Some("Endpoint removido"), None,
+            )
+            .await;
+        }
+    };
+
+    let timestamp = Utc::now().timestamp();
+    let signature = sign_payload(&endpoint.secret, timestamp, &delivery.payload);
+
+    let result = client
+        .post(&endpoint.url)
+        .header("Content-Type", "application/json")
+        .header(EVENT_HEADER, &delivery.event_type)
+        .header(DELIVERY_HEADER, &delivery.id)
+        .header(TIMESTAMP_HEADER, timestamp.to_string())
+        .header(SIGNATURE_HEADER, signature)
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+
+    let (status_code, error) = match result {
+        Ok(response) if response.status().is_success() => {
+            tracing::info!(event = %delivery.event_type, url = %endpoint.url, "webhook entregue");
+            return queries::record_webhook_attempt(
+                pool, &delivery.id, "delivered", Some(response.status().as_u16() as i64), None, None,
+            )
+            .await;
+        }
+        Ok(response) => (
+            Some(response.status().as_u16() as i64),
+            format!("Resposta HTTP {}", response.status()),
+        ),
+        Err(e) => (None, e.to_string()),
+    };
+
+    let attempts = delivery.attempts + 1;
+    if attempts >= MAX_ATTEMPTS {
+        tracing::error!(error = %error, "webhook falhou definitivamente");
+        queries::record_webhook_attempt(pool, &delivery.id, "failed", status_code, Some(&error), None).await
+    } else {
+        let next_attempt_at = Utc::now() + backoff_delay(attempts);
+        tracing::warn!(error = %error, next_attempt_at = %next_attempt_at, "webhook falhou, nova tentativa agendada");
+        queries::record_webhook_attempt(
+            pool, &delivery.id, "pending", status_code, Some(&error), Some(next_attempt_at),
+        )
+        .await
+    }
+}
+
+/// Inicia o worker que processa a fila de entregas em segundo plano
+pub fn spawn_worker(pool: DbPool, shutdown: CancellationToken) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let client = Client::builder()
+            .timeout(std::time::Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_default();
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(POLL_INTERVAL_SECS));
+
+        // O lote em andamento termina antes de o worker parar
+        loop {
+            tokio::select! {
+                _ = shutdown.cancelled() => break,
+                _ = interval.tick() => {}
+            }
+            if let Err(e) = dispatch_due(&pool, &client).await {
+                tracing::error!(error = %e, "erro no worker de webhooks");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::test_pool;
+    use axum::{extract::State, http::{HeaderMap, StatusCode}, routing::post, Router};
+    use std::sync::{Arc, Mutex};
+
+    type Received = Arc<Mutex<Vec<(HeaderMap, String)>>>;
+
+    // Receptor HTTP local que responde com o status configurado
+    async fn spawn_receiver(status: StatusCode) -> (String, Received) {
+        let received: Received = Arc::default();
+        let app = Router::new()
+            .route("/hook", post(move |State(received): State<Received>, headers: HeaderMap, body: String| async move {
+                received.lock().unwrap().push((headers, body));
+                status
+            }))
+            .with_state(received.clone());
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        (format!("http://{}/hook", addr), received)
+    }
+
+    #[tokio::test]
+    async fn test_delivers_signed_payload() {
+        let pool = test_pool().await;
+        let (url, received) = spawn_receiver(StatusCode::OK).await;
+        let client = queries::create_user(&pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let endpoint = queries::create_webhook_endpoint(
+            &pool, &client.id, &url, "whsec_test", &["process.accessed".to_string()],
+        ).await.unwrap();
+
+        // Evento não inscrito não é enfileirado
+        assert_eq!(enqueue(&pool, &client.id, WebhookEvent::ShareCreated, json!({})).await.unwrap(), 0);
+        assert_eq!(
+            enqueue(&pool, &client.id, WebhookEvent::ProcessAccessed, json!({ "process_id": "p1" })).await.unwrap(),
+            1
+        );
+
+        assert_eq!(dispatch_due(&pool, &Client::new()).await.unwrap(), 1);
+
+        let (headers, body) = received.lock().unwrap().pop().unwrap();
+        let timestamp: i64 = headers[TIMESTAMP_HEADER].to_str().unwrap().parse().unwrap();
+        assert_eq!(headers[EVENT_HEADER], "process.accessed");
+        assert_eq!(headers[SIGNATURE_HEADER].to_str().unwrap(), sign_payload("whsec_test", timestamp, &body));
+        assert!(body.contains("\"process_id\":\"p1\""));
+
+        let log = queries::list_webhook_deliveries_by_endpoint(&pool, &endpoint.id, 10).await.unwrap();
+        assert_eq!(log[0].status, "delivered");
+        assert_eq!(log[0].attempts, 1);
+        assert_eq!(log[0].last_status_code, Some(200));
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_rescheduled_with_backoff() {
+        let pool = test_pool().await;
+        let (url, received) = spawn_receiver(StatusCode::INTERNAL_SERVER_ERROR).await;
+        let client = queries::create_user(&pool, "cliente", "GCLIENT", "SCLIENT", "client").await.unwrap();
+        let endpoint = queries::create_webhook_endpoint(&pool, &client.id, &url, "whsec_test", &[]).await.unwrap();
+
+        enqueue(&pool, &client.id, WebhookEvent::ShareCreated, json!({})).await.unwrap();
+        dispatch_due(&pool, &Client::new()).await.unwrap();
+
+        // Reagendada para o futuro: não é reenviada imediatamente
+        assert_eq!(dispatch_due(&pool, &Client::new()).await.unwrap(), 0);
+        assert_eq!(received.lock().unwrap().len(), 1);
+
+        let log = queries::list_webhook_deliveries_by_endpoint(&pool, &endpoint.id, 10).await.unwrap();
+        assert_eq!(log[0].status, "pending");
+        assert_eq!(log[0].last_status_code, Some(500));
+        assert!(log[0].next_attempt_at.unwrap() > Utc::now() + Duration::seconds(BASE_BACKOFF_SECS - 5));
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        assert_eq!(backoff_delay(1), Duration::seconds(30));
+        assert_eq!(backoff_delay(2), Duration::seconds(60));
+        assert_eq!(backoff_delay(4), Duration::seconds(240));
+        assert_eq!(backoff_delay(20), Duration::seconds(MAX_BACKOFF_SECS));
+    }
+}